@@ -0,0 +1,156 @@
+use crate::{
+    engine::common::{HistogramChannel, ImageHistogram},
+    utils::color::{linear_to_srgb_channel, srgb_to_linear_channel},
+};
+
+use super::GlobalEdit;
+
+// middle gray (18% reflectance) in sRGB, which the median luminance is brought to
+const TARGET_MEDIAN: f32 = 0.46;
+
+// proposes exposure, contrast, highlights and shadows based on the histogram of the image before any color
+// adjustments (i.e. the geometry-only image). everything else in the edit is kept as is.
+pub fn auto_tone(edit: &GlobalEdit, histogram: &ImageHistogram) -> GlobalEdit {
+    let mut result = edit.clone();
+    let bins = luma_bins(histogram);
+    if bins.is_empty() {
+        return result;
+    }
+
+    let median = histogram.median(HistogramChannel::Luma);
+    let white_point = histogram.percentile(HistogramChannel::Luma, 0.995);
+    // only most of the way, so that high-key and low-key images keep some of their character
+    let mut exposure = 0.75
+        * (srgb_to_linear_channel(TARGET_MEDIAN) / srgb_to_linear_channel(median.max(0.01))).log2();
+    // brightening is limited so that only a small part of the image is pushed into clipping,
+    // which the highlights adjustment below can still recover
+    let headroom = (1.0 / srgb_to_linear_channel(white_point.max(0.01))).log2() + 0.5;
+    exposure = exposure.min(headroom.max(0.0)).clamp(-1.5, 1.5);
+    result.exposure = (exposure * 100.0).round() / 100.0;
+
+    let exposure_scale = 2.0f32.powf(result.exposure);
+    let exposed =
+        |v: f32| linear_to_srgb_channel(srgb_to_linear_channel(v) * exposure_scale).min(1.0);
+
+    // stretch (or compress) the tonal range so that it roughly covers the full range
+    let black_point = exposed(histogram.percentile(HistogramChannel::Luma, 0.005));
+    let spread = exposed(white_point) - black_point;
+    result.contrast = ((0.9 - spread) * 100.0).clamp(-25.0, 40.0).round();
+
+    let mut near_white = 0.0;
+    let mut near_black = 0.0;
+    for (value, fraction) in bins.iter() {
+        let v = exposed(*value);
+        if v > 0.9 {
+            near_white += fraction;
+        } else if v < 0.1 {
+            near_black += fraction;
+        }
+    }
+    result.highlights = (0.0 - (near_white * 300.0).min(60.0)).round();
+    result.shadows = (near_black * 300.0).min(60.0).round();
+
+    result
+}
+
+// the (sRGB) luminance at the center of each bin, and the fraction of pixels in that bin
+fn luma_bins(histogram: &ImageHistogram) -> Vec<(f32, f32)> {
+    let total = histogram.num_pixels(HistogramChannel::Luma);
+    if histogram.num_bins < 2 || total == 0 {
+        return Vec::new();
+    }
+    histogram
+        .bins(HistogramChannel::Luma)
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (histogram.bin_value(i), *c as f32 / total as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a histogram of 256 bins, where each pixel's luminance is one of the given (sRGB) values
+    fn luma_histogram(values: &[(f32, u32)]) -> ImageHistogram {
+        let num_bins = ImageHistogram::max_bins();
+        let counts: Vec<(usize, u32)> = values
+            .iter()
+            .map(|(value, count)| {
+                let bin = (value * (num_bins - 1) as f32) as usize;
+                (bin.min(num_bins - 1), *count)
+            })
+            .collect();
+        ImageHistogram::from_luma_counts(num_bins as u32, &counts)
+    }
+
+    // evenly spread luminances between low and high
+    fn spread(low: f32, high: f32) -> Vec<(f32, u32)> {
+        (0..=100)
+            .map(|i| (low + (high - low) * i as f32 / 100.0, 100))
+            .collect()
+    }
+
+    #[test]
+    fn empty_histogram_keeps_edit() {
+        let mut edit = GlobalEdit::new();
+        edit.exposure = 0.5;
+        assert!(auto_tone(&edit, &luma_histogram(&[])) == edit);
+    }
+
+    #[test]
+    fn dark_image_is_brightened() {
+        let result = auto_tone(&GlobalEdit::new(), &luma_histogram(&spread(0.05, 0.3)));
+        assert!(result.exposure > 0.0, "{}", result.exposure);
+        assert!(result.exposure <= 1.5);
+    }
+
+    #[test]
+    fn bright_image_is_darkened() {
+        let result = auto_tone(&GlobalEdit::new(), &luma_histogram(&spread(0.7, 0.95)));
+        assert!(result.exposure < 0.0, "{}", result.exposure);
+        assert!(result.exposure >= -1.5);
+    }
+
+    #[test]
+    fn well_exposed_image_is_mostly_kept() {
+        let result = auto_tone(&GlobalEdit::new(), &luma_histogram(&spread(0.0, 0.92)));
+        assert!(result.exposure.abs() < 0.25, "{}", result.exposure);
+        assert!(result.contrast.abs() <= 10.0, "{}", result.contrast);
+    }
+
+    #[test]
+    fn narrow_tonal_range_adds_contrast() {
+        let result = auto_tone(&GlobalEdit::new(), &luma_histogram(&spread(0.35, 0.6)));
+        assert!(result.contrast > 0.0, "{}", result.contrast);
+        assert!(result.contrast <= 40.0);
+    }
+
+    #[test]
+    fn near_black_and_near_white_recover_shadows_and_highlights() {
+        let mut values = spread(0.3, 0.6);
+        values.push((0.02, 2000));
+        values.push((0.98, 2000));
+        let result = auto_tone(&GlobalEdit::new(), &luma_histogram(&values));
+        assert!(
+            result.highlights < 0.0 && result.highlights >= -60.0,
+            "{}",
+            result.highlights
+        );
+        assert!(
+            result.shadows > 0.0 && result.shadows <= 60.0,
+            "{}",
+            result.shadows
+        );
+    }
+
+    #[test]
+    fn other_adjustments_are_kept() {
+        let mut edit = GlobalEdit::new();
+        edit.temperature = 20.0;
+        edit.saturation = -10.0;
+        let result = auto_tone(&edit, &luma_histogram(&spread(0.05, 0.3)));
+        assert_eq!(result.temperature, 20.0);
+        assert_eq!(result.saturation, -10.0);
+    }
+}
@@ -1,16 +1,30 @@
-use crate::ir::{ColorMixGroup, Frame, GlobalMask, Mask, MaskPrimitive, MaskTerm, Vignette};
+use crate::ir::{
+    BlendMode, ColorMixGroup, Frame, GlobalMask, Mask, MaskPrimitive, MaskTerm, MaskTermMode, RedEyeCorrection, RetouchSpot,
+    Vignette,
+};
 
-use crate::utils::rectangle::Rectangle;
+use crate::utils::{math::mix, rectangle::Rectangle, spline::EvaluatedSpline};
 
 use serde;
 
+// fields missing from persisted edits take their trivial values
 #[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default = "Edit::trivial")]
 pub struct Edit {
     pub resize_factor: Option<f32>,
     pub rotation_degrees: Option<f32>,
     pub crop_rect: Option<Rectangle>,
+    // edits saved before retouching existed don't have this field
+    #[serde(default)]
+    pub retouch_spots: Vec<RetouchSpot>,
+    #[serde(default)]
+    pub red_eye_corrections: Vec<RedEyeCorrection>,
     pub masked_edits: Vec<MaskedEdit>,
     pub framing: Option<Frame>,
+    // the last preset or pasted settings, whose amount can still be adjusted.
+    // edits saved before this existed don't have the field.
+    #[serde(default)]
+    pub applied_look: Option<AppliedLook>,
 }
 
 impl Edit {
@@ -19,20 +33,31 @@ impl Edit {
             resize_factor: None,
             rotation_degrees: None,
             crop_rect: None,
+            retouch_spots: Vec::new(),
+            red_eye_corrections: Vec::new(),
             masked_edits: vec![MaskedEdit::new(
                 Mask {
                     terms: vec![MaskTerm {
                         primitive: MaskPrimitive::Global(GlobalMask::default()),
                         inverted: false,
-                        subtracted: false,
+                        mode: MaskTermMode::Add,
                     }],
+                    refine_edges: 0.0,
                 },
                 GlobalEdit::new(),
                 "Global".to_string(),
             )],
             framing: None,
+            applied_look: None,
         }
     }
+
+    // the masked edit that applies to the whole image, which is usually the first one
+    pub fn global_masked_edit_index(&self) -> Option<usize> {
+        self.masked_edits
+            .iter()
+            .position(|masked_edit| masked_edit.mask.is_singe_global())
+    }
 }
 
 #[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
@@ -40,15 +65,38 @@ pub struct MaskedEdit {
     pub mask: Mask,
     pub edit: GlobalEdit,
     pub name: String,
+    // edits saved before these existed don't have them
+    #[serde(default = "MaskedEdit::default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "MaskedEdit::default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub blend_mode: BlendMode,
 }
 
 impl MaskedEdit {
     pub fn new(mask: Mask, edit: GlobalEdit, name: String) -> Self {
-        Self { mask, edit, name }
+        Self {
+            mask,
+            edit,
+            name,
+            enabled: Self::default_enabled(),
+            opacity: Self::default_opacity(),
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    fn default_enabled() -> bool {
+        true
+    }
+
+    fn default_opacity() -> f32 {
+        100.0
     }
 }
 
 #[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default = "GlobalEdit::new")]
 pub struct GlobalEdit {
     pub exposure: f32,
     pub contrast: f32,
@@ -99,4 +147,278 @@ impl GlobalEdit {
     pub fn initial_control_points() -> Vec<(f32, f32)> {
         vec![(0.0, 0.0), (1.0, 1.0)]
     }
+
+    // interpolates every numeric field, where t = 0 gives `a` and t = 1 gives `b`
+    pub fn mix(a: &GlobalEdit, b: &GlobalEdit, t: f32) -> GlobalEdit {
+        if t <= 0.0 {
+            return a.clone();
+        }
+        if t >= 1.0 {
+            return b.clone();
+        }
+        let mut color_mixer_edits = a.color_mixer_edits;
+        for (i, group) in color_mixer_edits.iter_mut().enumerate() {
+            let group_b = &b.color_mixer_edits[i];
+            group.hue = mix(group.hue, group_b.hue, t);
+            group.saturation = mix(group.saturation, group_b.saturation, t);
+            group.lightness = mix(group.lightness, group_b.lightness, t);
+        }
+        GlobalEdit {
+            exposure: mix(a.exposure, b.exposure, t),
+            contrast: mix(a.contrast, b.contrast, t),
+            highlights: mix(a.highlights, b.highlights, t),
+            shadows: mix(a.shadows, b.shadows, t),
+
+            curve_control_points_all: Self::mix_curve(
+                &a.curve_control_points_all,
+                &b.curve_control_points_all,
+                t,
+            ),
+            curve_control_points_r: Self::mix_curve(
+                &a.curve_control_points_r,
+                &b.curve_control_points_r,
+                t,
+            ),
+            curve_control_points_g: Self::mix_curve(
+                &a.curve_control_points_g,
+                &b.curve_control_points_g,
+                t,
+            ),
+            curve_control_points_b: Self::mix_curve(
+                &a.curve_control_points_b,
+                &b.curve_control_points_b,
+                t,
+            ),
+
+            temperature: mix(a.temperature, b.temperature, t),
+            tint: mix(a.tint, b.tint, t),
+            vibrance: mix(a.vibrance, b.vibrance, t),
+            saturation: mix(a.saturation, b.saturation, t),
+
+            color_mixer_edits,
+
+            dehaze: mix(a.dehaze, b.dehaze, t),
+            vignette: Vignette {
+                vignette: mix(a.vignette.vignette, b.vignette.vignette, t),
+                midpoint: mix(a.vignette.midpoint, b.vignette.midpoint, t),
+                feather: mix(a.vignette.feather, b.vignette.feather, t),
+                roundness: mix(a.vignette.roundness, b.vignette.roundness, t),
+            },
+        }
+    }
+
+    fn mix_curve(a: &Vec<(f32, f32)>, b: &Vec<(f32, f32)>, t: f32) -> Vec<(f32, f32)> {
+        let same_xs = a.len() == b.len() && a.iter().zip(b.iter()).all(|(p, q)| p.0 == q.0);
+        if same_xs {
+            return a
+                .iter()
+                .zip(b.iter())
+                .map(|(p, q)| (p.0, mix(p.1, q.1, t)))
+                .collect();
+        }
+        // otherwise, evaluate both curves at the control points of either, and interpolate those
+        let num_steps = 255u32;
+        let evaluated_a = EvaluatedSpline::from_control_points(a, 1.0, num_steps);
+        let evaluated_b = EvaluatedSpline::from_control_points(b, 1.0, num_steps);
+        let mut xs: Vec<f32> = a.iter().chain(b.iter()).map(|p| p.0).collect();
+        xs.sort_by(|x0, x1| x0.partial_cmp(x1).unwrap());
+        xs.dedup();
+        xs.iter()
+            .map(|x| {
+                let i = (x * num_steps as f32).round() as usize;
+                (*x, mix(evaluated_a.y_vals[i], evaluated_b.y_vals[i], t))
+            })
+            .collect()
+    }
+}
+
+// a preset or pasted settings applied at an adjustable amount, which interpolates the global edit.
+// masked edits carried by the look replace the existing ones, so they are always applied in full.
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AppliedLook {
+    // the global edit before the look was applied, and with the look fully applied
+    pub base: GlobalEdit,
+    pub target: GlobalEdit,
+    // from 0 to 100
+    pub amount: f32,
+    // whether the look also changed the (non-global) masked edits, which the amount doesn't apply to
+    #[serde(default)]
+    pub includes_masked_edits: bool,
+}
+
+impl AppliedLook {
+    // `fully_applied` is the result of applying a look on top of `original` at full strength
+    pub fn apply(original: &Edit, fully_applied: &Edit, amount: f32) -> Edit {
+        let mut result = fully_applied.clone();
+        let Some(target_index) = result.global_masked_edit_index() else {
+            return result;
+        };
+        let base = match original.global_masked_edit_index() {
+            Some(i) => original.masked_edits[i].edit.clone(),
+            None => GlobalEdit::new(),
+        };
+        let target = result.masked_edits[target_index].edit.clone();
+        if base == target {
+            // nothing to interpolate, e.g. when only geometry or masks were applied
+            return result;
+        }
+        let look = AppliedLook {
+            base,
+            target,
+            amount,
+            includes_masked_edits: Self::non_global_masked_edits(original)
+                != Self::non_global_masked_edits(fully_applied),
+        };
+        result.masked_edits[target_index].edit = look.global_edit();
+        result.applied_look = Some(look);
+        result
+    }
+
+    fn non_global_masked_edits(edit: &Edit) -> Vec<&MaskedEdit> {
+        let global_index = edit.global_masked_edit_index();
+        (0..edit.masked_edits.len())
+            .filter(|i| Some(*i) != global_index)
+            .map(|i| &edit.masked_edits[i])
+            .collect()
+    }
+
+    pub fn global_edit(&self) -> GlobalEdit {
+        GlobalEdit::mix(&self.base, &self.target, self.amount / 100.0)
+    }
+
+    // the look can no longer be adjusted once the global edit has been changed by other means
+    pub fn is_adjustable(edit: &Edit) -> bool {
+        if let (Some(look), Some(i)) = (&edit.applied_look, edit.global_masked_edit_index()) {
+            return edit.masked_edits[i].edit == look.global_edit();
+        }
+        false
+    }
+
+    pub fn with_amount(edit: &Edit, amount: f32) -> Edit {
+        let mut result = edit.clone();
+        if let (Some(look), Some(i)) = (&mut result.applied_look, edit.global_masked_edit_index()) {
+            look.amount = amount;
+            result.masked_edits[i].edit = look.global_edit();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::RadialGradientMask;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.01, "{} != {}", a, b);
+    }
+
+    fn edit_with_global(global: GlobalEdit) -> Edit {
+        let mut edit = Edit::trivial();
+        edit.masked_edits[0].edit = global;
+        edit
+    }
+
+    fn bright() -> GlobalEdit {
+        GlobalEdit {
+            exposure: 1.0,
+            contrast: 20.0,
+            ..GlobalEdit::new()
+        }
+    }
+
+    #[test]
+    fn mix_clamps_t() {
+        let a = GlobalEdit::new();
+        let b = bright();
+        assert!(GlobalEdit::mix(&a, &b, -0.5) == a);
+        assert!(GlobalEdit::mix(&a, &b, 0.0) == a);
+        assert!(GlobalEdit::mix(&a, &b, 1.0) == b);
+        assert!(GlobalEdit::mix(&a, &b, 1.5) == b);
+
+        let half = GlobalEdit::mix(&a, &b, 0.5);
+        assert_close(half.exposure, 0.5);
+        assert_close(half.contrast, 10.0);
+        assert!(half.curve_control_points_all == GlobalEdit::initial_control_points());
+    }
+
+    #[test]
+    fn mix_curve_with_same_xs_interpolates_control_points() {
+        let a = vec![(0.0, 0.0), (0.5, 0.5), (1.0, 1.0)];
+        let b = vec![(0.0, 0.2), (0.5, 0.7), (1.0, 1.0)];
+        let mixed = GlobalEdit::mix_curve(&a, &b, 0.5);
+        assert_eq!(mixed.len(), 3);
+        assert_close(mixed[0].1, 0.1);
+        assert_close(mixed[1].1, 0.6);
+        assert_close(mixed[2].1, 1.0);
+    }
+
+    #[test]
+    fn mix_curve_with_different_xs_uses_union_of_control_points() {
+        let a = vec![(0.0, 0.0), (0.5, 0.5), (1.0, 1.0)];
+        let b = vec![(0.0, 0.0), (0.25, 0.5), (1.0, 1.0)];
+        let mixed = GlobalEdit::mix_curve(&a, &b, 0.5);
+        let xs: Vec<f32> = mixed.iter().map(|p| p.0).collect();
+        assert_eq!(xs, vec![0.0, 0.25, 0.5, 1.0]);
+        // both curves are evaluated at every control point, so the mix is only exact at those
+        let evaluated_b = EvaluatedSpline::from_control_points(&b, 1.0, 255);
+        assert_close(mixed[1].1, mix(0.25, 0.5, 0.5));
+        assert_close(mixed[2].1, mix(0.5, evaluated_b.y_vals[128], 0.5));
+        assert_close(mixed[0].1, 0.0);
+        assert_close(mixed[3].1, 1.0);
+    }
+
+    #[test]
+    fn applied_look_interpolates_global_edit() {
+        let original = edit_with_global(GlobalEdit::new());
+        let fully_applied = edit_with_global(bright());
+
+        let applied = AppliedLook::apply(&original, &fully_applied, 50.0);
+        assert_close(applied.masked_edits[0].edit.exposure, 0.5);
+        assert!(AppliedLook::is_adjustable(&applied));
+        assert!(!applied.applied_look.as_ref().unwrap().includes_masked_edits);
+
+        let full = AppliedLook::with_amount(&applied, 100.0);
+        assert!(full.masked_edits[0].edit == bright());
+        let none = AppliedLook::with_amount(&applied, 0.0);
+        assert!(none.masked_edits[0].edit == GlobalEdit::new());
+    }
+
+    #[test]
+    fn applied_look_without_global_changes_is_not_adjustable() {
+        let original = edit_with_global(bright());
+        let applied = AppliedLook::apply(&original, &original, 50.0);
+        assert!(applied.applied_look.is_none());
+        assert!(!AppliedLook::is_adjustable(&applied));
+    }
+
+    #[test]
+    fn applied_look_is_not_adjustable_after_manual_edit() {
+        let original = edit_with_global(GlobalEdit::new());
+        let mut applied = AppliedLook::apply(&original, &edit_with_global(bright()), 50.0);
+        applied.masked_edits[0].edit.saturation = 10.0;
+        assert!(!AppliedLook::is_adjustable(&applied));
+    }
+
+    #[test]
+    fn applied_look_notes_masked_edits() {
+        let original = edit_with_global(GlobalEdit::new());
+        let mut fully_applied = edit_with_global(bright());
+        fully_applied.masked_edits.push(MaskedEdit::new(
+            Mask {
+                terms: vec![MaskTerm {
+                    primitive: MaskPrimitive::RadialGradient(RadialGradientMask::default(1.5)),
+                    inverted: false,
+                    mode: MaskTermMode::Add,
+                }],
+                refine_edges: 0.0,
+            },
+            bright(),
+            "Radial".to_string(),
+        ));
+        let applied = AppliedLook::apply(&original, &fully_applied, 30.0);
+        assert!(applied.applied_look.as_ref().unwrap().includes_masked_edits);
+        // masked edits carried by the look are not interpolated
+        assert!(applied.masked_edits[1] == fully_applied.masked_edits[1]);
+    }
 }
@@ -0,0 +1,345 @@
+use serde_json::{Map, Value};
+
+use super::{Edit, GlobalEdit};
+
+#[derive(Clone, PartialEq)]
+pub struct EditHistoryEntry {
+    pub edit: Edit,
+    // what changed compared to the previous entry
+    pub description: String,
+}
+
+impl EditHistoryEntry {
+    pub fn new(edit: Edit, description: String) -> Self {
+        Self { edit, description }
+    }
+
+    // the first entry of a history
+    pub fn initial(edit: Edit) -> Self {
+        Self::new(edit, "Open".to_owned())
+    }
+}
+
+pub type EditHistory = Vec<EditHistoryEntry>;
+
+// older entries are dropped once the history gets longer than this
+pub const MAX_EDIT_HISTORY_LENGTH: usize = 100;
+
+// the edit history of an image as it is persisted next to its edit,
+// where each edit is stored (and migrated) in the same way as a persisted edit.
+pub struct PersistedEditHistory {
+    pub entries: EditHistory,
+    pub current_edit_index: usize,
+}
+
+impl PersistedEditHistory {
+    pub fn to_json_string(&self) -> Result<String, String> {
+        let mut entries = Vec::new();
+        for entry in self.entries.iter() {
+            let mut map = Map::new();
+            map.insert(
+                "description".to_owned(),
+                Value::String(entry.description.clone()),
+            );
+            map.insert("edit".to_owned(), entry.edit.to_persisted_json_value()?);
+            entries.push(Value::Object(map));
+        }
+        let mut map = Map::new();
+        map.insert("entries".to_owned(), Value::Array(entries));
+        map.insert(
+            "current_edit_index".to_owned(),
+            Value::from(self.current_edit_index),
+        );
+        serde_json::to_string(&Value::Object(map)).map_err(|e| e.to_string())
+    }
+
+    pub fn from_json_str(json_str: &str) -> Result<Self, String> {
+        let value = serde_json::from_str::<Value>(json_str).map_err(|e| e.to_string())?;
+        let Some(Value::Array(entry_values)) = value.get("entries") else {
+            return Err("history has no entries".to_owned());
+        };
+        let mut entries = Vec::new();
+        for entry_value in entry_values.iter() {
+            let Some(edit_value) = entry_value.get("edit") else {
+                return Err("history entry has no edit".to_owned());
+            };
+            let description = match entry_value.get("description") {
+                Some(Value::String(description)) => description.clone(),
+                _ => String::new(),
+            };
+            let edit = Edit::from_persisted_json_value(edit_value.clone())?;
+            entries.push(EditHistoryEntry::new(edit, description));
+        }
+        let Some(current_edit_index) = value.get("current_edit_index").and_then(|i| i.as_u64())
+        else {
+            return Err("history has no current edit index".to_owned());
+        };
+        let current_edit_index = current_edit_index as usize;
+        if current_edit_index >= entries.len() {
+            return Err("history's current edit index is out of range".to_owned());
+        }
+        Ok(Self {
+            entries,
+            current_edit_index,
+        })
+    }
+
+    // adds an edit that the history doesn't know about as the new current entry, like a commit would.
+    // e.g. the persisted edit, when the app didn't exit cleanly after it was written (the history is written later)
+    pub fn push_recovered(&mut self, edit: Edit) {
+        self.entries.truncate(self.current_edit_index + 1);
+        self.entries
+            .push(EditHistoryEntry::new(edit, "Recovered".to_owned()));
+        if self.entries.len() > MAX_EDIT_HISTORY_LENGTH {
+            self.entries.remove(0);
+        }
+        self.current_edit_index = self.entries.len() - 1;
+    }
+}
+
+// a short human-readable summary of the changes from `before` to `after`, e.g. "Exposure +0.50"
+pub fn describe_edit_change(before: &Edit, after: &Edit) -> String {
+    if let Some(ref look) = after.applied_look {
+        if before.applied_look.as_ref() != Some(look) {
+            let is_same_look = before
+                .applied_look
+                .as_ref()
+                .is_some_and(|l| l.base == look.base && l.target == look.target);
+            if is_same_look {
+                return format!("Look Amount {:.0}%", look.amount);
+            }
+            return "Apply Look".to_owned();
+        }
+    }
+
+    let mut changes = Vec::new();
+    if before.rotation_degrees != after.rotation_degrees {
+        changes.push(format!(
+            "Rotate {:.1}°",
+            after.rotation_degrees.unwrap_or(0.0)
+        ));
+    }
+    if before.crop_rect != after.crop_rect {
+        changes.push("Crop".to_owned());
+    }
+    if before.retouch_spots != after.retouch_spots {
+        changes.push(describe_list_change(
+            "Retouch Spot",
+            before.retouch_spots.len(),
+            after.retouch_spots.len(),
+        ));
+    }
+    if before.red_eye_corrections != after.red_eye_corrections {
+        changes.push(describe_list_change(
+            "Red Eye Correction",
+            before.red_eye_corrections.len(),
+            after.red_eye_corrections.len(),
+        ));
+    }
+    if before.framing != after.framing {
+        changes.push("Framing".to_owned());
+    }
+
+    if before.masked_edits.len() != after.masked_edits.len() {
+        changes.push(describe_list_change(
+            "Mask",
+            before.masked_edits.len(),
+            after.masked_edits.len(),
+        ));
+    } else {
+        for (old, new) in before.masked_edits.iter().zip(after.masked_edits.iter()) {
+            // changes of the global edit are described without mentioning the mask
+            let suffix = if new.mask.is_singe_global() {
+                String::new()
+            } else {
+                format!(" ({})", new.name)
+            };
+            if old.mask != new.mask {
+                changes.push(format!("Edit Mask{}", suffix));
+            }
+            if old.name != new.name {
+                changes.push(format!("Rename Mask{}", suffix));
+            }
+            if old.enabled != new.enabled {
+                let action = if new.enabled { "Enable" } else { "Disable" };
+                changes.push(format!("{} Mask{}", action, suffix));
+            }
+            if old.opacity != new.opacity {
+                changes.push(format!("Opacity {:.0}%{}", new.opacity, suffix));
+            }
+            if old.blend_mode != new.blend_mode {
+                changes.push(format!("Blend Mode{}", suffix));
+            }
+            for change in describe_global_edit_change(&old.edit, &new.edit) {
+                changes.push(change + suffix.as_str());
+            }
+        }
+    }
+
+    match changes.len() {
+        0 => "Edit".to_owned(),
+        1 | 2 => changes.join(", "),
+        n => format!("{}, {} and {} more", changes[0], changes[1], n - 2),
+    }
+}
+
+fn describe_list_change(item_name: &str, before_len: usize, after_len: usize) -> String {
+    if after_len > before_len {
+        format!("Add {}", item_name)
+    } else if after_len < before_len {
+        format!("Remove {}", item_name)
+    } else {
+        format!("Edit {}", item_name)
+    }
+}
+
+fn describe_global_edit_change(before: &GlobalEdit, after: &GlobalEdit) -> Vec<String> {
+    let mut changes = Vec::new();
+    let scalars = [
+        ("Exposure", before.exposure, after.exposure, 2),
+        ("Contrast", before.contrast, after.contrast, 0),
+        ("Highlights", before.highlights, after.highlights, 0),
+        ("Shadows", before.shadows, after.shadows, 0),
+        ("Temperature", before.temperature, after.temperature, 0),
+        ("Tint", before.tint, after.tint, 0),
+        ("Vibrance", before.vibrance, after.vibrance, 0),
+        ("Saturation", before.saturation, after.saturation, 0),
+        ("Dehaze", before.dehaze, after.dehaze, 0),
+        (
+            "Vignette",
+            before.vignette.vignette,
+            after.vignette.vignette,
+            0,
+        ),
+        (
+            "Vignette Midpoint",
+            before.vignette.midpoint,
+            after.vignette.midpoint,
+            0,
+        ),
+        (
+            "Vignette Feather",
+            before.vignette.feather,
+            after.vignette.feather,
+            0,
+        ),
+        (
+            "Vignette Roundness",
+            before.vignette.roundness,
+            after.vignette.roundness,
+            0,
+        ),
+    ];
+    for (name, b, a, precision) in scalars {
+        if b != a {
+            changes.push(format!("{} {:+.*}", name, precision, a));
+        }
+    }
+    let curves = [
+        (
+            "Curve",
+            &before.curve_control_points_all,
+            &after.curve_control_points_all,
+        ),
+        (
+            "Red Curve",
+            &before.curve_control_points_r,
+            &after.curve_control_points_r,
+        ),
+        (
+            "Green Curve",
+            &before.curve_control_points_g,
+            &after.curve_control_points_g,
+        ),
+        (
+            "Blue Curve",
+            &before.curve_control_points_b,
+            &after.curve_control_points_b,
+        ),
+    ];
+    for (name, b, a) in curves {
+        if b != a {
+            changes.push(name.to_owned());
+        }
+    }
+    if before.color_mixer_edits != after.color_mixer_edits {
+        changes.push("Color Mixer".to_owned());
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovered_edit_becomes_current_entry() {
+        let edit = |exposure: f32| {
+            let mut edit = Edit::trivial();
+            edit.masked_edits[0].edit.exposure = exposure;
+            edit
+        };
+        let mut history = PersistedEditHistory {
+            entries: vec![
+                EditHistoryEntry::initial(edit(0.0)),
+                EditHistoryEntry::new(edit(1.0), "Exposure +1.00".to_owned()),
+                EditHistoryEntry::new(edit(2.0), "Exposure +2.00".to_owned()),
+            ],
+            current_edit_index: 1,
+        };
+        history.push_recovered(edit(3.0));
+        // the entries that could have been redone are replaced, like when committing
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(history.current_edit_index, 2);
+        assert!(history.entries[1].edit == edit(1.0));
+        assert!(history.entries[2].edit == edit(3.0));
+        assert_eq!(history.entries[2].description, "Recovered");
+    }
+
+    #[test]
+    fn describes_opacity_as_new_percentage() {
+        let before = Edit::trivial();
+        let mut after = before.clone();
+        after.masked_edits[0].opacity = 40.0;
+        assert_eq!(describe_edit_change(&before, &after), "Opacity 40%");
+        assert_eq!(describe_edit_change(&after, &before), "Opacity 100%");
+    }
+
+    #[test]
+    fn describes_global_adjustments() {
+        let before = Edit::trivial();
+        let mut after = before.clone();
+        after.masked_edits[0].edit.exposure = 0.5;
+        assert_eq!(describe_edit_change(&before, &after), "Exposure +0.50");
+
+        after.masked_edits[0].edit.contrast = -12.0;
+        assert_eq!(
+            describe_edit_change(&before, &after),
+            "Exposure +0.50, Contrast -12"
+        );
+
+        after.masked_edits[0].edit.saturation = 5.0;
+        after.rotation_degrees = Some(1.5);
+        assert_eq!(
+            describe_edit_change(&before, &after),
+            "Rotate 1.5°, Exposure +0.50 and 2 more"
+        );
+    }
+
+    #[test]
+    fn describes_masked_edits_by_name() {
+        let mut before = Edit::trivial();
+        let mut masked_edit = before.masked_edits[0].clone();
+        masked_edit.mask.terms[0].inverted = true;
+        masked_edit.name = "Sky".to_owned();
+        before.masked_edits.push(masked_edit);
+
+        let mut after = before.clone();
+        after.masked_edits[1].enabled = false;
+        assert_eq!(describe_edit_change(&before, &after), "Disable Mask (Sky)");
+
+        after.masked_edits.pop();
+        assert_eq!(describe_edit_change(&before, &after), "Remove Mask");
+        assert_eq!(describe_edit_change(&after, &after), "Edit");
+    }
+}
@@ -0,0 +1,248 @@
+use serde_json::{Map, Value};
+
+use crate::ir::{MaskTermMode, Vignette};
+use crate::versioning::Version;
+
+use super::Edit;
+
+// persisted edits are written as the serialized `Edit`, with an additional "version" field.
+// when reading an edit saved by an older version, each migration whose version is newer than
+// that of the saved edit is applied in order, before the json is deserialized into an `Edit`.
+type Migration = fn(&mut Map<String, Value>);
+
+fn migrations() -> Vec<(Version, Migration)> {
+    vec![
+        (
+            Version {
+                major: 0,
+                minor: 2,
+                patch: 0,
+            },
+            migrate_scalar_vignettes,
+        ),
+        (
+            Version {
+                major: 0,
+                minor: 3,
+                patch: 0,
+            },
+            migrate_mask_term_flags,
+        ),
+    ]
+}
+
+// edits saved before versioning existed don't have a version field
+fn unversioned() -> Version {
+    Version {
+        major: 0,
+        minor: 1,
+        patch: 0,
+    }
+}
+
+impl Edit {
+    pub fn to_persisted_json_string(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.to_persisted_json_value()?).map_err(|e| e.to_string())
+    }
+
+    pub fn to_persisted_json_value(&self) -> Result<Value, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        let version = serde_json::to_value(Version::current_build()).map_err(|e| e.to_string())?;
+        if let Value::Object(ref mut map) = value {
+            map.insert("version".to_owned(), version);
+        }
+        Ok(value)
+    }
+
+    pub fn from_persisted_json_str(json_str: &str) -> Result<Self, String> {
+        let value = serde_json::from_str::<Value>(json_str).map_err(|e| e.to_string())?;
+        Self::from_persisted_json_value(value)
+    }
+
+    pub fn from_persisted_json_value(value: Value) -> Result<Self, String> {
+        let Value::Object(mut map) = value else {
+            return Err("edit json is not an object".to_owned());
+        };
+        let version = persisted_version(&map)?;
+        map.remove("version");
+        // the fields of edits saved by a newer version can't be known, so they aren't guessed
+        if version > Version::current_build() {
+            return Err(format!(
+                "edit was saved by a newer version ({}.{}.{})",
+                version.major, version.minor, version.patch
+            ));
+        }
+        for (migration_version, migration) in migrations() {
+            if version < migration_version {
+                migration(&mut map);
+            }
+        }
+        serde_json::from_value::<Edit>(Value::Object(map)).map_err(|e| e.to_string())
+    }
+
+    // such an edit isn't corrupt, it just can't be read by this version
+    pub fn is_persisted_by_newer_version(value: &Value) -> bool {
+        match value {
+            Value::Object(map) => match persisted_version(map) {
+                Ok(version) => version > Version::current_build(),
+                Err(_) => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+fn persisted_version(map: &Map<String, Value>) -> Result<Version, String> {
+    match map.get("version") {
+        Some(version) => {
+            serde_json::from_value::<Version>(version.clone()).map_err(|e| e.to_string())
+        }
+        None => Ok(unversioned()),
+    }
+}
+
+// the vignette used to be a single number, before midpoint, feather and roundness were added
+fn migrate_scalar_vignettes(edit: &mut Map<String, Value>) {
+    let Some(Value::Array(masked_edits)) = edit.get_mut("masked_edits") else {
+        return;
+    };
+    for masked_edit in masked_edits.iter_mut() {
+        let Some(Value::Object(global_edit)) = masked_edit.get_mut("edit") else {
+            continue;
+        };
+        if let Some(Value::Number(amount)) = global_edit.get("vignette") {
+            let vignette = Vignette {
+                vignette: amount.as_f64().unwrap_or(0.0) as f32,
+                ..Vignette::new()
+            };
+            if let Ok(vignette) = serde_json::to_value(vignette) {
+                global_edit.insert("vignette".to_owned(), vignette);
+            }
+        }
+    }
+}
+
+// mask terms used to have "subtracted" and "intersected" flags, before they were replaced by a single mode
+fn migrate_mask_term_flags(edit: &mut Map<String, Value>) {
+    let Some(Value::Array(masked_edits)) = edit.get_mut("masked_edits") else {
+        return;
+    };
+    for masked_edit in masked_edits.iter_mut() {
+        let Some(Value::Array(terms)) = masked_edit.pointer_mut("/mask/terms") else {
+            continue;
+        };
+        for term in terms.iter_mut() {
+            let Value::Object(term) = term else {
+                continue;
+            };
+            let subtracted = term.remove("subtracted").and_then(|v| v.as_bool());
+            let intersected = term.remove("intersected").and_then(|v| v.as_bool());
+            let mode = if intersected == Some(true) {
+                MaskTermMode::Intersect
+            } else if subtracted == Some(true) {
+                MaskTermMode::Subtract
+            } else {
+                MaskTermMode::Add
+            };
+            if let Ok(mode) = serde_json::to_value(mode) {
+                term.insert("mode".to_owned(), mode);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::GlobalEdit;
+
+    #[test]
+    fn unversioned_scalar_vignette_is_migrated() {
+        let mut value = serde_json::to_value(Edit::trivial()).unwrap();
+        value["masked_edits"][0]["edit"]["vignette"] = serde_json::json!(-30.0);
+        assert!(value.get("version").is_none());
+
+        let edit = Edit::from_persisted_json_value(value).unwrap();
+        let vignette = &edit.masked_edits[0].edit.vignette;
+        assert_eq!(vignette.vignette, -30.0);
+        assert_eq!(vignette.midpoint, Vignette::new().midpoint);
+        assert_eq!(vignette.feather, Vignette::new().feather);
+        assert_eq!(vignette.roundness, Vignette::new().roundness);
+
+        // once saved again, it's at the current version
+        let resaved = edit.to_persisted_json_value().unwrap();
+        let version: Version = serde_json::from_value(resaved["version"].clone()).unwrap();
+        assert!(version == Version::current_build());
+    }
+
+    #[test]
+    fn mask_term_flags_are_migrated() {
+        let mut value = serde_json::to_value(Edit::trivial()).unwrap();
+        let global_term = value["masked_edits"][0]["mask"]["terms"][0].clone();
+        let mut terms = Vec::new();
+        for (subtracted, intersected) in [(false, false), (true, false), (false, true)] {
+            let mut term = global_term.clone();
+            term.as_object_mut().unwrap().remove("mode");
+            term["subtracted"] = serde_json::json!(subtracted);
+            // edits saved before intersection existed don't have this field
+            if intersected {
+                term["intersected"] = serde_json::json!(true);
+            }
+            terms.push(term);
+        }
+        value["masked_edits"][0]["mask"]["terms"] = Value::Array(terms);
+        value["version"] = serde_json::to_value(Version {
+            major: 0,
+            minor: 2,
+            patch: 0,
+        })
+        .unwrap();
+
+        let edit = Edit::from_persisted_json_value(value).unwrap();
+        let modes: Vec<MaskTermMode> = edit.masked_edits[0]
+            .mask
+            .terms
+            .iter()
+            .map(|term| term.mode)
+            .collect();
+        assert_eq!(
+            modes,
+            vec![
+                MaskTermMode::Add,
+                MaskTermMode::Subtract,
+                MaskTermMode::Intersect
+            ]
+        );
+    }
+
+    #[test]
+    fn current_version_round_trips() {
+        let mut edit = Edit::trivial();
+        edit.rotation_degrees = Some(3.5);
+        edit.masked_edits[0].edit = GlobalEdit {
+            exposure: 0.7,
+            curve_control_points_all: vec![(0.0, 0.1), (0.4, 0.5), (1.0, 0.9)],
+            vignette: Vignette {
+                vignette: -20.0,
+                ..Vignette::new()
+            },
+            ..GlobalEdit::new()
+        };
+        let json = edit.to_persisted_json_string().unwrap();
+        let read = Edit::from_persisted_json_str(json.as_str()).unwrap();
+        assert!(read == edit);
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let mut value = Edit::trivial().to_persisted_json_value().unwrap();
+        let current = Version::current_build();
+        let newer = Version {
+            minor: current.minor + 1,
+            ..current
+        };
+        value["version"] = serde_json::to_value(newer).unwrap();
+        assert!(Edit::is_persisted_by_newer_version(&value));
+        assert!(Edit::from_persisted_json_value(value).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::utils::{
+    math::{
+        get_edited_aspect_ratio, handle_new_crop_rect, handle_new_rotation,
+        remap_mask_to_new_image, remove_image_mask_terms,
+    },
+    rectangle::Rectangle,
+};
+
+use super::{Edit, MaskedEdit};
+
+// parts of an edit that can be copied from one image to another
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct EditSections {
+    // exposure, contrast, highlights and shadows
+    pub light: bool,
+    // white balance, vibrance, saturation and the color mixer
+    pub color: bool,
+    pub curves: bool,
+    // dehaze and vignette
+    pub effects: bool,
+    // rotation and crop
+    pub geometry: bool,
+    // all masked edits except the global one
+    pub masks: bool,
+}
+
+impl EditSections {
+    pub fn all() -> Self {
+        Self {
+            light: true,
+            color: true,
+            curves: true,
+            effects: true,
+            geometry: true,
+            masks: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            light: false,
+            color: false,
+            curves: false,
+            effects: false,
+            geometry: false,
+            masks: false,
+        }
+    }
+
+    // copies the selected sections of `source` into `target`.
+    // the aspect ratios are those of the original images, before any geometry edits.
+    pub fn apply(
+        &self,
+        source: &Edit,
+        source_image_aspect_ratio: f32,
+        target: &mut Edit,
+        target_image_aspect_ratio: f32,
+    ) {
+        if self.geometry {
+            // this also moves the existing masks of the target along with the new geometry.
+            // the rotation goes last, so that it can shrink the crop if it doesn't fit in the target image.
+            handle_new_crop_rect(
+                target_image_aspect_ratio,
+                target,
+                source.crop_rect.unwrap_or(Rectangle::regular()),
+            );
+            handle_new_rotation(
+                target_image_aspect_ratio,
+                target,
+                source.rotation_degrees.unwrap_or(0.0),
+            );
+        }
+
+        if let Some(source_global_index) = source.global_masked_edit_index() {
+            let target_global_index = match target.global_masked_edit_index() {
+                Some(i) => i,
+                None => {
+                    let trivial = Edit::trivial();
+                    target
+                        .masked_edits
+                        .insert(0, trivial.masked_edits[0].clone());
+                    0
+                }
+            };
+            let source_global = &source.masked_edits[source_global_index].edit;
+            let target_global = &mut target.masked_edits[target_global_index].edit;
+            if self.light {
+                target_global.exposure = source_global.exposure;
+                target_global.contrast = source_global.contrast;
+                target_global.highlights = source_global.highlights;
+                target_global.shadows = source_global.shadows;
+            }
+            if self.color {
+                target_global.temperature = source_global.temperature;
+                target_global.tint = source_global.tint;
+                target_global.vibrance = source_global.vibrance;
+                target_global.saturation = source_global.saturation;
+                target_global.color_mixer_edits = source_global.color_mixer_edits;
+            }
+            if self.curves {
+                target_global.curve_control_points_all =
+                    source_global.curve_control_points_all.clone();
+                target_global.curve_control_points_r = source_global.curve_control_points_r.clone();
+                target_global.curve_control_points_g = source_global.curve_control_points_g.clone();
+                target_global.curve_control_points_b = source_global.curve_control_points_b.clone();
+            }
+            if self.effects {
+                target_global.dehaze = source_global.dehaze;
+                target_global.vignette = source_global.vignette.clone();
+            }
+        }
+
+        if self.masks {
+            let source_aspect_ratio = get_edited_aspect_ratio(source_image_aspect_ratio, source);
+            let source_global_index = source.global_masked_edit_index();
+            let target_global_index = target.global_masked_edit_index();
+            let mut masked_edits: Vec<MaskedEdit> = Vec::new();
+            if let Some(i) = target_global_index {
+                masked_edits.push(target.masked_edits[i].clone());
+            }
+            for i in 0..source.masked_edits.len() {
+                if Some(i) == source_global_index {
+                    continue;
+                }
+                let mut masked_edit = source.masked_edits[i].clone();
+                if !remove_image_mask_terms(&mut masked_edit.mask) {
+                    continue;
+                }
+                remap_mask_to_new_image(
+                    &mut masked_edit.mask,
+                    source_aspect_ratio,
+                    target_image_aspect_ratio,
+                    target,
+                );
+                masked_edits.push(masked_edit);
+            }
+            if masked_edits.is_empty() {
+                masked_edits = Edit::trivial().masked_edits;
+            }
+            target.masked_edits = masked_edits;
+        }
+    }
+}
@@ -1,19 +1,28 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::{
-    engine::{common::ImageHistogram, Engine, ExecutionContext},
+    engine::{
+        common::{ImageHistogram, ImageScope, ScopeKind},
+        Engine, ExecutionContext,
+    },
+    ir::ImageMask,
     library::LibraryImageIdentifier,
+    presets::Preset,
     runtime::{BufferReader, Image, Runtime, Toolbox},
     services::{edit_writer::EditWriterService, services::Services},
 };
 
 use super::{
-    ir_generator::{to_ir_module, IdStore, IrGenerationOptions},
-    Edit, EditResult, MaskedEditResult,
+    auto_tone, auto_white_balance, describe_edit_change,
+    ir_generator::{to_histogram_ir_module, to_ir_module, IdStore, IrGenerationOptions},
+    Edit, EditHistory, EditHistoryEntry, EditResult, GlobalEdit, MaskedEditResult,
+    PersistedEditHistory, Snapshot, MAX_EDIT_HISTORY_LENGTH,
 };
 
-pub type EditHistory = Vec<Edit>;
-
 pub struct Editor {
     engine: Engine,
     engine_execution_context: ExecutionContext,
@@ -21,6 +30,11 @@ pub struct Editor {
     current_image_identifier: Option<LibraryImageIdentifier>,
     edit_contexts: HashMap<LibraryImageIdentifier, EditContext>,
 
+    // the scope computed along with the histogram, if any
+    scope: Option<ScopeKind>,
+    // chosen based on the image size if None
+    histogram_num_bins: Option<u32>,
+
     runtime: Arc<Runtime>,
     toolbox: Arc<Toolbox>,
     services: Arc<Services>,
@@ -30,12 +44,20 @@ pub struct EditContext {
     input_image: Option<Arc<Image>>,
     edit_history: EditHistory,
     current_edit_index: usize,
+    snapshots: Vec<Snapshot>,
 
     // an edit that is being actively modified
     // (e.g. as the user drags the slider, the temporary edit state)
     transient_edit: Option<Edit>,
     pub current_result: Option<EditResult>,
 
+    // the history is only written when leaving the image (or exiting), rather than on every edit
+    history_needs_writing: bool,
+
+    // nothing is written for an image whose persisted edit couldn't be read, but isn't corrupt either
+    // (e.g. it was saved by a newer version), so that the file is left as it is
+    read_only: bool,
+
     // full size result that includes framing
     // (not equal to current_result.final_image, which might not be full-size)
     pub current_full_size_editted_image: Option<Arc<Image>>,
@@ -54,8 +76,24 @@ impl EditContext {
         history
     }
 
+    pub fn edit_history_ref(&self) -> &EditHistory {
+        &self.edit_history
+    }
+
+    pub fn current_edit_index(&self) -> usize {
+        self.current_edit_index
+    }
+
+    pub fn snapshots_ref(&self) -> &Vec<Snapshot> {
+        &self.snapshots
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     pub fn current_edit_ref(&self) -> &Edit {
-        &self.edit_history[self.current_edit_index]
+        &self.edit_history[self.current_edit_index].edit
     }
 
     pub fn transient_edit_ref(&self) -> &Edit {
@@ -72,7 +110,7 @@ impl EditContext {
         if let Some(ref curr_transient_edit) = self.transient_edit {
             needs_update = *curr_transient_edit != transient_edit;
         } else {
-            needs_update = self.edit_history[self.current_edit_index] != transient_edit;
+            needs_update = *self.current_edit_ref() != transient_edit;
         }
         if needs_update {
             self.transient_edit = Some(transient_edit);
@@ -80,11 +118,12 @@ impl EditContext {
         needs_update
     }
 
-    // returns true iff edit history is updated
-    fn commit_transient_edit(&mut self) -> bool {
+    // returns true iff edit history is updated.
+    // without a description, the new history entry is described by what changed.
+    fn commit_transient_edit(&mut self, description: Option<String>) -> bool {
         let mut needs_commit = false;
         if let Some(ref transient) = self.transient_edit {
-            if *transient != self.edit_history[self.current_edit_index] {
+            if transient != self.current_edit_ref() {
                 needs_commit = true;
             }
         }
@@ -92,7 +131,14 @@ impl EditContext {
             while self.current_edit_index < self.edit_history.len() - 1 {
                 self.edit_history.pop();
             }
-            self.edit_history.push(self.transient_edit.take().unwrap());
+            let edit = self.transient_edit.take().unwrap();
+            let description =
+                description.unwrap_or_else(|| describe_edit_change(self.current_edit_ref(), &edit));
+            self.edit_history
+                .push(EditHistoryEntry::new(edit, description));
+            if self.edit_history.len() > MAX_EDIT_HISTORY_LENGTH {
+                self.edit_history.remove(0);
+            }
             self.current_edit_index = self.edit_history.len() - 1;
 
             self.current_full_size_editted_image = None;
@@ -133,12 +179,24 @@ impl EditContext {
         }
     }
 
+    // returns whether or not the current edit changed
+    fn jump_to_edit(&mut self, index: usize) -> bool {
+        if index < self.edit_history.len() && index != self.current_edit_index {
+            self.current_edit_index = index;
+            self.transient_edit = None;
+            self.current_full_size_editted_image = None;
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn override_resize_factor(&mut self, new_resize_factor: f32) {
         if let Some(ref mut e) = self.transient_edit {
             e.resize_factor = Some(new_resize_factor);
         }
-        for e in self.edit_history.iter_mut() {
-            e.resize_factor = Some(new_resize_factor);
+        for entry in self.edit_history.iter_mut() {
+            entry.edit.resize_factor = Some(new_resize_factor);
         }
     }
 }
@@ -150,6 +208,8 @@ impl Editor {
             engine,
             current_image_identifier: None,
             edit_contexts: HashMap::new(),
+            scope: None,
+            histogram_num_bins: None,
 
             engine_execution_context: ExecutionContext::new(),
             runtime,
@@ -164,34 +224,14 @@ impl Editor {
                 return;
             }
         }
+        self.maybe_update_current_history_in_filesystem();
+        // e.g. in case a missing mask image file was restored in the meantime
+        self.engine.retry_unavailable_mask_images();
 
         if let Some(context) = self.edit_contexts.get_mut(&identifier) {
             context.input_image = Some(image)
         } else {
-            let mut edit = Edit::trivial();
-            if let Some(image_path) = identifier.get_path() {
-                if let Some(edit_path) =
-                    EditWriterService::get_edit_path_for_image_path(&image_path)
-                {
-                    if edit_path.exists() {
-                        if let Ok(edit_json_str) = std::fs::read_to_string(&edit_path) {
-                            if let Ok(saved_edit) =
-                                serde_json::from_str::<Edit>(edit_json_str.as_str())
-                            {
-                                edit = saved_edit;
-                            }
-                        }
-                    }
-                }
-            }
-            let new_context = EditContext {
-                input_image: Some(image),
-                edit_history: vec![edit],
-                current_edit_index: 0,
-                transient_edit: None,
-                current_result: None,
-                current_full_size_editted_image: None,
-            };
+            let new_context = Self::load_edit_context(&identifier, Some(image), &self.services);
             self.edit_contexts.insert(identifier.clone(), new_context);
         }
 
@@ -199,14 +239,239 @@ impl Editor {
         self.execute_current_edit();
     }
 
+    // loads the persisted edit, history and snapshots of the image, if there are any
+    fn load_edit_context(
+        identifier: &LibraryImageIdentifier,
+        image: Option<Arc<Image>>,
+        services: &Services,
+    ) -> EditContext {
+        let mut edit = Edit::trivial();
+        let mut history = None;
+        let mut snapshots = Vec::new();
+        let mut read_only = false;
+        if let Some(edit_path) = EditWriterService::get_edit_path_for_identifier(identifier) {
+            if edit_path.exists() {
+                match Self::read_persisted_edit(&edit_path) {
+                    Ok(saved_edit) => edit = saved_edit,
+                    Err(PersistedEditError::Unsupported(e)) => {
+                        log::warn!(
+                            "failed to read edit {:?}: {}, changes to it won't be saved",
+                            edit_path,
+                            e
+                        );
+                        read_only = true;
+                    }
+                    Err(PersistedEditError::Corrupt(e)) => {
+                        // keep the corrupt edit around, instead of overwriting it with the next edit
+                        log::warn!("failed to read edit {:?}: {}", edit_path, e);
+                        EditWriterService::quarantine_file(&edit_path);
+                    }
+                }
+            }
+            if !read_only {
+                history = Self::read_persisted_history(&edit_path, &edit);
+                snapshots = Self::read_persisted_snapshots(&edit_path, identifier, services);
+            }
+        }
+        let history = history.unwrap_or(PersistedEditHistory {
+            entries: vec![EditHistoryEntry::initial(edit)],
+            current_edit_index: 0,
+        });
+        EditContext {
+            input_image: image,
+            edit_history: history.entries,
+            current_edit_index: history.current_edit_index,
+            snapshots,
+            transient_edit: None,
+            current_result: None,
+            history_needs_writing: false,
+            read_only,
+            current_full_size_editted_image: None,
+        }
+    }
+
+    fn read_persisted_edit(edit_path: &Path) -> Result<Edit, PersistedEditError> {
+        let edit_json_str = std::fs::read_to_string(edit_path).map_err(|e| match e.kind() {
+            // e.g. not valid utf-8
+            std::io::ErrorKind::InvalidData => PersistedEditError::Corrupt(e.to_string()),
+            _ => PersistedEditError::Unsupported(e.to_string()),
+        })?;
+        let value = serde_json::from_str::<serde_json::Value>(edit_json_str.as_str())
+            .map_err(|e| PersistedEditError::Corrupt(e.to_string()))?;
+        let is_from_newer_version = Edit::is_persisted_by_newer_version(&value);
+        Edit::from_persisted_json_value(value).map_err(|e| {
+            if is_from_newer_version {
+                PersistedEditError::Unsupported(e)
+            } else {
+                PersistedEditError::Corrupt(e)
+            }
+        })
+    }
+
+    // snapshots that can't be read are skipped, and the file is quarantined so that they aren't lost
+    // when the readable ones are written back
+    fn read_persisted_snapshots(
+        edit_path: &Path,
+        identifier: &LibraryImageIdentifier,
+        services: &Services,
+    ) -> Vec<Snapshot> {
+        let snapshots_path = EditWriterService::get_snapshots_path_for_edit_path(edit_path);
+        if !snapshots_path.exists() {
+            return Vec::new();
+        }
+        let snapshots_json_str = match std::fs::read_to_string(&snapshots_path) {
+            Ok(snapshots_json_str) => snapshots_json_str,
+            Err(e) => {
+                log::warn!("failed to read snapshots {:?}: {}", snapshots_path, e);
+                EditWriterService::quarantine_file(&snapshots_path);
+                return Vec::new();
+            }
+        };
+        match Snapshot::list_from_json_str(snapshots_json_str.as_str()) {
+            Ok((snapshots, errors)) => {
+                if !errors.is_empty() {
+                    for e in errors.iter() {
+                        log::warn!("failed to read snapshot in {:?}: {}", snapshots_path, e);
+                    }
+                    EditWriterService::quarantine_file(&snapshots_path);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    services
+                        .edit_writer
+                        .request_snapshots_update(snapshots.clone(), identifier.clone());
+                }
+                snapshots
+            }
+            Err(e) => {
+                log::warn!("failed to read snapshots {:?}: {}", snapshots_path, e);
+                EditWriterService::quarantine_file(&snapshots_path);
+                Vec::new()
+            }
+        }
+    }
+
+    // the history is written after the edit, so it's stale if the app didn't exit cleanly.
+    // in that case, the edit (which is what's actually shown) is added to the history as a recovered entry.
+    fn read_persisted_history(edit_path: &Path, edit: &Edit) -> Option<PersistedEditHistory> {
+        let history_path = EditWriterService::get_history_path_for_edit_path(edit_path);
+        let history_json_str = std::fs::read_to_string(history_path).ok()?;
+        let mut history = PersistedEditHistory::from_json_str(history_json_str.as_str()).ok()?;
+        if history.entries[history.current_edit_index].edit != *edit {
+            history.push_recovered(edit.clone());
+        }
+        Some(history)
+    }
+
     pub fn current_image_identifier(&self) -> Option<LibraryImageIdentifier> {
         self.current_image_identifier.clone()
     }
 
     pub fn clear_current_image(&mut self) {
+        self.maybe_update_current_history_in_filesystem();
         self.current_image_identifier = None;
     }
 
+    // the virtual copy starts off with the current edit of the original
+    pub fn init_virtual_copy(
+        &mut self,
+        original: &LibraryImageIdentifier,
+        virtual_copy: LibraryImageIdentifier,
+    ) {
+        let edit = match self.edit_contexts.get(original) {
+            Some(context) => context.current_edit_ref().clone(),
+            None => Self::load_edit_context(original, None, &self.services)
+                .current_edit_ref()
+                .clone(),
+        };
+        let context = EditContext {
+            input_image: None,
+            edit_history: vec![EditHistoryEntry::new(
+                edit,
+                "Create Virtual Copy".to_owned(),
+            )],
+            current_edit_index: 0,
+            snapshots: Vec::new(),
+            transient_edit: None,
+            current_result: None,
+            current_full_size_editted_image: None,
+            history_needs_writing: false,
+            read_only: false,
+        };
+        self.edit_contexts.insert(virtual_copy.clone(), context);
+        self.update_edit_in_filesystem(&virtual_copy);
+        self.update_history_in_filesystem(&virtual_copy);
+    }
+
+    // the current edit of an image, which doesn't have to be the current image
+    pub fn get_edit_of_image(&mut self, identifier: &LibraryImageIdentifier) -> Edit {
+        if !self.edit_contexts.contains_key(identifier) {
+            let context = Self::load_edit_context(identifier, None, &self.services);
+            self.edit_contexts.insert(identifier.clone(), context);
+        }
+        self.edit_contexts[identifier].current_edit_ref().clone()
+    }
+
+    // commits an edit into the history (and the persisted edit) of an image, without making it the current image.
+    // returns the edited image before framing (e.g. for a thumbnail), if anything was committed.
+    pub fn commit_edit_to_image(
+        &mut self,
+        identifier: &LibraryImageIdentifier,
+        image: Arc<Image>,
+        edit: Edit,
+    ) -> Option<Arc<Image>> {
+        if self.current_image_identifier.as_ref() == Some(identifier) {
+            self.update_transient_edit(edit, false);
+            if !self.commit_transient_edit(true) {
+                return None;
+            }
+            let result = self.current_edit_context_ref()?.current_result.as_ref()?;
+            return Some(result.before_framing.clone());
+        }
+
+        if !self.edit_contexts.contains_key(identifier) {
+            let context = Self::load_edit_context(identifier, None, &self.services);
+            self.edit_contexts.insert(identifier.clone(), context);
+        }
+        let context = self.edit_contexts.get_mut(identifier).unwrap();
+        context.input_image = Some(image.clone());
+        context.update_transient_edit(edit);
+        if !context.commit_transient_edit(None) {
+            return None;
+        }
+        self.update_edit_in_filesystem(identifier);
+        // the history is otherwise written when leaving the image, which isn't the current one
+        self.update_history_in_filesystem(identifier);
+
+        // executed in its own context, so that the results of the current image can still be reused
+        let (module, id_store) = to_ir_module(
+            self.edit_contexts[identifier].current_edit_ref(),
+            &IrGenerationOptions {
+                compute_histogram: false,
+                histogram_num_bins: None,
+                compute_scope: None,
+            },
+        );
+        let mut execution_context = ExecutionContext::new();
+        self.engine
+            .execute_module(&module, image, &mut execution_context);
+        let before_framing = execution_context
+            .value_store
+            .map
+            .get(&id_store.before_framing)
+            .expect("cannot find image before framing")
+            .as_image()
+            .clone();
+        self.toolbox.generate_mipmap(&before_framing);
+        Some(before_framing)
+    }
+
+    // drops the edit state of an image that's no longer in the library
+    pub fn forget_image(&mut self, identifier: &LibraryImageIdentifier) {
+        if self.current_image_identifier.as_ref() == Some(identifier) {
+            self.current_image_identifier = None;
+        }
+        self.edit_contexts.remove(identifier);
+    }
+
     pub fn current_edit_context_ref(&self) -> Option<&EditContext> {
         let identifier = self.current_image_identifier.as_ref()?;
         self.edit_contexts.get(identifier)
@@ -217,6 +482,34 @@ impl Editor {
         self.edit_contexts.get_mut(identifier)
     }
 
+    pub fn scope(&self) -> Option<ScopeKind> {
+        self.scope
+    }
+
+    pub fn set_scope(&mut self, scope: Option<ScopeKind>) {
+        if self.scope == scope {
+            return;
+        }
+        self.scope = scope;
+        if self.current_edit_context_ref().is_some() {
+            self.execute_transient_edit();
+        }
+    }
+
+    pub fn histogram_num_bins(&self) -> Option<u32> {
+        self.histogram_num_bins
+    }
+
+    pub fn set_histogram_num_bins(&mut self, num_bins: Option<u32>) {
+        if self.histogram_num_bins == num_bins {
+            return;
+        }
+        self.histogram_num_bins = num_bins;
+        if self.current_edit_context_ref().is_some() {
+            self.execute_transient_edit();
+        }
+    }
+
     pub fn update_transient_edit(&mut self, transient_edit: Edit, execute: bool) {
         let updated = self
             .current_edit_context_mut()
@@ -231,7 +524,7 @@ impl Editor {
         let committed = self
             .current_edit_context_mut()
             .unwrap()
-            .commit_transient_edit();
+            .commit_transient_edit(None);
         if committed {
             self.update_current_edit_in_filesystem();
         }
@@ -241,19 +534,204 @@ impl Editor {
         committed
     }
 
-    fn update_current_edit_in_filesystem(&self) {
+    fn update_current_edit_in_filesystem(&mut self) {
+        if let Some(identifier) = self.current_image_identifier.clone() {
+            self.update_edit_in_filesystem(&identifier);
+        }
+    }
+
+    fn update_edit_in_filesystem(&mut self, identifier: &LibraryImageIdentifier) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(edit_context) = self.edit_contexts.get_mut(identifier) {
+            if edit_context.read_only {
+                return;
+            }
+            self.services
+                .edit_writer
+                .request_update(edit_context.current_edit_ref().clone(), identifier.clone());
+            edit_context.history_needs_writing = true;
+        }
+    }
+
+    fn update_history_in_filesystem(&mut self, identifier: &LibraryImageIdentifier) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(edit_context) = self.edit_contexts.get_mut(identifier) {
+            if edit_context.read_only {
+                return;
+            }
+            let history = PersistedEditHistory {
+                entries: edit_context.edit_history.clone(),
+                current_edit_index: edit_context.current_edit_index,
+            };
+            self.services
+                .edit_writer
+                .request_history_update(history, identifier.clone());
+            edit_context.history_needs_writing = false;
+        }
+    }
+
+    fn maybe_update_current_history_in_filesystem(&mut self) {
+        let Some(identifier) = self.current_image_identifier.clone() else {
+            return;
+        };
+        if let Some(edit_context) = self.edit_contexts.get(&identifier) {
+            if edit_context.history_needs_writing {
+                self.update_history_in_filesystem(&identifier);
+            }
+        }
+    }
+
+    pub fn on_exit(&mut self) {
+        self.maybe_update_current_history_in_filesystem();
+    }
+
+    fn update_snapshots_in_filesystem(&self) {
         #[cfg(not(target_arch = "wasm32"))]
         if let Some(ref identifier) = self.current_image_identifier {
-            if let Some(path) = identifier.get_path() {
-                if let Some(edit_context) = self.current_edit_context_ref() {
-                    self.services
-                        .edit_writer
-                        .request_update(edit_context.current_edit_ref().clone(), path);
+            if let Some(edit_context) = self.current_edit_context_ref() {
+                if edit_context.read_only {
+                    return;
                 }
+                self.services
+                    .edit_writer
+                    .request_snapshots_update(edit_context.snapshots.clone(), identifier.clone());
             }
         }
     }
 
+    // saves the current edit as a snapshot of the current image
+    pub fn create_snapshot(&mut self, name: String) {
+        if let Some(context) = self.current_edit_context_mut() {
+            let edit = context.current_edit_ref().clone();
+            context.snapshots.push(Snapshot::new(name, edit));
+            self.update_snapshots_in_filesystem();
+        }
+    }
+
+    // returns whether there was a snapshot at the index
+    pub fn delete_snapshot(&mut self, index: usize) -> bool {
+        let Some(context) = self.current_edit_context_mut() else {
+            return false;
+        };
+        if index >= context.snapshots.len() {
+            return false;
+        }
+        context.snapshots.remove(index);
+        self.update_snapshots_in_filesystem();
+        true
+    }
+
+    // the snapshot becomes the current edit, as a new edit history entry
+    pub fn restore_snapshot(&mut self, index: usize) -> bool {
+        let Some(context) = self.current_edit_context_mut() else {
+            return false;
+        };
+        let Some(snapshot) = context.snapshots.get(index).cloned() else {
+            return false;
+        };
+        context.update_transient_edit(snapshot.edit);
+        let description = format!("Restore Snapshot \"{}\"", snapshot.name);
+        let committed = context.commit_transient_edit(Some(description));
+        if committed {
+            self.update_current_edit_in_filesystem();
+            self.execute_current_edit();
+        }
+        committed
+    }
+
+    // the current edit with the preset applied on top
+    pub fn get_edit_with_preset(&self, preset: &Preset, amount: f32) -> Edit {
+        let context = self.current_edit_context_ref().unwrap();
+        preset.apply(
+            context.current_edit_ref(),
+            context.input_image().aspect_ratio(),
+            amount,
+        )
+    }
+
+    // applies the preset as a single edit history entry
+    pub fn apply_preset(&mut self, preset: &Preset, amount: f32) -> bool {
+        let edit = self.get_edit_with_preset(preset, amount);
+        self.update_transient_edit(edit, false);
+        self.commit_transient_edit(true)
+    }
+
+    // the histogram of the current image with only geometry edits applied, which auto adjustments are based on
+    pub fn compute_geometry_only_histogram(&mut self) -> Option<BufferReader<ImageHistogram>> {
+        let image = self
+            .current_edit_context_ref()?
+            .current_result
+            .as_ref()?
+            .geometry_only
+            .clone();
+        let (module, histogram_id) = to_histogram_ir_module();
+        // a separate execution context, so that the values of the current edit can still be reused
+        let mut execution_context = ExecutionContext::new();
+        self.engine
+            .execute_module(&module, image, &mut execution_context);
+        let histogram_buffer = execution_context
+            .value_store
+            .map
+            .get(&histogram_id)?
+            .as_buffer()
+            .clone();
+        Some(BufferReader::new(
+            self.runtime.clone(),
+            histogram_buffer,
+            None,
+            Box::new(|v| ImageHistogram::from_u32_slice(v.as_slice())),
+        ))
+    }
+
+    // sets the tone adjustments of the global edit based on the geometry-only histogram, as a single edit history entry
+    pub fn apply_auto_tone(&mut self, histogram: &ImageHistogram) -> bool {
+        self.apply_to_global_edit("Auto Tone", |global_edit| auto_tone(global_edit, histogram))
+    }
+
+    // the color of the light in the current image with only geometry edits applied, see `Toolbox::estimate_illuminant`
+    pub fn estimate_geometry_only_illuminant(&self) -> Option<BufferReader<Option<[f32; 3]>>> {
+        let image = self
+            .current_edit_context_ref()?
+            .current_result
+            .as_ref()?
+            .geometry_only
+            .clone();
+        Some(self.toolbox.estimate_illuminant(image))
+    }
+
+    // sets temperature and tint of the global edit to neutralize the illuminant, as a single edit history entry
+    pub fn apply_auto_white_balance(&mut self, illuminant: [f32; 3]) -> bool {
+        self.apply_to_global_edit("Auto White Balance", |global_edit| {
+            auto_white_balance(global_edit, illuminant)
+        })
+    }
+
+    // replaces the global edit with a function of it, as an edit history entry with the given description
+    fn apply_to_global_edit(
+        &mut self,
+        description: &str,
+        f: impl FnOnce(&GlobalEdit) -> GlobalEdit,
+    ) -> bool {
+        let pending_committed = self.commit_transient_edit(false);
+        let mut committed = false;
+        if let Some(context) = self.current_edit_context_mut() {
+            let mut edit = context.current_edit_ref().clone();
+            if let Some(global_index) = edit.global_masked_edit_index() {
+                let global_edit = &mut edit.masked_edits[global_index].edit;
+                *global_edit = f(global_edit);
+                context.update_transient_edit(edit);
+                committed = context.commit_transient_edit(Some(description.to_owned()));
+            }
+        }
+        if committed {
+            self.update_current_edit_in_filesystem();
+        }
+        if committed || pending_committed {
+            self.execute_current_edit();
+        }
+        committed
+    }
+
     pub fn can_undo(&mut self) -> bool {
         if let Some(context) = self.current_edit_context_ref() {
             context.can_undo()
@@ -292,11 +770,25 @@ impl Editor {
         false
     }
 
+    // makes an entry of the edit history the current edit, keeping the entries after it for redo
+    pub fn jump_to_edit(&mut self, index: usize) -> bool {
+        if let Some(context) = self.current_edit_context_mut() {
+            if context.jump_to_edit(index) {
+                self.execute_current_edit();
+                self.update_current_edit_in_filesystem();
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn execute_current_edit(&mut self) {
         let (module, id_store) = to_ir_module(
             self.current_edit_context_ref().unwrap().current_edit_ref(),
             &IrGenerationOptions {
                 compute_histogram: true,
+                histogram_num_bins: self.histogram_num_bins,
+                compute_scope: self.scope,
             },
         );
         let image = self
@@ -317,6 +809,8 @@ impl Editor {
                 .transient_edit_ref(),
             &IrGenerationOptions {
                 compute_histogram: true,
+                histogram_num_bins: self.histogram_num_bins,
+                compute_scope: self.scope,
             },
         );
         let image = self
@@ -357,6 +851,8 @@ impl Editor {
             &edit,
             &IrGenerationOptions {
                 compute_histogram: false,
+                histogram_num_bins: None,
+                compute_scope: None,
             },
         );
 
@@ -377,14 +873,63 @@ impl Editor {
         full_size_result_image
     }
 
+    // reads the grayscale image at the path, for use as an image mask of the current edit
+    pub fn import_image_mask(&mut self, path: PathBuf) -> Result<ImageMask, String> {
+        self.engine.import_mask_image(path)
+    }
+
+    // the combined mask of a masked edit, or one of its terms if term_index is given.
+    pub fn get_full_size_mask(
+        &mut self,
+        masked_edit_index: usize,
+        term_index: Option<usize>,
+    ) -> Arc<Image> {
+        let edit = self.get_full_size_edit();
+
+        let (module, id_store) = to_ir_module(
+            &edit,
+            &IrGenerationOptions {
+                compute_histogram: false,
+                histogram_num_bins: None,
+                compute_scope: None,
+            },
+        );
+
+        self.engine.execute_module(
+            &module,
+            self.current_edit_context_ref()
+                .unwrap()
+                .input_image()
+                .clone(),
+            &mut self.engine_execution_context,
+        );
+        let result = self.collect_result(&id_store);
+        let masked_edit_result = &result.masked_edit_results[masked_edit_index];
+        match term_index {
+            Some(i) => masked_edit_result.mask_terms[i].clone(),
+            None => masked_edit_result.mask.clone(),
+        }
+    }
+
     fn collect_result(&mut self, id_store: &IdStore) -> EditResult {
         let mut histogram_initial_value = None;
+        let mut scope_initial_value = None;
         if let Some(context) = self.current_edit_context_mut() {
             if let Some(ref mut current_result) = context.current_result {
                 if let Some(ref mut current_histogram) = current_result.histogram_final {
                     current_histogram.poll_value();
                     histogram_initial_value = current_histogram.take_value();
                 }
+                if let Some(ref mut current_scope) = current_result.scope_final {
+                    current_scope.poll_value();
+                    scope_initial_value = current_scope.take_value();
+                }
+            }
+        }
+        // the previous scope is only shown until the new one is ready if it's of the same kind
+        if let Some(ref value) = scope_initial_value {
+            if Some(value.kind) != self.scope {
+                scope_initial_value = None;
             }
         }
 
@@ -412,9 +957,30 @@ impl Editor {
             ));
         }
 
+        let mut final_scope = None;
+        if let (Some(ref final_scope_id), Some(kind)) = (id_store.final_scope, self.scope) {
+            let final_scope_buffer = value_map
+                .get(final_scope_id)
+                .expect("cannot find scope")
+                .as_buffer()
+                .clone();
+
+            final_scope = Some(BufferReader::new(
+                self.runtime.clone(),
+                final_scope_buffer,
+                scope_initial_value,
+                Box::new(move |v| ImageScope::from_u32_slice(kind, v.as_slice())),
+            ));
+        }
+
         let mut masked_edit_results = Vec::new();
 
         for masked_edit_id_store in id_store.masked_edit_id_stores.iter() {
+            let target = value_map
+                .get(&masked_edit_id_store.target_id)
+                .expect("cannot find target image")
+                .as_image()
+                .clone();
             let mask = value_map
                 .get(&masked_edit_id_store.mask_id)
                 .expect("cannot find mask")
@@ -435,6 +1001,7 @@ impl Editor {
                 mask_terms.push(term)
             }
             masked_edit_results.push(MaskedEditResult {
+                target,
                 mask,
                 mask_terms,
                 result_image,
@@ -460,7 +1027,15 @@ impl Editor {
             geometry_only,
             before_framing,
             histogram_final: final_histogram,
+            scope_final: final_scope,
             masked_edit_results,
         }
     }
 }
+
+enum PersistedEditError {
+    // can't be read by this version (e.g. saved by a newer one), so it's left as it is
+    Unsupported(String),
+    // can't be read by any version
+    Corrupt(String),
+}
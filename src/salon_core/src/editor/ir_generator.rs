@@ -1,11 +1,12 @@
 use super::{Edit, GlobalEdit, MaskedEdit};
 
 use crate::{
+    engine::common::ScopeKind,
     ir::{
         AdjustContrastOp, AdjustExposureOp, AdjustHighlightsAndShadowsOp,
         AdjustTemperatureAndTintOp, AdjustVibranceAndSaturationOp, AdjustVignetteOp, ApplyCurveOp,
-        ApplyDehazeOp, ApplyFramingOp, ApplyMaskedEditsOp, ColorMixGroup, ColorMixOp,
-        ComputeBasicStatisticsOp, ComputeHistogramOp, Id, InputOp, Module, Op, PrepareDehazeOp,
+        ApplyDehazeOp, ApplyFramingOp, ApplyMaskedEditsOp, ApplyRedEyeCorrectionOp, ApplyRetouchSpotOp, ColorMixGroup, ColorMixOp,
+        ComputeBasicStatisticsOp, ComputeHistogramOp, ComputeVectorscopeOp, ComputeWaveformOp, Id, InputOp, Module, Op, PrepareDehazeOp,
         ResizeOp, RotateAndCropOp,
     },
     utils::rectangle::Rectangle,
@@ -16,10 +17,13 @@ pub struct IdStore {
     pub geometry_only: Id,
     pub before_framing: Id,
     pub final_histogram: Option<Id>,
+    pub final_scope: Option<Id>,
     pub masked_edit_id_stores: Vec<MaskedEditIdStore>,
 }
 
 pub struct MaskedEditIdStore {
+    // the image the mask is computed against and the edit is applied to
+    pub target_id: Id,
     pub mask_id: Id,
     pub term_ids: Vec<Id>,
     pub result_image_id: Id,
@@ -27,6 +31,9 @@ pub struct MaskedEditIdStore {
 
 pub struct IrGenerationOptions {
     pub compute_histogram: bool,
+    // chosen based on the image size if None
+    pub histogram_num_bins: Option<u32>,
+    pub compute_scope: Option<ScopeKind>,
 }
 
 pub fn to_ir_module(edit: &Edit, options: &IrGenerationOptions) -> (Module, IdStore) {
@@ -42,9 +49,12 @@ pub fn to_ir_module(edit: &Edit, options: &IrGenerationOptions) -> (Module, IdSt
 
     let geometry_only = current_output_id;
 
+    maybe_add_retouch(edit, &mut module, &mut current_output_id);
+    maybe_add_red_eye_correction(edit, &mut module, &mut current_output_id);
+
     let mut masked_edit_id_stores = Vec::new();
-    for edit in edit.masked_edits.iter() {
-        let masked_id_store = add_masked_edit(edit, &mut module, current_output_id);
+    for masked_edit in edit.masked_edits.iter() {
+        let masked_id_store = add_masked_edit(masked_edit, edit, &mut module, current_output_id);
         current_output_id = masked_id_store.result_image_id;
         masked_edit_id_stores.push(masked_id_store);
     }
@@ -53,8 +63,16 @@ pub fn to_ir_module(edit: &Edit, options: &IrGenerationOptions) -> (Module, IdSt
 
     let mut final_histogram_id = None;
     if options.compute_histogram {
-        final_histogram_id = Some(add_final_histogram(&mut module, &current_output_id));
+        final_histogram_id = Some(add_final_histogram(
+            &mut module,
+            &current_output_id,
+            options.histogram_num_bins,
+        ));
     };
+    let mut final_scope_id = None;
+    if let Some(kind) = options.compute_scope {
+        final_scope_id = Some(add_final_scope(&mut module, &current_output_id, kind));
+    }
 
     maybe_add_framing(edit, &mut module, &mut current_output_id);
 
@@ -63,12 +81,24 @@ pub fn to_ir_module(edit: &Edit, options: &IrGenerationOptions) -> (Module, IdSt
         geometry_only,
         before_framing,
         final_histogram: final_histogram_id,
+        final_scope: final_scope_id,
         masked_edit_id_stores,
     };
     // println!("{:#?}", module.ops());
     (module, id_store)
 }
 
+// computes the histogram of the input image alone, e.g. for analyzing an intermediate result of an edit
+pub fn to_histogram_ir_module() -> (Module, Id) {
+    let mut module = Module::new_empty();
+
+    let input_id = module.alloc_id();
+    module.push_op(Op::Input(InputOp { result: input_id }));
+
+    let histogram_id = add_final_histogram(&mut module, &input_id, None);
+    (module, histogram_id)
+}
+
 fn maybe_add_resize(edit: &Edit, module: &mut Module, current_output_id: &mut Id) {
     if let Some(ref factor) = edit.resize_factor {
         if *factor != 1.0 {
@@ -96,6 +126,31 @@ fn maybe_add_rotate_and_crop(edit: &Edit, module: &mut Module, current_output_id
     }
 }
 
+fn maybe_add_retouch(edit: &Edit, module: &mut Module, current_output_id: &mut Id) {
+    // one op per spot, so that editing a spot doesn't require re-applying the ones before it
+    for spot in edit.retouch_spots.iter() {
+        let retouched_image_id = module.alloc_id();
+        module.push_op(Op::ApplyRetouchSpot(ApplyRetouchSpotOp {
+            result: retouched_image_id,
+            arg: *current_output_id,
+            spot: spot.clone(),
+        }));
+        *current_output_id = retouched_image_id;
+    }
+}
+
+fn maybe_add_red_eye_correction(edit: &Edit, module: &mut Module, current_output_id: &mut Id) {
+    for correction in edit.red_eye_corrections.iter() {
+        let corrected_image_id = module.alloc_id();
+        module.push_op(Op::ApplyRedEyeCorrection(ApplyRedEyeCorrectionOp {
+            result: corrected_image_id,
+            arg: *current_output_id,
+            correction: correction.clone(),
+        }));
+        *current_output_id = corrected_image_id;
+    }
+}
+
 fn maybe_add_framing(edit: &Edit, module: &mut Module, current_output_id: &mut Id) {
     if edit.framing.is_some() {
         let framed_image_id = module.alloc_id();
@@ -108,22 +163,60 @@ fn maybe_add_framing(edit: &Edit, module: &mut Module, current_output_id: &mut I
     }
 }
 
-fn add_final_histogram(module: &mut Module, current_output_id: &Id) -> Id {
+fn add_final_histogram(module: &mut Module, current_output_id: &Id, num_bins: Option<u32>) -> Id {
     let histogram_id = module.alloc_id();
     module.push_op(Op::ComputeHistogram(ComputeHistogramOp {
         result: histogram_id,
         arg: *current_output_id,
+        num_bins,
     }));
 
     histogram_id
 }
 
+fn add_final_scope(module: &mut Module, current_output_id: &Id, kind: ScopeKind) -> Id {
+    let scope_id = module.alloc_id();
+    match kind {
+        ScopeKind::LumaWaveform | ScopeKind::RgbParade => {
+            module.push_op(Op::ComputeWaveform(ComputeWaveformOp {
+                result: scope_id,
+                arg: *current_output_id,
+                parade: kind == ScopeKind::RgbParade,
+            }));
+        }
+        ScopeKind::Vectorscope => {
+            module.push_op(Op::ComputeVectorscope(ComputeVectorscopeOp {
+                result: scope_id,
+                arg: *current_output_id,
+            }));
+        }
+    }
+    scope_id
+}
+
 fn add_masked_edit(
     masked_edit: &MaskedEdit,
+    edit: &Edit,
     module: &mut Module,
     target_id: Id,
 ) -> MaskedEditIdStore {
-    let (mask_id, term_ids) = masked_edit.mask.create_compute_mask_ops(target_id, module);
+    // the mask is still computed for disabled edits, so that it can be displayed
+    let (mask_id, term_ids) = masked_edit.mask.create_compute_mask_ops(
+        target_id,
+        edit.rotation_degrees.unwrap_or(0.0),
+        edit.crop_rect.unwrap_or(Rectangle::regular()),
+        module,
+    );
+
+    if !masked_edit.enabled {
+        return MaskedEditIdStore {
+            target_id,
+            mask_id,
+            term_ids,
+            result_image_id: target_id,
+        };
+    }
+
     let edited_id = add_global_edit(&masked_edit.edit, module, target_id);
 
     let result_image_id = module.alloc_id();
@@ -133,9 +226,12 @@ fn add_masked_edit(
         mask: mask_id,
         original_target: target_id,
         edited: edited_id,
+        opacity: masked_edit.opacity,
+        blend_mode: masked_edit.blend_mode,
     }));
 
     MaskedEditIdStore {
+        target_id,
         mask_id,
         term_ids,
         result_image_id,
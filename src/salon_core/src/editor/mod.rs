@@ -1,8 +1,19 @@
+mod auto_tone;
 mod edit;
+mod edit_history;
+mod edit_migration;
+mod edit_sections;
 mod editor;
 mod ir_generator;
 mod result;
+mod snapshot;
+mod white_balance;
 
+pub use auto_tone::*;
 pub use edit::*;
+pub use edit_history::*;
+pub use edit_sections::*;
 pub use editor::*;
-pub use result::*;
\ No newline at end of file
+pub use result::*;
+pub use snapshot::*;
+pub use white_balance::*;
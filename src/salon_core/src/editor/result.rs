@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::engine::common::ImageHistogram;
+use crate::engine::common::{ImageHistogram, ImageScope};
 use crate::runtime::{BufferReader, Image};
 
 pub struct EditResult {
@@ -8,10 +8,13 @@ pub struct EditResult {
     pub geometry_only: Arc<Image>,
     pub before_framing: Arc<Image>,
     pub histogram_final: Option<BufferReader<ImageHistogram>>,
+    pub scope_final: Option<BufferReader<ImageScope>>,
     pub masked_edit_results: Vec<MaskedEditResult>,
 }
 
 pub struct MaskedEditResult {
+    // the image before this masked edit, which includes all previous masked edits
+    pub target: Arc<Image>,
     pub mask: Arc<Image>,
     pub mask_terms: Vec<Arc<Image>>,
     pub result_image: Arc<Image>,
@@ -0,0 +1,96 @@
+use serde_json::{Map, Value};
+
+use super::Edit;
+
+// a named edit state of an image, which can be restored later on
+#[derive(Clone, PartialEq)]
+pub struct Snapshot {
+    pub name: String,
+    pub edit: Edit,
+}
+
+impl Snapshot {
+    pub fn new(name: String, edit: Edit) -> Self {
+        Self { name, edit }
+    }
+
+    // the edits are stored (and migrated) in the same way as a persisted edit
+    pub fn list_to_json_string(snapshots: &[Snapshot]) -> Result<String, String> {
+        let mut values = Vec::new();
+        for snapshot in snapshots.iter() {
+            let mut map = Map::new();
+            map.insert("name".to_owned(), Value::String(snapshot.name.clone()));
+            map.insert("edit".to_owned(), snapshot.edit.to_persisted_json_value()?);
+            values.push(Value::Object(map));
+        }
+        serde_json::to_string_pretty(&Value::Array(values)).map_err(|e| e.to_string())
+    }
+
+    // returns the snapshots that could be read, and the errors of the ones that couldn't
+    pub fn list_from_json_str(json_str: &str) -> Result<(Vec<Snapshot>, Vec<String>), String> {
+        let value = serde_json::from_str::<Value>(json_str).map_err(|e| e.to_string())?;
+        let Value::Array(values) = value else {
+            return Err("snapshots json is not an array".to_owned());
+        };
+        let mut snapshots = Vec::new();
+        let mut errors = Vec::new();
+        for value in values.iter() {
+            match Snapshot::from_json_value(value) {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(e) => errors.push(e),
+            }
+        }
+        Ok((snapshots, errors))
+    }
+
+    fn from_json_value(value: &Value) -> Result<Snapshot, String> {
+        let name = match value.get("name") {
+            Some(Value::String(name)) => name.clone(),
+            _ => return Err("snapshot has no name".to_owned()),
+        };
+        let Some(edit_value) = value.get("edit") else {
+            return Err(format!("snapshot \"{}\" has no edit", name));
+        };
+        let edit = Edit::from_persisted_json_value(edit_value.clone())
+            .map_err(|e| format!("snapshot \"{}\": {}", name, e))?;
+        Ok(Snapshot::new(name, edit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_round_trip() {
+        let snapshots = vec![
+            Snapshot::new("a".to_owned(), Edit::trivial()),
+            Snapshot::new("b".to_owned(), Edit::trivial()),
+        ];
+        let json_str = Snapshot::list_to_json_string(&snapshots).unwrap();
+        let (read, errors) = Snapshot::list_from_json_str(json_str.as_str()).unwrap();
+        assert!(read == snapshots);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn unreadable_snapshots_are_skipped() {
+        let snapshots = vec![Snapshot::new("good".to_owned(), Edit::trivial())];
+        let json_str = Snapshot::list_to_json_string(&snapshots).unwrap();
+        let mut values: Vec<Value> = serde_json::from_str(json_str.as_str()).unwrap();
+        values.insert(0, serde_json::json!({ "name": "no edit" }));
+        values.push(serde_json::json!({ "name": "bad edit", "edit": "not an edit" }));
+        let json_str = serde_json::to_string(&values).unwrap();
+
+        let (read, errors) = Snapshot::list_from_json_str(json_str.as_str()).unwrap();
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].name, "good");
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn snapshots_that_are_not_a_list_are_an_error() {
+        assert!(Snapshot::list_from_json_str("{}").is_err());
+        assert!(Snapshot::list_from_json_str("not json").is_err());
+    }
+}
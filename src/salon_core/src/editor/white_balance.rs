@@ -0,0 +1,129 @@
+use crate::utils::color::{planckian_uv_krystek, rgb_to_xyz, xy_to_uv, xyz_to_xy};
+
+use super::GlobalEdit;
+
+// the color temperature that a temperature of 0 corresponds to, as in temperature_tint.wgsl
+const ORIGINAL_CCT: f32 = 5000.0;
+
+// the temperature and tint that make a (linear rgb) color neutral.
+//
+// temperature_tint.wgsl moves the uv chromaticity of every pixel by
+//     uv(ORIGINAL_CCT) - uv(ORIGINAL_CCT + temperature * 10) + (1, -1) * tint / 3000
+// so the color is neutral when that moves its uv onto the uv of the white point.
+// the tint moves along (1, -1), so the temperature is chosen such that the remaining offset is along that direction.
+// returns None for black, and the results are clamped to the range of the sliders.
+pub fn neutralizing_temperature_and_tint(rgb: [f32; 3]) -> Option<(f32, f32)> {
+    let sample_uv = xy_to_uv(xyz_to_xy(rgb_to_xyz(rgb))?);
+    let white_uv = xy_to_uv(xyz_to_xy(rgb_to_xyz([1.0, 1.0, 1.0]))?);
+    let original_uv = planckian_uv_krystek(ORIGINAL_CCT);
+    let target = (
+        original_uv.0 + sample_uv.0 - white_uv.0,
+        original_uv.1 + sample_uv.1 - white_uv.1,
+    );
+    let offset_from_target = |temperature: f32| {
+        let uv = planckian_uv_krystek(ORIGINAL_CCT + temperature * 10.0);
+        (uv.0 - target.0, uv.1 - target.1)
+    };
+
+    // the offset's component perpendicular to (1, -1) is what the tint can't fix
+    let mut temperature = 0.0;
+    let mut min_error = f32::INFINITY;
+    for i in -1000..=1000 {
+        let t = i as f32 * 0.1;
+        let offset = offset_from_target(t);
+        let error = (offset.0 + offset.1).abs();
+        if error < min_error {
+            min_error = error;
+            temperature = t;
+        }
+    }
+    let offset = offset_from_target(temperature);
+    let tint = 3000.0 * 0.5 * (offset.0 - offset.1);
+
+    // (adding 0 turns -0 into 0, which would otherwise show up on the sliders)
+    Some((
+        temperature.round() + 0.0,
+        tint.clamp(-100.0, 100.0).round() + 0.0,
+    ))
+}
+
+// sets temperature and tint so that the estimated color of the light becomes neutral
+pub fn auto_white_balance(edit: &GlobalEdit, illuminant: [f32; 3]) -> GlobalEdit {
+    let mut result = edit.clone();
+    if let Some((temperature, tint)) = neutralizing_temperature_and_tint(illuminant) {
+        result.temperature = temperature;
+        result.tint = tint;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the uv chromaticity of the color after the temperature and tint adjustment. this re-implements the formula of
+    // temperature_tint.wgsl in rust, the shader itself isn't run, so the two have to be kept in sync by hand
+    fn reimplemented_shader_uv(rgb: [f32; 3], temperature: f32, tint: f32) -> (f32, f32) {
+        let uv = xy_to_uv(xyz_to_xy(rgb_to_xyz(rgb)).unwrap());
+        let original_uv = planckian_uv_krystek(ORIGINAL_CCT);
+        let new_uv = planckian_uv_krystek(ORIGINAL_CCT + temperature * 10.0);
+        let change_in_uv = (tint / 3000.0, -tint / 3000.0);
+        (
+            original_uv.0 + uv.0 - new_uv.0 + change_in_uv.0,
+            original_uv.1 + uv.1 - new_uv.1 + change_in_uv.1,
+        )
+    }
+
+    fn assert_neutralized(rgb: [f32; 3]) {
+        let (temperature, tint) = neutralizing_temperature_and_tint(rgb).unwrap();
+        assert!(
+            temperature.abs() < 100.0 && tint.abs() < 100.0,
+            "{} {} out of range",
+            temperature,
+            tint
+        );
+        let uv = reimplemented_shader_uv(rgb, temperature, tint);
+        let white_uv = xy_to_uv(xyz_to_xy(rgb_to_xyz([1.0, 1.0, 1.0])).unwrap());
+        // within the rounding of the sliders to whole numbers
+        assert!(
+            (uv.0 - white_uv.0).abs() < 3e-4 && (uv.1 - white_uv.1).abs() < 3e-4,
+            "{:?} with temperature {} and tint {} is not neutral",
+            rgb,
+            temperature,
+            tint
+        );
+    }
+
+    #[test]
+    fn neutral_colors_need_no_adjustment() {
+        assert_eq!(
+            neutralizing_temperature_and_tint([0.5, 0.5, 0.5]),
+            Some((0.0, 0.0))
+        );
+        assert_eq!(
+            neutralizing_temperature_and_tint([1.0, 1.0, 1.0]),
+            Some((0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn black_cannot_be_neutralized() {
+        assert_eq!(neutralizing_temperature_and_tint([0.0, 0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn warm_and_cool_colors_move_temperature_in_opposite_directions() {
+        let (warm, _) = neutralizing_temperature_and_tint([0.55, 0.5, 0.45]).unwrap();
+        let (cool, _) = neutralizing_temperature_and_tint([0.45, 0.5, 0.55]).unwrap();
+        assert!(warm < 0.0, "{}", warm);
+        assert!(cool > 0.0, "{}", cool);
+    }
+
+    #[test]
+    fn adjustment_makes_the_color_neutral() {
+        assert_neutralized([0.55, 0.5, 0.45]);
+        assert_neutralized([0.45, 0.5, 0.55]);
+        assert_neutralized([0.5, 0.55, 0.5]);
+        assert_neutralized([0.55, 0.5, 0.55]);
+    }
+}
@@ -4,15 +4,33 @@ pub struct ImageHistogram {
     pub b: Vec<u32>,
     pub luma: Vec<u32>,
     pub num_bins: u32,
+    // the number of pixels at or below 0 and at or above 1 (before any clamping), indexed by HistogramChannel
+    pub clipped_shadows: [u32; 4],
+    pub clipped_highlights: [u32; 4],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HistogramChannel {
+    // matches histogram_compute.wgsl
+    R = 0,
+    G = 1,
+    B = 2,
+    Luma = 3,
 }
 
 impl ImageHistogram {
     pub fn max_bins() -> usize {
         256
     }
-    pub fn num_bins_for(_dimensions: (u32, u32)) -> usize {
-        // need more sophisticated logic here?
-        100
+    pub fn min_bins() -> usize {
+        16
+    }
+    // chosen by the number of pixels (the Rice rule), so that bins of small images aren't mostly empty,
+    // while large images get up to one bin per 8-bit level
+    pub fn num_bins_for(dimensions: (u32, u32)) -> usize {
+        let num_pixels = dimensions.0 as f64 * dimensions.1 as f64;
+        let num_bins = (2.0 * num_pixels.cbrt()).round() as usize;
+        num_bins.clamp(ImageHistogram::min_bins(), ImageHistogram::max_bins())
     }
     pub fn max_value(&self) -> u32 {
         let mut max = 0u32;
@@ -31,6 +49,71 @@ impl ImageHistogram {
         max
     }
 
+    // the counts of the bins that are in use
+    pub fn bins(&self, channel: HistogramChannel) -> &[u32] {
+        let counts = match channel {
+            HistogramChannel::R => &self.r,
+            HistogramChannel::G => &self.g,
+            HistogramChannel::B => &self.b,
+            HistogramChannel::Luma => &self.luma,
+        };
+        &counts[..(self.num_bins as usize).min(counts.len())]
+    }
+
+    pub fn num_pixels(&self, channel: HistogramChannel) -> u64 {
+        self.bins(channel).iter().map(|c| *c as u64).sum()
+    }
+
+    // the (sRGB encoded) value at the center of a bin
+    pub fn bin_value(&self, bin: usize) -> f32 {
+        if self.num_bins < 2 {
+            return 0.5;
+        }
+        ((bin as f32 + 0.5) / (self.num_bins - 1) as f32).min(1.0)
+    }
+
+    pub fn mean(&self, channel: HistogramChannel) -> f32 {
+        let total = self.num_pixels(channel);
+        if total == 0 {
+            return 0.0;
+        }
+        let sum: f64 = self
+            .bins(channel)
+            .iter()
+            .enumerate()
+            .map(|(i, c)| self.bin_value(i) as f64 * *c as f64)
+            .sum();
+        (sum / total as f64) as f32
+    }
+
+    // the value below which a fraction p (from 0 to 1) of the pixels lie
+    pub fn percentile(&self, channel: HistogramChannel, p: f32) -> f32 {
+        let total = self.num_pixels(channel);
+        if total == 0 {
+            return 0.0;
+        }
+        let mut accumulated = 0u64;
+        for (i, c) in self.bins(channel).iter().enumerate() {
+            accumulated += *c as u64;
+            if accumulated as f64 >= p as f64 * total as f64 {
+                return self.bin_value(i);
+            }
+        }
+        1.0
+    }
+
+    pub fn median(&self, channel: HistogramChannel) -> f32 {
+        self.percentile(channel, 0.5)
+    }
+
+    pub fn clipped_shadows(&self, channel: HistogramChannel) -> u32 {
+        self.clipped_shadows[channel as usize]
+    }
+
+    pub fn clipped_highlights(&self, channel: HistogramChannel) -> u32 {
+        self.clipped_highlights[channel as usize]
+    }
+
     pub fn from_u32_slice(data: &[u32]) -> Self {
         let r = data[ImageHistogram::max_bins() * 0..ImageHistogram::max_bins() * 1].to_vec();
         let g = data[ImageHistogram::max_bins() * 1..ImageHistogram::max_bins() * 2].to_vec();
@@ -39,12 +122,126 @@ impl ImageHistogram {
 
         let num_bins = data[ImageHistogram::max_bins() * 4];
 
+        let clipped_start = ImageHistogram::max_bins() * 4 + 1;
+        let mut clipped_shadows = [0u32; 4];
+        clipped_shadows.copy_from_slice(&data[clipped_start..clipped_start + 4]);
+        let mut clipped_highlights = [0u32; 4];
+        clipped_highlights.copy_from_slice(&data[clipped_start + 4..clipped_start + 8]);
+
         ImageHistogram {
             r,
             g,
             b,
             luma,
             num_bins,
+            clipped_shadows,
+            clipped_highlights,
         }
     }
+
+    pub fn buffer_len() -> usize {
+        4 * ImageHistogram::max_bins() + 1 + 8
+    }
+}
+
+#[cfg(test)]
+impl ImageHistogram {
+    // a synthetic histogram of `num_bins` bins, with only the given luma counts (e.g. for tests of its users)
+    pub fn from_luma_counts(num_bins: u32, counts: &[(usize, u32)]) -> Self {
+        let mut luma = vec![0; ImageHistogram::max_bins()];
+        for (bin, count) in counts {
+            luma[*bin] += *count;
+        }
+        ImageHistogram {
+            r: vec![0; ImageHistogram::max_bins()],
+            g: vec![0; ImageHistogram::max_bins()],
+            b: vec![0; ImageHistogram::max_bins()],
+            luma,
+            num_bins,
+            clipped_shadows: [0; 4],
+            clipped_highlights: [0; 4],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    // a histogram of 17 bins (so that bin i is centered at (i + 0.5) / 16), with the given luma counts
+    fn luma_histogram(counts: &[(usize, u32)]) -> ImageHistogram {
+        ImageHistogram::from_luma_counts(17, counts)
+    }
+
+    #[test]
+    fn statistics_of_empty_histogram_are_zero() {
+        let histogram = luma_histogram(&[]);
+        assert_close(histogram.mean(HistogramChannel::Luma), 0.0);
+        assert_close(histogram.median(HistogramChannel::Luma), 0.0);
+        assert_close(histogram.percentile(HistogramChannel::Luma, 0.9), 0.0);
+    }
+
+    #[test]
+    fn statistics_of_single_bin_are_its_value() {
+        let histogram = luma_histogram(&[(4, 100)]);
+        let value = histogram.bin_value(4);
+        assert_close(value, 4.5 / 16.0);
+        assert_close(histogram.mean(HistogramChannel::Luma), value);
+        assert_close(histogram.median(HistogramChannel::Luma), value);
+        assert_close(histogram.percentile(HistogramChannel::Luma, 0.01), value);
+        assert_close(histogram.percentile(HistogramChannel::Luma, 0.99), value);
+    }
+
+    #[test]
+    fn mean_is_weighted_by_counts() {
+        let histogram = luma_histogram(&[(2, 30), (10, 10)]);
+        let expected = (30.0 * histogram.bin_value(2) + 10.0 * histogram.bin_value(10)) / 40.0;
+        assert_close(histogram.mean(HistogramChannel::Luma), expected);
+    }
+
+    #[test]
+    fn percentile_is_first_bin_reaching_fraction() {
+        let histogram = luma_histogram(&[(0, 10), (5, 40), (12, 50)]);
+        assert_close(
+            histogram.percentile(HistogramChannel::Luma, 0.05),
+            histogram.bin_value(0),
+        );
+        assert_close(
+            histogram.percentile(HistogramChannel::Luma, 0.15),
+            histogram.bin_value(5),
+        );
+        assert_close(
+            histogram.median(HistogramChannel::Luma),
+            histogram.bin_value(5),
+        );
+        assert_close(
+            histogram.percentile(HistogramChannel::Luma, 0.55),
+            histogram.bin_value(12),
+        );
+        assert_close(
+            histogram.percentile(HistogramChannel::Luma, 1.0),
+            histogram.bin_value(12),
+        );
+    }
+
+    #[test]
+    fn bins_beyond_num_bins_are_ignored() {
+        let histogram = luma_histogram(&[(3, 10), (200, 1000)]);
+        assert_eq!(histogram.num_pixels(HistogramChannel::Luma), 10);
+        assert_close(
+            histogram.median(HistogramChannel::Luma),
+            histogram.bin_value(3),
+        );
+    }
+
+    #[test]
+    fn last_bin_value_is_clamped_to_one() {
+        let histogram = luma_histogram(&[(16, 1)]);
+        assert_close(histogram.bin_value(16), 1.0);
+        assert_close(histogram.mean(HistogramChannel::Luma), 1.0);
+    }
 }
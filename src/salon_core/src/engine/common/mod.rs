@@ -1,3 +1,5 @@
 mod histogram;
+mod scope;
 
-pub use histogram::*;
\ No newline at end of file
+pub use histogram::*;
+pub use scope::*;
@@ -0,0 +1,60 @@
+// matches waveform.wgsl
+const WAVEFORM_NUM_COLUMNS: u32 = 240;
+const WAVEFORM_NUM_LEVELS: u32 = 128;
+// matches vectorscope.wgsl
+const VECTORSCOPE_SIZE: u32 = 128;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScopeKind {
+    LumaWaveform,
+    // the waveforms of the r, g and b channels, side by side
+    RgbParade,
+    Vectorscope,
+}
+
+impl ScopeKind {
+    // the number of columns and rows of the scope
+    pub fn dimensions(&self) -> (u32, u32) {
+        match *self {
+            ScopeKind::LumaWaveform | ScopeKind::RgbParade => {
+                (WAVEFORM_NUM_COLUMNS, WAVEFORM_NUM_LEVELS)
+            }
+            ScopeKind::Vectorscope => (VECTORSCOPE_SIZE, VECTORSCOPE_SIZE),
+        }
+    }
+}
+
+// the number of pixels that fall into each cell of a scope.
+// for waveforms, columns correspond to those of the image, and rows to (sRGB encoded) levels, from black to white.
+// in the rgb parade, the first, second and last third of the columns are the r, g and b channels.
+// for the vectorscope, columns and rows are the (BT.709) Cb and Cr of the pixels, with Cr increasing upwards.
+pub struct ImageScope {
+    pub kind: ScopeKind,
+    pub dimensions: (u32, u32),
+    // row-major, with the first row being the bottom one
+    pub counts: Vec<u32>,
+}
+
+impl ImageScope {
+    pub fn buffer_len_for(kind: ScopeKind) -> usize {
+        let (w, h) = kind.dimensions();
+        (w * h) as usize
+    }
+
+    pub fn from_u32_slice(kind: ScopeKind, data: &[u32]) -> Self {
+        let len = ImageScope::buffer_len_for(kind);
+        ImageScope {
+            kind,
+            dimensions: kind.dimensions(),
+            counts: data[0..len].to_vec(),
+        }
+    }
+
+    pub fn count(&self, x: u32, y: u32) -> u32 {
+        self.counts[(y * self.dimensions.0 + x) as usize]
+    }
+
+    pub fn max_count(&self) -> u32 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+}
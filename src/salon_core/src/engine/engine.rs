@@ -1,19 +1,22 @@
-use std::{sync::Arc};
+use std::{path::PathBuf, sync::Arc};
 
 
 
 use crate::{
-    ir::{Module, Op, Value},
+    ir::{ImageMask, Module, Op, Value},
     runtime::{Image, Runtime, Toolbox},
 };
 
 use super::{
+    mask_image_store::MaskImageStore,
     op_impl_collection::OpImplCollection,
     ops::{
         add_mask::AddMaskImpl,
         apply_masked_edits::ApplyMaskedEditsImpl,
         basic_statistics::ComputeBasicStatisticsImpl,
+        brush_mask::ComputeBrushMaskImpl,
         color_mix::ColorMixImpl,
+        color_range_mask::ComputeColorRangeMaskImpl,
         contrast::AdjustContrastImpl,
         curve::ApplyCurveImpl,
         dehaze_apply::ApplyDehazeImpl,
@@ -23,15 +26,24 @@ use super::{
         global_mask::ComputeGlobalMaskImpl,
         highlights_shadows::AdjustHighlightsAndShadowsImpl,
         histogram::{ComputeHistogramImpl},
+        image_mask::ComputeImageMaskImpl,
+        intersect_mask::IntersectMaskImpl,
         invert_mask::InvertMaskImpl,
         linear_gradient_mask::ComputeLinearGradientMaskImpl,
+        luminance_range_mask::ComputeLuminanceRangeMaskImpl,
+        polygon_mask::ComputePolygonMaskImpl,
         radial_gradient_mask::ComputeRadialGradientMaskImpl,
+        red_eye::ApplyRedEyeCorrectionImpl,
+        refine_mask::RefineMaskImpl,
         resize::ResizeImpl,
+        retouch_spot::ApplyRetouchSpotImpl,
         rotate_and_crop::RotateAndCropImpl,
         subtract_mask::{SubtractMaskImpl},
         temperature_tint::AdjustTemperatureAndTintImpl,
+        vectorscope::ComputeVectorscopeImpl,
         vibrance_saturation::AdjustVibranceAndSaturationImpl,
         vignette::AdjustVignetteImpl,
+        waveform::ComputeWaveformImpl,
     },
     ExecutionContext,
 };
@@ -40,17 +52,29 @@ pub struct Engine {
     runtime: Arc<Runtime>,
     toolbox: Arc<Toolbox>,
     op_impls: OpImplCollection,
+    mask_images: MaskImageStore,
 }
 
 impl Engine {
     pub fn new(runtime: Arc<Runtime>, toolbox: Arc<Toolbox>) -> Self {
+        let mask_images = MaskImageStore::new(runtime.clone(), toolbox.clone());
         Engine {
             runtime,
             toolbox,
             op_impls: OpImplCollection::new(),
+            mask_images,
         }
     }
 
+    pub fn import_mask_image(&mut self, path: PathBuf) -> Result<ImageMask, String> {
+        self.mask_images.import(path)
+    }
+
+    // mask images that couldn't be loaded are tried again on the next execution
+    pub fn retry_unavailable_mask_images(&mut self) {
+        self.mask_images.retry_unavailable();
+    }
+
     pub fn execute_module(
         &mut self,
         module: &Module,
@@ -58,6 +82,7 @@ impl Engine {
         execution_context: &mut ExecutionContext,
     ) {
         self.reset_op_impls(module);
+        self.mask_images.load_missing(module);
         self.apply_ops(module, input_img, execution_context);
     }
 
@@ -210,6 +235,22 @@ impl Engine {
                         &mut self.toolbox,
                     );
                 }
+                Op::ComputeWaveform(ref op) => {
+                    self.op_impls.waveform.as_mut().unwrap().encode_commands(
+                        &mut encoder,
+                        op,
+                        &mut execution_context.value_store,
+                        &mut self.toolbox,
+                    );
+                }
+                Op::ComputeVectorscope(ref op) => {
+                    self.op_impls.vectorscope.as_mut().unwrap().encode_commands(
+                        &mut encoder,
+                        op,
+                        &mut execution_context.value_store,
+                        &mut self.toolbox,
+                    );
+                }
                 Op::RotateAndCrop(ref op) => {
                     self.op_impls
                         .rotate_and_crop
@@ -262,6 +303,59 @@ impl Engine {
                             &mut self.toolbox,
                         );
                 }
+                Op::ComputeBrushMask(ref op) => {
+                    self.op_impls.brush_mask.as_mut().unwrap().encode_commands(
+                        &mut encoder,
+                        op,
+                        &mut execution_context.value_store,
+                        &mut self.toolbox,
+                    );
+                }
+                Op::ComputePolygonMask(ref op) => {
+                    self.op_impls.polygon_mask.as_mut().unwrap().encode_commands(
+                        &mut encoder,
+                        op,
+                        &mut execution_context.value_store,
+                        &mut self.toolbox,
+                    );
+                }
+                Op::ComputeLuminanceRangeMask(ref op) => {
+                    self.op_impls
+                        .luminance_range_mask
+                        .as_mut()
+                        .unwrap()
+                        .encode_commands(
+                            &mut encoder,
+                            op,
+                            &mut execution_context.value_store,
+                            &mut self.toolbox,
+                        );
+                }
+                Op::ComputeColorRangeMask(ref op) => {
+                    self.op_impls
+                        .color_range_mask
+                        .as_mut()
+                        .unwrap()
+                        .encode_commands(
+                            &mut encoder,
+                            op,
+                            &mut execution_context.value_store,
+                            &mut self.toolbox,
+                        );
+                }
+                Op::ComputeImageMask(ref op) => {
+                    self.op_impls
+                        .image_mask
+                        .as_mut()
+                        .unwrap()
+                        .encode_commands(
+                            &mut encoder,
+                            op,
+                            &mut execution_context.value_store,
+                            &self.mask_images,
+                            &mut self.toolbox,
+                        );
+                }
                 Op::AddMask(ref op) => {
                     self.op_impls.add_mask.as_mut().unwrap().encode_commands(
                         &mut encoder,
@@ -282,6 +376,18 @@ impl Engine {
                             &mut self.toolbox,
                         );
                 }
+                Op::IntersectMask(ref op) => {
+                    self.op_impls
+                        .intersect_mask
+                        .as_mut()
+                        .unwrap()
+                        .encode_commands(
+                            &mut encoder,
+                            op,
+                            &mut execution_context.value_store,
+                            &mut self.toolbox,
+                        );
+                }
                 Op::InvertMask(ref op) => {
                     self.op_impls.invert_mask.as_mut().unwrap().encode_commands(
                         &mut encoder,
@@ -290,6 +396,14 @@ impl Engine {
                         &mut self.toolbox,
                     );
                 }
+                Op::RefineMask(ref op) => {
+                    self.op_impls.refine_mask.as_mut().unwrap().encode_commands(
+                        &mut encoder,
+                        op,
+                        &mut execution_context.value_store,
+                        &mut self.toolbox,
+                    );
+                }
                 Op::ApplyMaskedEdits(ref op) => {
                     self.op_impls
                         .apply_masked_edits
@@ -310,6 +424,22 @@ impl Engine {
                         &mut self.toolbox,
                     );
                 }
+                Op::ApplyRetouchSpot(ref op) => {
+                    self.op_impls.retouch_spot.as_mut().unwrap().encode_commands(
+                        &mut encoder,
+                        op,
+                        &mut execution_context.value_store,
+                        &mut self.toolbox,
+                    );
+                }
+                Op::ApplyRedEyeCorrection(ref op) => {
+                    self.op_impls.red_eye.as_mut().unwrap().encode_commands(
+                        &mut encoder,
+                        op,
+                        &mut execution_context.value_store,
+                        &mut self.toolbox,
+                    );
+                }
             }
         }
 
@@ -402,6 +532,20 @@ impl Engine {
                     }
                     self.op_impls.histogram.as_mut().unwrap().reset();
                 }
+                Op::ComputeWaveform(_) => {
+                    if self.op_impls.waveform.is_none() {
+                        self.op_impls.waveform =
+                            Some(ComputeWaveformImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.waveform.as_mut().unwrap().reset();
+                }
+                Op::ComputeVectorscope(_) => {
+                    if self.op_impls.vectorscope.is_none() {
+                        self.op_impls.vectorscope =
+                            Some(ComputeVectorscopeImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.vectorscope.as_mut().unwrap().reset();
+                }
                 Op::RotateAndCrop(_) => {
                     if self.op_impls.rotate_and_crop.is_none() {
                         self.op_impls.rotate_and_crop =
@@ -436,6 +580,41 @@ impl Engine {
                     }
                     self.op_impls.linear_gradient_mask.as_mut().unwrap().reset();
                 }
+                Op::ComputeBrushMask(_) => {
+                    if self.op_impls.brush_mask.is_none() {
+                        self.op_impls.brush_mask =
+                            Some(ComputeBrushMaskImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.brush_mask.as_mut().unwrap().reset();
+                }
+                Op::ComputePolygonMask(_) => {
+                    if self.op_impls.polygon_mask.is_none() {
+                        self.op_impls.polygon_mask =
+                            Some(ComputePolygonMaskImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.polygon_mask.as_mut().unwrap().reset();
+                }
+                Op::ComputeLuminanceRangeMask(_) => {
+                    if self.op_impls.luminance_range_mask.is_none() {
+                        self.op_impls.luminance_range_mask =
+                            Some(ComputeLuminanceRangeMaskImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.luminance_range_mask.as_mut().unwrap().reset();
+                }
+                Op::ComputeColorRangeMask(_) => {
+                    if self.op_impls.color_range_mask.is_none() {
+                        self.op_impls.color_range_mask =
+                            Some(ComputeColorRangeMaskImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.color_range_mask.as_mut().unwrap().reset();
+                }
+                Op::ComputeImageMask(_) => {
+                    if self.op_impls.image_mask.is_none() {
+                        self.op_impls.image_mask =
+                            Some(ComputeImageMaskImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.image_mask.as_mut().unwrap().reset();
+                }
                 Op::AddMask(_) => {
                     if self.op_impls.add_mask.is_none() {
                         self.op_impls.add_mask = Some(AddMaskImpl::new(self.runtime.clone()))
@@ -449,12 +628,25 @@ impl Engine {
                     }
                     self.op_impls.subtract_mask.as_mut().unwrap().reset();
                 }
+                Op::IntersectMask(_) => {
+                    if self.op_impls.intersect_mask.is_none() {
+                        self.op_impls.intersect_mask =
+                            Some(IntersectMaskImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.intersect_mask.as_mut().unwrap().reset();
+                }
                 Op::InvertMask(_) => {
                     if self.op_impls.invert_mask.is_none() {
                         self.op_impls.invert_mask = Some(InvertMaskImpl::new(self.runtime.clone()))
                     }
                     self.op_impls.invert_mask.as_mut().unwrap().reset();
                 }
+                Op::RefineMask(_) => {
+                    if self.op_impls.refine_mask.is_none() {
+                        self.op_impls.refine_mask = Some(RefineMaskImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.refine_mask.as_mut().unwrap().reset();
+                }
                 Op::ApplyMaskedEdits(_) => {
                     if self.op_impls.apply_masked_edits.is_none() {
                         self.op_impls.apply_masked_edits =
@@ -468,6 +660,20 @@ impl Engine {
                     }
                     self.op_impls.framing.as_mut().unwrap().reset();
                 }
+                Op::ApplyRetouchSpot(_) => {
+                    if self.op_impls.retouch_spot.is_none() {
+                        self.op_impls.retouch_spot =
+                            Some(ApplyRetouchSpotImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.retouch_spot.as_mut().unwrap().reset();
+                }
+                Op::ApplyRedEyeCorrection(_) => {
+                    if self.op_impls.red_eye.is_none() {
+                        self.op_impls.red_eye =
+                            Some(ApplyRedEyeCorrectionImpl::new(self.runtime.clone()))
+                    }
+                    self.op_impls.red_eye.as_mut().unwrap().reset();
+                }
             }
         }
     }
@@ -42,6 +42,11 @@ impl ExecutionContext {
         let last_module = self.last_module.as_ref().unwrap();
         let result_id_to_op_index = self.last_module_result_id_to_op_index.as_ref().unwrap();
         for op in new_module.ops().iter() {
+            // the previous result might still be reading the buffer (e.g. it belongs to another virtual copy
+            // of the image), so these are computed again, into a new buffer if the previous one is still mapped
+            if op.result_is_read_by_host() {
+                continue;
+            }
             let result_id = op.get_result_id();
             if let Some(last_index) = result_id_to_op_index.get(&result_id) {
                 let last_op = &last_module.ops()[*last_index];
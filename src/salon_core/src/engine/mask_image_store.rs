@@ -0,0 +1,99 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use crate::{
+    ir::{ImageMask, Module, Op},
+    runtime::{Image, Runtime, Toolbox},
+};
+
+// the decoded images of image masks, so that files are only read when a mask is imported or first used
+pub struct MaskImageStore {
+    runtime: Arc<Runtime>,
+    toolbox: Arc<Toolbox>,
+    // keyed by path and content hash
+    images: HashMap<(PathBuf, String), Arc<Image>>,
+    // masks whose file is missing, couldn't be decoded, or has changed since it was imported.
+    // these aren't read again until `retry_unavailable` is called.
+    unavailable: HashSet<(PathBuf, String)>,
+}
+
+impl MaskImageStore {
+    pub fn new(runtime: Arc<Runtime>, toolbox: Arc<Toolbox>) -> Self {
+        MaskImageStore {
+            runtime,
+            toolbox,
+            images: HashMap::new(),
+            unavailable: HashSet::new(),
+        }
+    }
+
+    // reads and decodes the file, which the returned mask refers to by its current content
+    pub fn import(&mut self, path: PathBuf) -> Result<ImageMask, String> {
+        let path_str = path.to_str().unwrap_or("").to_owned();
+        let image_bytes = std::fs::read(&path)
+            .map_err(|e| format!("could not read mask image at path {}: {}", path_str, e))?;
+        let image = self
+            .decode(image_bytes.as_slice())
+            .ok_or(format!("could not decode mask image at path {}", path_str))?;
+        let mask = ImageMask {
+            path,
+            content_hash: sha256::digest(image_bytes.as_slice()),
+        };
+        let key = (mask.path.clone(), mask.content_hash.clone());
+        self.unavailable.remove(&key);
+        self.images.insert(key, image);
+        Ok(mask)
+    }
+
+    pub fn get(&self, mask: &ImageMask) -> Option<&Arc<Image>> {
+        self.images
+            .get(&(mask.path.clone(), mask.content_hash.clone()))
+    }
+
+    // loads the images of the image masks in the module that haven't been loaded (or found unavailable) yet
+    pub fn load_missing(&mut self, module: &Module) {
+        for op in module.ops() {
+            let Op::ComputeImageMask(ref op) = op else {
+                continue;
+            };
+            let key = (op.mask.path.clone(), op.mask.content_hash.clone());
+            if self.images.contains_key(&key) || self.unavailable.contains(&key) {
+                continue;
+            }
+            match self.load(&op.mask) {
+                Some(image) => {
+                    self.images.insert(key, image);
+                }
+                None => {
+                    log::warn!("mask image unavailable: {:?}", op.mask.path);
+                    self.unavailable.insert(key);
+                }
+            }
+        }
+    }
+
+    // lets masks that were unavailable be read again, e.g. in case their file was restored
+    pub fn retry_unavailable(&mut self) {
+        self.unavailable.clear();
+    }
+
+    fn load(&self, mask: &ImageMask) -> Option<Arc<Image>> {
+        let image_bytes = std::fs::read(&mask.path).ok()?;
+        if sha256::digest(image_bytes.as_slice()) != mask.content_hash {
+            return None;
+        }
+        self.decode(image_bytes.as_slice())
+    }
+
+    fn decode(&self, image_bytes: &[u8]) -> Option<Arc<Image>> {
+        let img = image::load_from_memory(image_bytes).ok()?;
+        // the gray values are used as the mask values as-is, keeping the precision of 16-bit images
+        let img = image::DynamicImage::ImageLuma16(img.to_luma16());
+        let image = Arc::new(self.runtime.create_rgba16float_image_from_dynamic_image(img));
+        self.toolbox.generate_mipmap(&image);
+        Some(image)
+    }
+}
@@ -1,4 +1,5 @@
 mod engine;
+mod mask_image_store;
 mod ops;
 mod op_impl_collection;
 mod value_store;
@@ -1,5 +1,5 @@
 use super::ops::{
-    add_mask::AddMaskImpl, apply_masked_edits::ApplyMaskedEditsImpl, basic_statistics::ComputeBasicStatisticsImpl, color_mix::ColorMixImpl, contrast::AdjustContrastImpl, curve::ApplyCurveImpl, dehaze_apply::ApplyDehazeImpl, dehaze_prepare::PrepareDehazeImpl, exposure::AdjustExposureImpl, framing::ApplyFramingImpl, global_mask::ComputeGlobalMaskImpl, highlights_shadows::AdjustHighlightsAndShadowsImpl, histogram::ComputeHistogramImpl, invert_mask::InvertMaskImpl, linear_gradient_mask::ComputeLinearGradientMaskImpl, radial_gradient_mask::ComputeRadialGradientMaskImpl, resize::ResizeImpl, rotate_and_crop::RotateAndCropImpl, subtract_mask::SubtractMaskImpl, temperature_tint::AdjustTemperatureAndTintImpl, vibrance_saturation::AdjustVibranceAndSaturationImpl, vignette::AdjustVignetteImpl
+    add_mask::AddMaskImpl, apply_masked_edits::ApplyMaskedEditsImpl, basic_statistics::ComputeBasicStatisticsImpl, brush_mask::ComputeBrushMaskImpl, color_mix::ColorMixImpl, color_range_mask::ComputeColorRangeMaskImpl, contrast::AdjustContrastImpl, curve::ApplyCurveImpl, dehaze_apply::ApplyDehazeImpl, dehaze_prepare::PrepareDehazeImpl, exposure::AdjustExposureImpl, framing::ApplyFramingImpl, global_mask::ComputeGlobalMaskImpl, highlights_shadows::AdjustHighlightsAndShadowsImpl, histogram::ComputeHistogramImpl, waveform::ComputeWaveformImpl, vectorscope::ComputeVectorscopeImpl, image_mask::ComputeImageMaskImpl, intersect_mask::IntersectMaskImpl, invert_mask::InvertMaskImpl, linear_gradient_mask::ComputeLinearGradientMaskImpl, luminance_range_mask::ComputeLuminanceRangeMaskImpl, polygon_mask::ComputePolygonMaskImpl, radial_gradient_mask::ComputeRadialGradientMaskImpl, red_eye::ApplyRedEyeCorrectionImpl, refine_mask::RefineMaskImpl, resize::ResizeImpl, retouch_spot::ApplyRetouchSpotImpl, rotate_and_crop::RotateAndCropImpl, subtract_mask::SubtractMaskImpl, temperature_tint::AdjustTemperatureAndTintImpl, vibrance_saturation::AdjustVibranceAndSaturationImpl, vignette::AdjustVignetteImpl
 };
 
 #[derive(Default)]
@@ -16,6 +16,8 @@ pub struct OpImplCollection {
     pub apply_dehaze: Option<ApplyDehazeImpl>,
     pub basic_statistics: Option<ComputeBasicStatisticsImpl>,
     pub histogram: Option<ComputeHistogramImpl>,
+    pub waveform: Option<ComputeWaveformImpl>,
+    pub vectorscope: Option<ComputeVectorscopeImpl>,
     pub rotate_and_crop: Option<RotateAndCropImpl>,
     pub resize: Option<ResizeImpl>,
     pub global_mask: Option<ComputeGlobalMaskImpl>,
@@ -23,9 +25,18 @@ pub struct OpImplCollection {
     pub linear_gradient_mask: Option<ComputeLinearGradientMaskImpl>,
     pub add_mask: Option<AddMaskImpl>,
     pub subtract_mask: Option<SubtractMaskImpl>,
+    pub intersect_mask: Option<IntersectMaskImpl>,
     pub invert_mask: Option<InvertMaskImpl>,
+    pub refine_mask: Option<RefineMaskImpl>,
     pub apply_masked_edits: Option<ApplyMaskedEditsImpl>,
     pub framing: Option<ApplyFramingImpl>,
+    pub retouch_spot: Option<ApplyRetouchSpotImpl>,
+    pub red_eye: Option<ApplyRedEyeCorrectionImpl>,
+    pub brush_mask: Option<ComputeBrushMaskImpl>,
+    pub polygon_mask: Option<ComputePolygonMaskImpl>,
+    pub luminance_range_mask: Option<ComputeLuminanceRangeMaskImpl>,
+    pub color_range_mask: Option<ComputeColorRangeMaskImpl>,
+    pub image_mask: Option<ComputeImageMaskImpl>,
 }
 
 impl OpImplCollection {
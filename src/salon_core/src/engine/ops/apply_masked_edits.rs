@@ -1,14 +1,15 @@
-use std::{sync::Arc};
+use std::{mem::size_of, sync::Arc};
 
 use crate::runtime::Toolbox;
 
 use crate::{
     engine::{value_store::ValueStore},
-    ir::{ApplyMaskedEditsOp},
+    ir::{ApplyMaskedEditsOp, BlendMode},
     runtime::{
         BindGroupDescriptor, BindGroupEntry, BindGroupManager,
         BindingResource, Runtime,
     },
+    runtime::{BufferProperties, RingBuffer},
     shader::{Shader, ShaderLibraryModule},
     utils::math::div_up,
 };
@@ -17,6 +18,7 @@ pub struct ApplyMaskedEditsImpl {
     runtime: Arc<Runtime>,
     pipeline: wgpu::ComputePipeline,
     bind_group_manager: BindGroupManager,
+    ring_buffer: RingBuffer,
 }
 impl ApplyMaskedEditsImpl {
     pub fn new(runtime: Arc<Runtime>) -> Self {
@@ -29,15 +31,25 @@ impl ApplyMaskedEditsImpl {
 
         let bind_group_manager = BindGroupManager::new(runtime.clone(), bind_group_layout);
 
+        let ring_buffer = RingBuffer::new(
+            runtime.clone(),
+            BufferProperties {
+                size: size_of::<f32>() + size_of::<u32>(),
+                host_readable: false,
+            },
+        );
+
         ApplyMaskedEditsImpl {
             runtime,
             pipeline,
             bind_group_manager,
+            ring_buffer,
         }
     }
 }
 impl ApplyMaskedEditsImpl {
     pub fn reset(&mut self) {
+        self.ring_buffer.mark_all_available();
         self.bind_group_manager.clear_cache();
     }
 
@@ -63,6 +75,23 @@ impl ApplyMaskedEditsImpl {
             &edited_img.properties,
         );
 
+        let buffer = self.ring_buffer.get();
+
+        let blend_mode = match op.blend_mode {
+            BlendMode::Normal => 0u32,
+            BlendMode::Luminosity => 1u32,
+            BlendMode::Color => 2u32,
+        };
+
+        self.runtime
+            .queue
+            .write_buffer(&buffer.buffer, 0, bytemuck::cast_slice(&[op.opacity]));
+        self.runtime.queue.write_buffer(
+            &buffer.buffer,
+            size_of::<f32>() as u64,
+            bytemuck::cast_slice(&[blend_mode]),
+        );
+
         let bind_group = self.bind_group_manager.get_or_create(BindGroupDescriptor {
             entries: vec![
                 BindGroupEntry {
@@ -81,6 +110,10 @@ impl ApplyMaskedEditsImpl {
                     binding: 3,
                     resource: BindingResource::TextureStorage(&output_img, 0),
                 },
+                BindGroupEntry {
+                    binding: 4,
+                    resource: BindingResource::Buffer(buffer),
+                },
             ],
         });
 
@@ -0,0 +1,201 @@
+use std::{mem::size_of, sync::Arc};
+
+use crate::runtime::Toolbox;
+
+use crate::{
+    engine::value_store::ValueStore,
+    ir::ComputeBrushMaskOp,
+    runtime::{
+        BindGroupDescriptor, BindGroupEntry, BindGroupManager,
+        BindingResource, Runtime,
+    },
+    runtime::{BufferProperties, RingBuffer},
+    runtime::{ImageFormat, ImageProperties},
+    shader::Shader,
+    utils::math::div_up,
+};
+
+pub struct ComputeBrushMaskImpl {
+    runtime: Arc<Runtime>,
+    pipeline: wgpu::ComputePipeline,
+    bind_group_manager: BindGroupManager,
+    ring_buffer: RingBuffer,
+    // the strokes and points are of variable length, so their ring buffers are replaced by larger ones
+    // whenever a mask doesn't fit
+    ring_buffer_strokes: RingBuffer,
+    ring_buffer_points: RingBuffer,
+    max_strokes: usize,
+    max_points: usize,
+}
+impl ComputeBrushMaskImpl {
+    pub fn new(runtime: Arc<Runtime>) -> Self {
+        let shader_code = Shader::from_code(include_str!("shaders/brush_mask.wgsl")).full_code();
+
+        let (pipeline, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("BrushMask"));
+
+        let bind_group_manager = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        let ring_buffer = RingBuffer::new(
+            runtime.clone(),
+            BufferProperties {
+                size: size_of::<u32>(),
+                host_readable: false,
+            },
+        );
+
+        let max_strokes = 64;
+        let max_points = 4096;
+        let ring_buffer_strokes = Self::create_strokes_ring_buffer(runtime.clone(), max_strokes);
+        let ring_buffer_points = Self::create_points_ring_buffer(runtime.clone(), max_points);
+
+        ComputeBrushMaskImpl {
+            runtime,
+            pipeline,
+            bind_group_manager,
+            ring_buffer,
+            ring_buffer_strokes,
+            ring_buffer_points,
+            max_strokes,
+            max_points,
+        }
+    }
+
+    fn create_strokes_ring_buffer(runtime: Arc<Runtime>, max_strokes: usize) -> RingBuffer {
+        RingBuffer::new(
+            runtime,
+            BufferProperties {
+                size: size_of::<[u32; 8]>() * max_strokes,
+                host_readable: false,
+            },
+        )
+    }
+
+    fn create_points_ring_buffer(runtime: Arc<Runtime>, max_points: usize) -> RingBuffer {
+        RingBuffer::new(
+            runtime,
+            BufferProperties {
+                size: size_of::<f32>() * 2 * max_points,
+                host_readable: false,
+            },
+        )
+    }
+}
+impl ComputeBrushMaskImpl {
+    pub fn reset(&mut self) {
+        self.ring_buffer.mark_all_available();
+        self.bind_group_manager.clear_cache();
+        self.ring_buffer_strokes.mark_all_available();
+        self.ring_buffer_points.mark_all_available();
+    }
+
+    pub fn encode_commands(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        op: &ComputeBrushMaskOp,
+        value_store: &mut ValueStore,
+        toolbox: &Toolbox,
+    ) {
+        let target_img = value_store.map.get(&op.target).unwrap().as_image().clone();
+
+        let mask_img_properties = ImageProperties {
+            format: ImageFormat::Rgba16Float,
+            ..target_img.properties
+        };
+        let output_img = value_store.ensure_value_at_id_is_image_of_properties(
+            self.runtime.as_ref(),
+            op.result,
+            &mask_img_properties,
+        );
+
+        // matches the Stroke struct in brush_mask.wgsl
+        let mut strokes_data: Vec<u32> = Vec::new();
+        let mut points_data: Vec<f32> = Vec::new();
+        for stroke in op.mask.strokes.iter() {
+            if stroke.points.is_empty() {
+                continue;
+            }
+            strokes_data.push((points_data.len() / 2) as u32);
+            strokes_data.push(stroke.points.len() as u32);
+            strokes_data.push(stroke.size.to_bits());
+            strokes_data.push(stroke.feather.to_bits());
+            strokes_data.push(stroke.flow.to_bits());
+            strokes_data.push(stroke.erase as u32);
+            strokes_data.push(0);
+            strokes_data.push(0);
+            for (x, y) in stroke.points.iter() {
+                points_data.push(*x);
+                points_data.push(*y);
+            }
+        }
+        let num_strokes = (strokes_data.len() / 8) as u32;
+
+        // buffers that are replaced are kept alive by the commands already encoded with them
+        if strokes_data.len() / 8 > self.max_strokes {
+            self.max_strokes = (strokes_data.len() / 8).next_power_of_two();
+            self.ring_buffer_strokes =
+                Self::create_strokes_ring_buffer(self.runtime.clone(), self.max_strokes);
+        }
+        if points_data.len() / 2 > self.max_points {
+            self.max_points = (points_data.len() / 2).next_power_of_two();
+            self.ring_buffer_points =
+                Self::create_points_ring_buffer(self.runtime.clone(), self.max_points);
+        }
+
+        let strokes_buffer = self.ring_buffer_strokes.get();
+        let points_buffer = self.ring_buffer_points.get();
+        if !strokes_data.is_empty() {
+            self.runtime.queue.write_buffer(
+                &strokes_buffer.buffer,
+                0,
+                bytemuck::cast_slice(strokes_data.as_slice()),
+            );
+            self.runtime.queue.write_buffer(
+                &points_buffer.buffer,
+                0,
+                bytemuck::cast_slice(points_data.as_slice()),
+            );
+        }
+
+        let buffer = self.ring_buffer.get();
+        self.runtime
+            .queue
+            .write_buffer(&buffer.buffer, 0, bytemuck::cast_slice(&[num_strokes]));
+
+        let bind_group = self.bind_group_manager.get_or_create(BindGroupDescriptor {
+            entries: vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(buffer),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Buffer(strokes_buffer),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Buffer(points_buffer),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: BindingResource::TextureStorage(output_img, 0),
+                },
+            ],
+        });
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+
+            let num_workgroups_x = div_up(output_img.properties.dimensions.0, 16);
+            let num_workgroups_y = div_up(output_img.properties.dimensions.1, 16);
+
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+
+        toolbox.encode_mipmap_generation_command(output_img, encoder);
+    }
+}
@@ -78,7 +78,7 @@ impl ComputeHistogramImpl {
         let input_img = value_store.map.get(&op.arg).unwrap().as_image().clone();
 
         let buffer_props = BufferProperties {
-            size: (4 * ImageHistogram::max_bins() + 1) * size_of::<u32>(),
+            size: ImageHistogram::buffer_len() * size_of::<u32>(),
             host_readable: true,
         };
 
@@ -89,7 +89,10 @@ impl ComputeHistogramImpl {
         );
 
         let uniform_buffer = self.ring_buffer.get();
-        let num_bins = ImageHistogram::num_bins_for(input_img.properties.dimensions);
+        let num_bins = match op.num_bins {
+            Some(n) => (n as usize).clamp(ImageHistogram::min_bins(), ImageHistogram::max_bins()),
+            None => ImageHistogram::num_bins_for(input_img.properties.dimensions),
+        };
         self.runtime.queue.write_buffer(
             &uniform_buffer.buffer,
             0,
@@ -0,0 +1,162 @@
+use std::{mem::size_of, sync::Arc};
+
+use crate::runtime::{Image, Toolbox};
+
+use crate::{
+    engine::{mask_image_store::MaskImageStore, value_store::ValueStore},
+    ir::ComputeImageMaskOp,
+    runtime::{
+        BindGroupDescriptor, BindGroupEntry, BindGroupManager,
+        BindingResource, Runtime,
+    },
+    runtime::{BufferProperties, ColorSpace, RingBuffer, Sampler},
+    runtime::{ImageFormat, ImageProperties},
+    shader::Shader,
+    utils::math::div_up,
+};
+
+pub struct ComputeImageMaskImpl {
+    runtime: Arc<Runtime>,
+    pipeline: wgpu::ComputePipeline,
+    bind_group_manager: BindGroupManager,
+    ring_buffer: RingBuffer,
+    texture_sampler: Sampler,
+    // bound in place of mask images that are unavailable
+    placeholder_image: Arc<Image>,
+}
+impl ComputeImageMaskImpl {
+    pub fn new(runtime: Arc<Runtime>) -> Self {
+        let shader_code = Shader::from_code(include_str!("shaders/image_mask.wgsl")).full_code();
+
+        let (pipeline, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("ImageMask"));
+
+        let bind_group_manager = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        let ring_buffer = RingBuffer::new(
+            runtime.clone(),
+            BufferProperties {
+                size: size_of::<f32>() * 6 + size_of::<u32>() * 2,
+                host_readable: false,
+            },
+        );
+
+        let texture_sampler = runtime.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let placeholder_image = Arc::new(runtime.create_image_of_properties(ImageProperties {
+            dimensions: (1, 1),
+            format: ImageFormat::Rgba8Unorm,
+            color_space: ColorSpace::sRGB,
+        }));
+
+        ComputeImageMaskImpl {
+            runtime,
+            pipeline,
+            bind_group_manager,
+            ring_buffer,
+            texture_sampler,
+            placeholder_image,
+        }
+    }
+}
+impl ComputeImageMaskImpl {
+    pub fn reset(&mut self) {
+        self.ring_buffer.mark_all_available();
+        self.bind_group_manager.clear_cache();
+    }
+
+    pub fn encode_commands(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        op: &ComputeImageMaskOp,
+        value_store: &mut ValueStore,
+        mask_images: &MaskImageStore,
+        toolbox: &Toolbox,
+    ) {
+        let target_img = value_store.map.get(&op.target).unwrap().as_image().clone();
+
+        let mask_img_properties = ImageProperties {
+            format: ImageFormat::Rgba16Float,
+            ..target_img.properties
+        };
+        let output_img = value_store.ensure_value_at_id_is_image_of_properties(
+            self.runtime.as_ref(),
+            op.result,
+            &mask_img_properties,
+        );
+
+        // loaded by `MaskImageStore` before the execution, and missing if the file is unavailable
+        let mask_image = mask_images.get(&op.mask).cloned();
+        let has_image = mask_image.is_some();
+        let mask_image = mask_image.unwrap_or(self.placeholder_image.clone());
+
+        // the mask image is usually much larger than the (possibly downsized) target
+        let source_height = mask_image.properties.dimensions.1 as f32 * op.crop_rect.size.y;
+        let lod = (source_height / output_img.properties.dimensions.1 as f32)
+            .log2()
+            .max(0.0);
+
+        let buffer = self.ring_buffer.get();
+        self.runtime.queue.write_buffer(
+            &buffer.buffer,
+            0,
+            bytemuck::cast_slice(&[
+                op.crop_rect.center.x,
+                op.crop_rect.center.y,
+                op.crop_rect.size.x,
+                op.crop_rect.size.y,
+                op.rotation_degrees.to_radians(),
+                lod,
+            ]),
+        );
+        self.runtime.queue.write_buffer(
+            &buffer.buffer,
+            (size_of::<f32>() * 6) as u64,
+            bytemuck::cast_slice(&[has_image as u32]),
+        );
+
+        let bind_group = self.bind_group_manager.get_or_create(BindGroupDescriptor {
+            entries: vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Texture(&mask_image),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&self.texture_sampler),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureStorage(output_img, 0),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: BindingResource::Buffer(buffer),
+                },
+            ],
+        });
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+
+            let num_workgroups_x = div_up(output_img.properties.dimensions.0, 16);
+            let num_workgroups_y = div_up(output_img.properties.dimensions.1, 16);
+
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+
+        toolbox.encode_mipmap_generation_command(output_img, encoder);
+    }
+}
@@ -5,6 +5,8 @@ pub mod temperature_tint;
 pub mod vibrance_saturation;
 pub mod basic_statistics;
 pub mod histogram;
+pub mod waveform;
+pub mod vectorscope;
 pub mod curve;
 pub mod color_mix;
 pub mod vignette;
@@ -15,8 +17,17 @@ pub mod resize;
 pub mod global_mask;
 pub mod radial_gradient_mask;
 pub mod linear_gradient_mask;
+pub mod brush_mask;
+pub mod polygon_mask;
+pub mod luminance_range_mask;
+pub mod color_range_mask;
+pub mod image_mask;
 pub mod add_mask;
 pub mod subtract_mask;
+pub mod intersect_mask;
 pub mod invert_mask;
+pub mod refine_mask;
 pub mod apply_masked_edits;
 pub mod framing;
+pub mod red_eye;
+pub mod retouch_spot;
@@ -0,0 +1,159 @@
+use std::{mem::size_of, sync::Arc};
+
+use crate::runtime::Toolbox;
+
+use crate::{
+    engine::value_store::ValueStore,
+    ir::ComputePolygonMaskOp,
+    runtime::{
+        BindGroupDescriptor, BindGroupEntry, BindGroupManager,
+        BindingResource, Runtime,
+    },
+    runtime::{BufferProperties, RingBuffer},
+    runtime::{ImageFormat, ImageProperties},
+    shader::Shader,
+    utils::math::div_up,
+};
+
+pub struct ComputePolygonMaskImpl {
+    runtime: Arc<Runtime>,
+    pipeline: wgpu::ComputePipeline,
+    bind_group_manager: BindGroupManager,
+    ring_buffer: RingBuffer,
+    // polygons have variable numbers of vertices, so the ring buffer is replaced by a larger one
+    // whenever a polygon doesn't fit
+    ring_buffer_vertices: RingBuffer,
+    max_vertices: usize,
+}
+impl ComputePolygonMaskImpl {
+    pub fn new(runtime: Arc<Runtime>) -> Self {
+        let shader_code = Shader::from_code(include_str!("shaders/polygon_mask.wgsl")).full_code();
+
+        let (pipeline, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("PolygonMask"));
+
+        let bind_group_manager = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        let ring_buffer = RingBuffer::new(
+            runtime.clone(),
+            BufferProperties {
+                size: size_of::<u32>() + size_of::<f32>(),
+                host_readable: false,
+            },
+        );
+
+        let max_vertices = 256;
+        let ring_buffer_vertices = Self::create_vertices_ring_buffer(runtime.clone(), max_vertices);
+
+        ComputePolygonMaskImpl {
+            runtime,
+            pipeline,
+            bind_group_manager,
+            ring_buffer,
+            ring_buffer_vertices,
+            max_vertices,
+        }
+    }
+
+    fn create_vertices_ring_buffer(runtime: Arc<Runtime>, max_vertices: usize) -> RingBuffer {
+        RingBuffer::new(
+            runtime,
+            BufferProperties {
+                size: size_of::<f32>() * 2 * max_vertices,
+                host_readable: false,
+            },
+        )
+    }
+}
+impl ComputePolygonMaskImpl {
+    pub fn reset(&mut self) {
+        self.ring_buffer.mark_all_available();
+        self.bind_group_manager.clear_cache();
+        self.ring_buffer_vertices.mark_all_available();
+    }
+
+    pub fn encode_commands(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        op: &ComputePolygonMaskOp,
+        value_store: &mut ValueStore,
+        toolbox: &Toolbox,
+    ) {
+        let target_img = value_store.map.get(&op.target).unwrap().as_image().clone();
+
+        let mask_img_properties = ImageProperties {
+            format: ImageFormat::Rgba16Float,
+            ..target_img.properties
+        };
+        let output_img = value_store.ensure_value_at_id_is_image_of_properties(
+            self.runtime.as_ref(),
+            op.result,
+            &mask_img_properties,
+        );
+
+        let mut vertices_data: Vec<f32> = Vec::new();
+        for (x, y) in op.mask.vertices.iter() {
+            vertices_data.push(*x);
+            vertices_data.push(*y);
+        }
+        let num_vertices = op.mask.vertices.len() as u32;
+
+        // buffers that are replaced are kept alive by the commands already encoded with them
+        if op.mask.vertices.len() > self.max_vertices {
+            self.max_vertices = op.mask.vertices.len().next_power_of_two();
+            self.ring_buffer_vertices =
+                Self::create_vertices_ring_buffer(self.runtime.clone(), self.max_vertices);
+        }
+
+        let vertex_buffer = self.ring_buffer_vertices.get();
+        if !vertices_data.is_empty() {
+            self.runtime.queue.write_buffer(
+                &vertex_buffer.buffer,
+                0,
+                bytemuck::cast_slice(vertices_data.as_slice()),
+            );
+        }
+
+        let buffer = self.ring_buffer.get();
+        self.runtime
+            .queue
+            .write_buffer(&buffer.buffer, 0, bytemuck::cast_slice(&[num_vertices]));
+        self.runtime.queue.write_buffer(
+            &buffer.buffer,
+            size_of::<u32>() as u64,
+            bytemuck::cast_slice(&[op.mask.feather]),
+        );
+
+        let bind_group = self.bind_group_manager.get_or_create(BindGroupDescriptor {
+            entries: vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(buffer),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Buffer(vertex_buffer),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureStorage(output_img, 0),
+                },
+            ],
+        });
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+
+            let num_workgroups_x = div_up(output_img.properties.dimensions.0, 16);
+            let num_workgroups_y = div_up(output_img.properties.dimensions.1, 16);
+
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+
+        toolbox.encode_mipmap_generation_command(output_img, encoder);
+    }
+}
@@ -64,7 +64,7 @@ impl ComputeRadialGradientMaskImpl {
         let target_img = value_store.map.get(&op.target).unwrap().as_image().clone();
 
         let mask_img_properties = ImageProperties {
-            format: ImageFormat::Rgba8Unorm,
+            format: ImageFormat::Rgba16Float,
             ..target_img.properties
         };
         let output_img = value_store.ensure_value_at_id_is_image_of_properties(
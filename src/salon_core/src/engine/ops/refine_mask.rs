@@ -0,0 +1,298 @@
+use std::{mem::size_of, sync::Arc};
+
+use crate::runtime::{Image, ImageFormat, ImageProperties, Toolbox};
+
+use crate::{
+    engine::value_store::ValueStore,
+    ir::RefineMaskOp,
+    runtime::{
+        BindGroupDescriptor, BindGroupEntry, BindGroupManager,
+        BindingResource, Runtime,
+    },
+    runtime::{BufferProperties, RingBuffer},
+    shader::{Shader, ShaderLibraryModule},
+    utils::math::div_up,
+};
+
+// edge-aware refinement of a mask, using a guided filter (He et al.) with the luminance of the target image as the guide.
+pub struct RefineMaskImpl {
+    runtime: Arc<Runtime>,
+
+    ring_buffer: RingBuffer,
+
+    // intermediate images, reused across ops and executions
+    temp_images: Vec<Arc<Image>>,
+    next_temp_image_index: usize,
+
+    pipeline_prepare: wgpu::ComputePipeline,
+    bind_group_manager_prepare: BindGroupManager,
+
+    pipeline_box_blur: wgpu::ComputePipeline,
+    bind_group_manager_box_blur: BindGroupManager,
+
+    pipeline_coefficients: wgpu::ComputePipeline,
+    bind_group_manager_coefficients: BindGroupManager,
+
+    pipeline_apply: wgpu::ComputePipeline,
+    bind_group_manager_apply: BindGroupManager,
+}
+
+impl RefineMaskImpl {
+    pub fn new(runtime: Arc<Runtime>) -> Self {
+        let shader_code = Shader::from_code(include_str!("shaders/refine_mask_prepare.wgsl"))
+            .with_library(ShaderLibraryModule::ColorSpaces)
+            .full_code();
+        let (pipeline_prepare, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("RefineMaskPrepare"));
+        let bind_group_manager_prepare = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        let shader_code =
+            Shader::from_code(include_str!("shaders/refine_mask_box_blur.wgsl")).full_code();
+        let (pipeline_box_blur, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("RefineMaskBoxBlur"));
+        let bind_group_manager_box_blur = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        let shader_code =
+            Shader::from_code(include_str!("shaders/refine_mask_coefficients.wgsl")).full_code();
+        let (pipeline_coefficients, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("RefineMaskCoefficients"));
+        let bind_group_manager_coefficients =
+            BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        let shader_code = Shader::from_code(include_str!("shaders/refine_mask_apply.wgsl"))
+            .with_library(ShaderLibraryModule::ColorSpaces)
+            .full_code();
+        let (pipeline_apply, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("RefineMaskApply"));
+        let bind_group_manager_apply = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        let ring_buffer = RingBuffer::new(
+            runtime.clone(),
+            BufferProperties {
+                size: size_of::<i32>() * 4,
+                host_readable: false,
+            },
+        );
+
+        Self {
+            runtime,
+            ring_buffer,
+
+            temp_images: Vec::new(),
+            next_temp_image_index: 0,
+
+            pipeline_prepare,
+            bind_group_manager_prepare,
+
+            pipeline_box_blur,
+            bind_group_manager_box_blur,
+
+            pipeline_coefficients,
+            bind_group_manager_coefficients,
+
+            pipeline_apply,
+            bind_group_manager_apply,
+        }
+    }
+}
+impl RefineMaskImpl {
+    pub fn reset(&mut self) {
+        self.ring_buffer.mark_all_available();
+        self.next_temp_image_index = 0;
+        self.bind_group_manager_prepare.clear_cache();
+        self.bind_group_manager_box_blur.clear_cache();
+        self.bind_group_manager_coefficients.clear_cache();
+        self.bind_group_manager_apply.clear_cache();
+    }
+
+    pub fn encode_commands(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        op: &RefineMaskOp,
+        value_store: &mut ValueStore,
+        toolbox: &Toolbox,
+    ) {
+        let guide_img = value_store.map.get(&op.guide).unwrap().as_image().clone();
+        let mask_img = value_store.map.get(&op.mask).unwrap().as_image().clone();
+
+        let output_img = value_store
+            .ensure_value_at_id_is_image_of_properties(
+                self.runtime.as_ref(),
+                op.result,
+                &mask_img.properties,
+            )
+            .clone();
+
+        let temp_properties = ImageProperties {
+            format: ImageFormat::Rgba16Float,
+            ..mask_img.properties
+        };
+        let temp_0 = self.get_temp_image(&temp_properties);
+        let temp_1 = self.get_temp_image(&temp_properties);
+
+        // the window radius scales with the image, so that the result doesn't depend on the resolution
+        let dimensions = mask_img.properties.dimensions;
+        let radius = (op.strength.clamp(0.0, 100.0) / 100.0 * 0.05 * dimensions.1 as f32)
+            .round()
+            .max(1.0) as i32;
+        let eps = 1e-3f32;
+
+        let num_workgroups_x = div_up(dimensions.0, 16);
+        let num_workgroups_y = div_up(dimensions.1, 16);
+
+        // (I, p, I*p, I*I)
+        {
+            let bind_group = self
+                .bind_group_manager_prepare
+                .get_or_create(BindGroupDescriptor {
+                    entries: vec![
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::Texture(&guide_img),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::Texture(&mask_img),
+                        },
+                        BindGroupEntry {
+                            binding: 2,
+                            resource: BindingResource::TextureStorage(&temp_0, 0),
+                        },
+                    ],
+                });
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            compute_pass.set_pipeline(&self.pipeline_prepare);
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+
+        // window means of (I, p, I*p, I*I)
+        self.encode_box_blur(encoder, &temp_0, &temp_1, (1, 0), radius);
+        self.encode_box_blur(encoder, &temp_1, &temp_0, (0, 1), radius);
+
+        // (a, b)
+        {
+            let buffer = self.ring_buffer.get();
+            self.runtime
+                .queue
+                .write_buffer(&buffer.buffer, 0, bytemuck::cast_slice(&[eps]));
+            let bind_group = self
+                .bind_group_manager_coefficients
+                .get_or_create(BindGroupDescriptor {
+                    entries: vec![
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::Texture(&temp_0),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::TextureStorage(&temp_1, 0),
+                        },
+                        BindGroupEntry {
+                            binding: 2,
+                            resource: BindingResource::Buffer(buffer),
+                        },
+                    ],
+                });
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            compute_pass.set_pipeline(&self.pipeline_coefficients);
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+
+        // window means of (a, b)
+        self.encode_box_blur(encoder, &temp_1, &temp_0, (1, 0), radius);
+        self.encode_box_blur(encoder, &temp_0, &temp_1, (0, 1), radius);
+
+        // q = mean_a * I + mean_b
+        {
+            let bind_group = self
+                .bind_group_manager_apply
+                .get_or_create(BindGroupDescriptor {
+                    entries: vec![
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::Texture(&guide_img),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::Texture(&temp_1),
+                        },
+                        BindGroupEntry {
+                            binding: 2,
+                            resource: BindingResource::TextureStorage(&output_img, 0),
+                        },
+                    ],
+                });
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            compute_pass.set_pipeline(&self.pipeline_apply);
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+
+        toolbox.encode_mipmap_generation_command(&output_img, encoder);
+    }
+
+    fn encode_box_blur(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        input_img: &Image,
+        output_img: &Image,
+        direction: (i32, i32),
+        radius: i32,
+    ) {
+        let buffer = self.ring_buffer.get();
+        self.runtime.queue.write_buffer(
+            &buffer.buffer,
+            0,
+            bytemuck::cast_slice(&[direction.0, direction.1, radius, 0]),
+        );
+        let bind_group = self
+            .bind_group_manager_box_blur
+            .get_or_create(BindGroupDescriptor {
+                entries: vec![
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Texture(input_img),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureStorage(output_img, 0),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::Buffer(buffer),
+                    },
+                ],
+            });
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            ..Default::default()
+        });
+        compute_pass.set_pipeline(&self.pipeline_box_blur);
+        compute_pass.set_bind_group(0, bind_group, &[]);
+        compute_pass.dispatch_workgroups(
+            div_up(output_img.properties.dimensions.0, 16),
+            div_up(output_img.properties.dimensions.1, 16),
+            1,
+        );
+    }
+
+    fn get_temp_image(&mut self, properties: &ImageProperties) -> Arc<Image> {
+        let index = self.next_temp_image_index;
+        self.next_temp_image_index += 1;
+        if index >= self.temp_images.len() {
+            let image = Arc::new(self.runtime.create_image_of_properties(properties.clone()));
+            self.temp_images.push(image);
+        } else if self.temp_images[index].properties != *properties {
+            self.temp_images[index] =
+                Arc::new(self.runtime.create_image_of_properties(properties.clone()));
+        }
+        self.temp_images[index].clone()
+    }
+}
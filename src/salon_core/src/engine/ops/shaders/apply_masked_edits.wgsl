@@ -12,6 +12,18 @@ var mask: texture_2d<f32>;
 @group(0) @binding(3)
 var output: texture_storage_2d<rgba16float, write>;
 
+struct Params {
+    opacity: f32,
+    blend_mode: u32,
+};
+
+@group(0) @binding(4)
+var<uniform> params: Params;
+
+const BLEND_MODE_NORMAL: u32 = 0u;
+const BLEND_MODE_LUMINOSITY: u32 = 1u;
+const BLEND_MODE_COLOR: u32 = 2u;
+
 
 @compute
 @workgroup_size(16, 16)
@@ -22,7 +34,20 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     }
     let c0 = textureLoad(original, global_id.xy, 0).rgb;
     let c1 = textureLoad(edited, global_id.xy, 0).rgb;
-    let t = textureLoad(mask, global_id.xy, 0).r;
-    let c = mix(c0, c1, t);
+    let t = textureLoad(mask, global_id.xy, 0).r * params.opacity * 0.01;
+
+    var blended = c1;
+    if (params.blend_mode == BLEND_MODE_LUMINOSITY) {
+        let LCh0 = rgb_to_LCh(c0);
+        let LCh1 = rgb_to_LCh(c1);
+        blended = LCh_to_rgb(vec3(LCh1.x, LCh0.y, LCh0.z));
+    }
+    else if (params.blend_mode == BLEND_MODE_COLOR) {
+        let LCh0 = rgb_to_LCh(c0);
+        let LCh1 = rgb_to_LCh(c1);
+        blended = LCh_to_rgb(vec3(LCh0.x, LCh1.y, LCh1.z));
+    }
+
+    let c = mix(c0, blended, t);
     textureStore(output, global_id.xy, vec4(c, 1.0));
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+struct Params {
+    num_strokes: u32,
+};
+
+struct Stroke {
+    first_point: u32,
+    num_points: u32,
+    size: f32,
+    feather: f32,
+    flow: f32,
+    erase: u32,
+    padding_0: u32,
+    padding_1: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(0) @binding(1)
+var<storage, read> strokes: array<Stroke>;
+
+@group(0) @binding(2)
+var<storage, read> points: array<vec2<f32>>;
+
+@group(0) @binding(3)
+var output: texture_storage_2d<rgba16float, write>;
+
+fn distance_to_segment(p: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> f32 {
+    let ab = b - a;
+    let length_squared = dot(ab, ab);
+    if (length_squared == 0.0) {
+        return distance(p, a);
+    }
+    let t = clamp(dot(p - a, ab) / length_squared, 0.0, 1.0);
+    return distance(p, a + t * ab);
+}
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    let size = vec2<f32>(output_size);
+    let xy = vec2<f32>(global_id.xy) + 0.5;
+
+    var result = 0.0;
+
+    for (var s = 0u; s < params.num_strokes; s = s + 1u) {
+        let stroke = strokes[s];
+
+        // the distance to the stroke is the distance to the closest segment of its polyline
+        var dist = distance(xy, points[stroke.first_point] * size);
+        for (var i = 1u; i < stroke.num_points; i = i + 1u) {
+            let a = points[stroke.first_point + i - 1u] * size;
+            let b = points[stroke.first_point + i] * size;
+            dist = min(dist, distance_to_segment(xy, a, b));
+        }
+
+        let radius = max(stroke.size * size.y, 0.5);
+        let feather = max(stroke.feather * 0.01, 0.001);
+        let coverage = stroke.flow * 0.01 * (1.0 - smoothstep(1.0 - feather, 1.0, dist / radius));
+
+        if (stroke.erase == 1u) {
+            result = result * (1.0 - coverage);
+        } else {
+            result = result + (1.0 - result) * coverage;
+        }
+    }
+
+    textureStore(output, global_id.xy, vec4<f32>(result));
+}
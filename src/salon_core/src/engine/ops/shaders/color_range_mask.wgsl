@@ -0,0 +1,52 @@
+@group(0) @binding(0)
+var target_image: texture_2d<f32>;
+
+const max_num_reference_colors: u32 = 8u;
+
+struct Mask {
+    num_colors: u32,
+    tolerance: f32,
+    padding_0: f32,
+    padding_1: f32,
+    // linear rgb, the 4th component is unused
+    reference_colors: array<vec4<f32>, max_num_reference_colors>,
+};
+
+@group(0) @binding(1)
+var<uniform> mask: Mask;
+
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba16float, write>;
+
+// color difference in LCh, with lightness weighted down,
+// so that the same color under different lighting is still selected
+fn LCh_distance(a: vec3<f32>, b: vec3<f32>) -> f32 {
+    let dL = (a.x - b.x) * 0.5;
+    let dh = a.z - b.z;
+    // the distance between the (C, h) points in polar coordinates, h is in radians
+    let dC_squared = max(a.y * a.y + b.y * b.y - 2.0 * a.y * b.y * cos(dh), 0.0);
+    return sqrt(dL * dL + dC_squared);
+}
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    let rgb = max(textureLoad(target_image, global_id.xy, 0).rgb, vec3(0.0));
+    let LCh = rgb_to_LCh(rgb);
+
+    let tolerance = max(mask.tolerance, 0.001);
+
+    var result = 0.0;
+    for (var i = 0u; i < mask.num_colors; i = i + 1u) {
+        let reference_LCh = rgb_to_LCh(max(mask.reference_colors[i].rgb, vec3(0.0)));
+        let d = LCh_distance(LCh, reference_LCh);
+        result = max(result, 1.0 - smoothstep(tolerance * 0.5, tolerance, d));
+    }
+
+    textureStore(output, global_id.xy, vec4<f32>(result));
+}
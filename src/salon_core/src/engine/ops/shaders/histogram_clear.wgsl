@@ -6,6 +6,8 @@ struct Buffer {
     b: array<atomic<u32>, max_bins>,
     luma: array<atomic<u32>, max_bins>,
     num_bins: u32,
+    clipped_shadows: array<atomic<u32>, 4>,
+    clipped_highlights: array<atomic<u32>, 4>,
 };
 
 @group(0) @binding(0)
@@ -20,4 +22,8 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     atomicStore(&buffer.g[i], 0u);
     atomicStore(&buffer.b[i], 0u);
     atomicStore(&buffer.luma[i], 0u);
+    if (i < 4u) {
+        atomicStore(&buffer.clipped_shadows[i], 0u);
+        atomicStore(&buffer.clipped_highlights[i], 0u);
+    }
 }
\ No newline at end of file
@@ -16,6 +16,9 @@ struct Buffer {
     b: array<atomic<u32>, max_bins>,
     luma: array<atomic<u32>, max_bins>,
     num_bins: u32,
+    // the number of pixels at or beyond black and white, for r, g, b and luma
+    clipped_shadows: array<atomic<u32>, 4>,
+    clipped_highlights: array<atomic<u32>, 4>,
 };
 
 @group(0) @binding(2)
@@ -38,6 +41,7 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(local_i
         buffer.num_bins = uniforms.num_bins;
 
         var c = textureLoad(input, global_id.xy, 0).rgb;
+        count_clipped(vec4(c, dot(c, vec3(0.2126, 0.7152, 0.0722))));
         c = linear_to_srgb(c);
         c = clamp(c, vec3(0.0), vec3(1.0));
 
@@ -58,14 +62,30 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(local_i
     // this needs to be executed even for out-of-bounds threads;
     workgroupBarrier();
 
-    if (!out_of_bounds) {
-        var write_index = local_id.x * 16u + local_id.y;
-        while (write_index < max_bins){
-            atomicAdd(&buffer.r[write_index], atomicLoad(&buffer_local.r[write_index]));
-            atomicAdd(&buffer.g[write_index], atomicLoad(&buffer_local.g[write_index]));
-            atomicAdd(&buffer.b[write_index], atomicLoad(&buffer_local.b[write_index]));
-            atomicAdd(&buffer.luma[write_index], atomicLoad(&buffer_local.luma[write_index]));
-            write_index = write_index + 16u * 16u;
+    // as is the flush, since the threads of a workgroup on the border of the image can be out of bounds
+    // while other threads in the same workgroup have counted pixels
+    var write_index = local_id.x * 16u + local_id.y;
+    while (write_index < max_bins){
+        atomicAdd(&buffer.r[write_index], atomicLoad(&buffer_local.r[write_index]));
+        atomicAdd(&buffer.g[write_index], atomicLoad(&buffer_local.g[write_index]));
+        atomicAdd(&buffer.b[write_index], atomicLoad(&buffer_local.b[write_index]));
+        atomicAdd(&buffer.luma[write_index], atomicLoad(&buffer_local.luma[write_index]));
+        write_index = write_index + 16u * 16u;
+    }
+    let clipped_index = local_id.x * 16u + local_id.y;
+    if (clipped_index < 4u) {
+        atomicAdd(&buffer.clipped_shadows[clipped_index], atomicLoad(&buffer_local.clipped_shadows[clipped_index]));
+        atomicAdd(&buffer.clipped_highlights[clipped_index], atomicLoad(&buffer_local.clipped_highlights[clipped_index]));
+    }
+}
+
+fn count_clipped(rgbl: vec4<f32>) {
+    for (var i = 0; i < 4; i = i + 1) {
+        if (rgbl[i] <= 0.0) {
+            atomicAdd(&buffer_local.clipped_shadows[i], 1u);
+        }
+        else if (rgbl[i] >= 1.0) {
+            atomicAdd(&buffer_local.clipped_highlights[i], 1u);
         }
     }
 }
\ No newline at end of file
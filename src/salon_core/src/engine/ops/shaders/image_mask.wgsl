@@ -0,0 +1,58 @@
+@group(0) @binding(0)
+var mask_image: texture_2d<f32>;
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba16float, write>;
+
+struct Params {
+    crop_center: vec2<f32>,
+    crop_size: vec2<f32>,
+    rotation_radians: f32,
+    lod: f32,
+    has_image: u32,
+};
+
+@group(0) @binding(3)
+var<uniform> params: Params;
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    if (params.has_image == 0u) {
+        textureStore(output, global_id.xy, vec4<f32>(0.0));
+        return;
+    }
+
+    // same mapping as rotate_and_crop.wgsl, from the edited image to the original one
+    let mask_image_size = textureDimensions(mask_image);
+    let aspect_ratio = f32(mask_image_size.x) / f32(mask_image_size.y);
+
+    var uv = (vec2<f32>(global_id.xy) + 0.5) / vec2<f32>(output_size);
+    uv = (uv - 0.5) * params.crop_size;
+    uv.x *= aspect_ratio;
+
+    let rotation_col0 = vec2 (
+        cos(-params.rotation_radians),
+        sin(-params.rotation_radians)
+    );
+    let rotation_col1 = vec2 (
+        -sin(-params.rotation_radians),
+        cos(-params.rotation_radians)
+    );
+    let rotation = mat2x2(rotation_col0, rotation_col1);
+    uv = rotation * uv;
+
+    uv.x /= aspect_ratio;
+    uv = uv + params.crop_center;
+
+    let m = textureSampleLevel(mask_image, tex_sampler, uv, params.lod).r;
+    textureStore(output, global_id.xy, vec4<f32>(m));
+}
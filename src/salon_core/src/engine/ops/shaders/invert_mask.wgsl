@@ -2,7 +2,7 @@
 var mask_0: texture_2d<f32>;
 
 @group(0) @binding(1)
-var output: texture_storage_2d<rgba8unorm, write>;
+var output: texture_storage_2d<rgba16float, write>;
 
 
 @compute
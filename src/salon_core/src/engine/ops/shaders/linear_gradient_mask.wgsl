@@ -9,7 +9,7 @@ struct Mask {
 var<uniform> mask: Mask;
 
 @group(0) @binding(1)
-var output: texture_storage_2d<rgba8unorm, write>; 
+var output: texture_storage_2d<rgba16float, write>; 
 
 @compute
 @workgroup_size(16, 16)
@@ -0,0 +1,34 @@
+@group(0) @binding(0)
+var target_image: texture_2d<f32>;
+
+struct Mask {
+    low: f32,
+    high: f32,
+    low_feather: f32,
+    high_feather: f32,
+};
+
+@group(0) @binding(1)
+var<uniform> mask: Mask;
+
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba16float, write>;
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    let rgb = textureLoad(target_image, global_id.xy, 0).rgb;
+    let L = Y_to_L(rgb_to_XYZ(max(rgb, vec3(0.0))).y);
+
+    // the feathers extend outwards from the [low, high] range
+    let low_feather = max(mask.low_feather, 0.001);
+    let high_feather = max(mask.high_feather, 0.001);
+    let result = smoothstep(mask.low - low_feather, mask.low, L) * (1.0 - smoothstep(mask.high, mask.high + high_feather, L));
+
+    textureStore(output, global_id.xy, vec4<f32>(result));
+}
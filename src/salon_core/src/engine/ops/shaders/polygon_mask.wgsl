@@ -0,0 +1,68 @@
+struct Params {
+    num_vertices: u32,
+    feather: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(0) @binding(1)
+var<storage, read> vertices: array<vec2<f32>>;
+
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba16float, write>;
+
+fn distance_to_segment(p: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> f32 {
+    let ab = b - a;
+    let length_squared = dot(ab, ab);
+    if (length_squared == 0.0) {
+        return distance(p, a);
+    }
+    let t = clamp(dot(p - a, ab) / length_squared, 0.0, 1.0);
+    return distance(p, a + t * ab);
+}
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    if (params.num_vertices < 3u) {
+        textureStore(output, global_id.xy, vec4<f32>(0.0));
+        return;
+    }
+
+    let size = vec2<f32>(output_size);
+    let xy = vec2<f32>(global_id.xy) + 0.5;
+
+    // even-odd rule for whether the pixel is inside, along with the distance to the closest edge
+    var inside = false;
+    var dist = 1e10;
+    var j = params.num_vertices - 1u;
+    for (var i = 0u; i < params.num_vertices; i = i + 1u) {
+        let a = vertices[i] * size;
+        let b = vertices[j] * size;
+        if ((a.y > xy.y) != (b.y > xy.y)) {
+            let x_intersect = a.x + (xy.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if (xy.x < x_intersect) {
+                inside = !inside;
+            }
+        }
+        dist = min(dist, distance_to_segment(xy, a, b));
+        j = i;
+    }
+
+    var signed_dist = -dist;
+    if (inside) {
+        signed_dist = dist;
+    }
+
+    // at least one pixel wide, so that hard edges are still anti-aliased
+    let feather = max(params.feather * size.y, 1.0);
+    let result = smoothstep(-0.5 * feather, 0.5 * feather, signed_dist);
+
+    textureStore(output, global_id.xy, vec4<f32>(result));
+}
@@ -0,0 +1,49 @@
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba16float, write>;
+
+struct Params {
+    center_x: f32,
+    center_y: f32,
+    pupil_radius: f32,
+    darken: f32,
+};
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    let original = textureLoad(input, global_id.xy, 0).rgb;
+
+    let size = vec2<f32>(output_size);
+    let center = vec2(params.center_x, params.center_y) * size;
+    let radius = max(params.pupil_radius * size.y, 1.0);
+
+    let dist = distance(vec2<f32>(global_id.xy), center) / radius;
+    if (dist >= 1.0) {
+        textureStore(output, global_id.xy, vec4<f32>(original, 1.0));
+        return;
+    }
+
+    // detect red pupil pixels: red needs to clearly dominate both green and blue
+    let srgb = linear_to_srgb(original);
+    let redness = (srgb.r - max(srgb.g, srgb.b)) / max(srgb.r, 0.0001);
+    var weight = smoothstep(0.2, 0.5, redness) * smoothstep(0.05, 0.15, srgb.r);
+    // soften the border of the region
+    weight = weight * (1.0 - smoothstep(0.8, 1.0, dist));
+
+    // desaturate by replacing red with the average of the other two channels, then darken
+    var corrected = vec3(0.5 * (original.g + original.b), original.g, original.b);
+    corrected = corrected * (1.0 - params.darken * 0.01);
+
+    textureStore(output, global_id.xy, vec4<f32>(mix(original, corrected, weight), 1.0));
+}
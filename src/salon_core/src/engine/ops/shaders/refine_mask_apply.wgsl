@@ -0,0 +1,24 @@
+@group(0) @binding(0)
+var guide: texture_2d<f32>;
+
+@group(0) @binding(1)
+var coefficients: texture_2d<f32>;
+
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba16float, write>;
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    let rgb = textureLoad(guide, global_id.xy, 0).rgb;
+    let I = dot(linear_to_srgb(rgb), vec3(0.2126, 0.7152, 0.0722));
+    let ab = textureLoad(coefficients, global_id.xy, 0).xy;
+
+    let m = clamp(ab.x * I + ab.y, 0.0, 1.0);
+    textureStore(output, global_id.xy, vec4(vec3(m), 1.0));
+}
@@ -0,0 +1,35 @@
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba16float, write>;
+
+struct Params {
+    direction: vec2<i32>,
+    radius: i32,
+    padding: i32,
+};
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    let max_coords = vec2<i32>(output_size) - vec2(1);
+    let center = vec2<i32>(global_id.xy);
+
+    var sum = vec4(0.0);
+    for (var i = -params.radius; i <= params.radius; i = i + 1) {
+        let coords = clamp(center + params.direction * i, vec2(0), max_coords);
+        sum = sum + textureLoad(input, coords, 0);
+    }
+    let mean = sum / f32(2 * params.radius + 1);
+
+    textureStore(output, global_id.xy, mean);
+}
@@ -0,0 +1,33 @@
+@group(0) @binding(0)
+var means: texture_2d<f32>;
+
+@group(0) @binding(1)
+var output: texture_storage_2d<rgba16float, write>;
+
+struct Params {
+    eps: f32,
+};
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    // means of I, p, I*p and I*I within the window
+    let m = textureLoad(means, global_id.xy, 0);
+    let mean_I = m.x;
+    let mean_p = m.y;
+    let cov_Ip = m.z - mean_I * mean_p;
+    let var_I = max(m.w - mean_I * mean_I, 0.0);
+
+    let a = cov_Ip / (var_I + params.eps);
+    let b = mean_p - a * mean_I;
+
+    textureStore(output, global_id.xy, vec4(a, b, 0.0, 0.0));
+}
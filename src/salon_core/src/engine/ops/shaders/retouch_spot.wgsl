@@ -0,0 +1,81 @@
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(0) @binding(2)
+var output: texture_storage_2d<rgba16float, write>;
+
+struct Params {
+    source_x: f32,
+    source_y: f32,
+    destination_x: f32,
+    destination_y: f32,
+    radius: f32,
+    feather: f32,
+    opacity: f32,
+    mode: u32,
+};
+
+@group(0) @binding(3)
+var<uniform> params: Params;
+
+const MODE_HEAL: u32 = 0u;
+
+const num_boundary_samples: i32 = 32;
+
+fn sample_at(xy: vec2<f32>, size: vec2<f32>, lod: f32) -> vec3<f32> {
+    return textureSampleLevel(input, tex_sampler, (xy + 0.5) / size, lod).rgb;
+}
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let output_size = textureDimensions(output);
+    if(global_id.x >= output_size.x || global_id.y >= output_size.y){
+        return;
+    }
+
+    let original = textureLoad(input, global_id.xy, 0).rgb;
+
+    let size = vec2<f32>(output_size);
+    let xy = vec2<f32>(global_id.xy);
+    let source = vec2(params.source_x, params.source_y) * size;
+    let destination = vec2(params.destination_x, params.destination_y) * size;
+    let radius = max(params.radius * size.y, 1.0);
+
+    let dist = distance(xy, destination) / radius;
+    if (dist >= 1.0) {
+        textureStore(output, global_id.xy, vec4<f32>(original, 1.0));
+        return;
+    }
+
+    let offset = xy - destination;
+    var c = sample_at(source + offset, size, 0.0);
+
+    if (params.mode == MODE_HEAL) {
+        // approximates gradient-domain (poisson) cloning:
+        // the differences between the destination and the source along the spot's boundary are
+        // smoothly interpolated across the interior (Farbman et al., "Coordinates for Instant Image Cloning")
+        // the boundary is sampled from a coarser mip level to avoid picking up noise.
+        let boundary_lod = max(log2(radius * 0.1), 0.0);
+        var correction = vec3(0.0);
+        var total_weight = 0.0;
+        for (var i = 0; i < num_boundary_samples; i = i + 1) {
+            let theta = 6.28318530718 * f32(i) / f32(num_boundary_samples);
+            let boundary_offset = vec2(cos(theta), sin(theta)) * radius;
+            let diff = sample_at(destination + boundary_offset, size, boundary_lod) - sample_at(source + boundary_offset, size, boundary_lod);
+            let d = distance(offset, boundary_offset) / radius;
+            let weight = 1.0 / max(d * d, 0.0001);
+            correction = correction + diff * weight;
+            total_weight = total_weight + weight;
+        }
+        c = c + correction / total_weight;
+    }
+
+    let feather = max(params.feather * 0.01, 0.001);
+    let alpha = params.opacity * 0.01 * (1.0 - smoothstep(1.0 - feather, 1.0, dist));
+
+    textureStore(output, global_id.xy, vec4<f32>(mix(original, c, alpha), 1.0));
+}
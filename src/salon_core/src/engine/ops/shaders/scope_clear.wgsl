@@ -0,0 +1,15 @@
+struct Buffer {
+    counts: array<u32>,
+};
+
+@group(0) @binding(0)
+var<storage, read_write> buffer: Buffer;
+
+@compute
+@workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let i = global_id.x;
+    if (i < arrayLength(&buffer.counts)) {
+        buffer.counts[i] = 0u;
+    }
+}
@@ -5,7 +5,7 @@ var mask_0: texture_2d<f32>;
 var mask_1: texture_2d<f32>;
 
 @group(0) @binding(2)
-var output: texture_storage_2d<rgba8unorm, write>;
+var output: texture_storage_2d<rgba16float, write>;
 
 
 @compute
@@ -0,0 +1,31 @@
+// matches scope.rs
+const size: u32 = 128u;
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+struct Buffer {
+    counts: array<atomic<u32>, 16384>, // size * size
+};
+
+@group(0) @binding(1)
+var<storage, read_write> buffer: Buffer;
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let input_size = textureDimensions(input);
+    if (global_id.x >= input_size.x || global_id.y >= input_size.y) {
+        return;
+    }
+
+    let c = clamp(linear_to_srgb(textureLoad(input, global_id.xy, 0).rgb), vec3(0.0), vec3(1.0));
+
+    // BT.709, with Cb and Cr in [-0.5, 0.5]
+    let luma = dot(c, vec3(0.2126, 0.7152, 0.0722));
+    let cb = (c.b - luma) / 1.8556;
+    let cr = (c.r - luma) / 1.5748;
+
+    let xy = vec2<u32>(clamp(vec2(cb, cr) + 0.5, vec2(0.0), vec2(1.0)) * f32(size - 1u) + 0.5);
+    atomicAdd(&buffer.counts[xy.y * size + xy.x], 1u);
+}
@@ -0,0 +1,49 @@
+// matches scope.rs
+const num_columns: u32 = 240u;
+const num_levels: u32 = 128u;
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+struct Uniforms {
+    parade: u32,
+};
+
+@group(0) @binding(1)
+var<uniform> uniforms: Uniforms;
+
+struct Buffer {
+    counts: array<atomic<u32>, 30720>, // num_columns * num_levels
+};
+
+@group(0) @binding(2)
+var<storage, read_write> buffer: Buffer;
+
+fn val_to_level(v: f32) -> u32 {
+    return u32(clamp(v, 0.0, 1.0) * f32(num_levels - 1u) + 0.5);
+}
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let input_size = textureDimensions(input);
+    if (global_id.x >= input_size.x || global_id.y >= input_size.y) {
+        return;
+    }
+
+    let c = linear_to_srgb(textureLoad(input, global_id.xy, 0).rgb);
+
+    if (uniforms.parade == 0u) {
+        let column = global_id.x * num_columns / input_size.x;
+        let level = val_to_level(dot(c, vec3(0.2126, 0.7152, 0.0722)));
+        atomicAdd(&buffer.counts[level * num_columns + column], 1u);
+    }
+    else {
+        // each channel takes up a third of the columns
+        let section_columns = num_columns / 3u;
+        let column = global_id.x * section_columns / input_size.x;
+        atomicAdd(&buffer.counts[val_to_level(c.r) * num_columns + column], 1u);
+        atomicAdd(&buffer.counts[val_to_level(c.g) * num_columns + section_columns + column], 1u);
+        atomicAdd(&buffer.counts[val_to_level(c.b) * num_columns + section_columns * 2u + column], 1u);
+    }
+}
@@ -1,7 +1,12 @@
-use crate::ir::{AddMaskOp, InvertMaskOp, SubtractMaskOp};
+use std::path::PathBuf;
+
+use crate::ir::{AddMaskOp, IntersectMaskOp, InvertMaskOp, RefineMaskOp, SubtractMaskOp};
+use crate::utils::rectangle::Rectangle;
 
 use super::{
-    ComputeGlobalMaskOp, ComputeLinearGradientMaskOp, ComputeRadialGradientMaskOp, Id, Module, Op,
+    ComputeBrushMaskOp, ComputeColorRangeMaskOp, ComputeGlobalMaskOp, ComputeImageMaskOp,
+    ComputeLinearGradientMaskOp, ComputeLuminanceRangeMaskOp, ComputePolygonMaskOp,
+    ComputeRadialGradientMaskOp, Id, Module, Op,
 };
 
 #[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
@@ -9,37 +14,79 @@ pub enum MaskPrimitive {
     Global(GlobalMask),
     RadialGradient(RadialGradientMask),
     LinearGradient(LinearGradientMask),
+    Brush(BrushMask),
+    Polygon(PolygonMask),
+    LuminanceRange(LuminanceRangeMask),
+    ColorRange(ColorRangeMask),
+    Image(ImageMask),
 }
 
 impl MaskPrimitive {
-    pub fn create_compute_mask_ops(&self, target: Id, module: &mut Module) -> Id {
+    // rotation_degrees and crop_rect are the geometry edits of the image, which image masks also go through
+    pub fn create_compute_mask_ops(
+        &self,
+        target: Id,
+        rotation_degrees: f32,
+        crop_rect: Rectangle,
+        module: &mut Module,
+    ) -> Id {
         match self {
             MaskPrimitive::Global(ref m) => m.create_compute_mask_ops(target, module),
             MaskPrimitive::RadialGradient(ref m) => m.create_compute_mask_ops(target, module),
             MaskPrimitive::LinearGradient(ref m) => m.create_compute_mask_ops(target, module),
+            MaskPrimitive::Brush(ref m) => m.create_compute_mask_ops(target, module),
+            MaskPrimitive::Polygon(ref m) => m.create_compute_mask_ops(target, module),
+            MaskPrimitive::LuminanceRange(ref m) => m.create_compute_mask_ops(target, module),
+            MaskPrimitive::ColorRange(ref m) => m.create_compute_mask_ops(target, module),
+            MaskPrimitive::Image(ref m) => {
+                m.create_compute_mask_ops(target, rotation_degrees, crop_rect, module)
+            }
         }
     }
 }
 
+// how a term is combined with the terms before it
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum MaskTermMode {
+    Add,
+    Subtract,
+    Intersect,
+}
+
 #[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
 pub struct MaskTerm {
     pub primitive: MaskPrimitive,
     pub inverted: bool,
-    pub subtracted: bool,
+    pub mode: MaskTermMode,
 }
 
 #[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Mask {
     pub terms: Vec<MaskTerm>,
+    // strength of the edge-aware refinement (0 to 100), where 0 disables it.
+    // edits saved before this existed don't have the field.
+    #[serde(default)]
+    pub refine_edges: f32,
 }
 
 impl Mask {
-    pub fn create_compute_mask_ops(&self, target: Id, module: &mut Module) -> (Id, Vec<Id>) {
+    pub fn create_compute_mask_ops(
+        &self,
+        target: Id,
+        rotation_degrees: f32,
+        crop_rect: Rectangle,
+        module: &mut Module,
+    ) -> (Id, Vec<Id>) {
         assert!(self.terms.len() > 0usize, "mask has no terms!");
 
         let mut term_ids = Vec::new();
         for term in self.terms.iter() {
-            let primitive_id = term.primitive.create_compute_mask_ops(target, module);
+            let primitive_id = term.primitive.create_compute_mask_ops(
+                target,
+                rotation_degrees,
+                crop_rect,
+                module,
+            );
             let mut term_id = primitive_id;
             if term.inverted {
                 term_id = module.alloc_id();
@@ -53,7 +100,7 @@ impl Mask {
 
         let mut result_id = term_ids[0];
 
-        if self.terms[0].subtracted {
+        if self.terms[0].mode != MaskTermMode::Add {
             // this can happen if all the previous + terms are deleted
             let term_id = module.alloc_id();
             module.push_op(Op::ComputeGlobalMask(ComputeGlobalMaskOp {
@@ -66,28 +113,45 @@ impl Mask {
 
         for i in 1..term_ids.len() {
             let new_result = module.alloc_id();
-            if self.terms[i].subtracted {
-                module.push_op(Op::SubtractMask(SubtractMaskOp {
+            match self.terms[i].mode {
+                MaskTermMode::Add => module.push_op(Op::AddMask(AddMaskOp {
                     result: new_result,
                     mask_0: result_id,
                     mask_1: term_ids[i],
-                }));
-            } else {
-                module.push_op(Op::AddMask(AddMaskOp {
+                })),
+                MaskTermMode::Subtract => module.push_op(Op::SubtractMask(SubtractMaskOp {
                     result: new_result,
                     mask_0: result_id,
                     mask_1: term_ids[i],
-                }));
+                })),
+                MaskTermMode::Intersect => module.push_op(Op::IntersectMask(IntersectMaskOp {
+                    result: new_result,
+                    mask_0: result_id,
+                    mask_1: term_ids[i],
+                })),
             }
             result_id = new_result
         }
+
+        if self.refine_edges > 0.0 {
+            // snap the edges of the combined mask to the edges in the target image
+            let refined = module.alloc_id();
+            module.push_op(Op::RefineMask(RefineMaskOp {
+                result: refined,
+                mask: result_id,
+                guide: target,
+                strength: self.refine_edges,
+            }));
+            result_id = refined;
+        }
+
         (result_id, term_ids)
     }
 
     pub fn is_singe_global(&self) -> bool {
         self.terms.len() == 1
             && !self.terms[0].inverted
-            && !self.terms[0].subtracted
+            && self.terms[0].mode == MaskTermMode::Add
             && match self.terms[0].primitive {
                 MaskPrimitive::Global(_) => true,
                 _ => false,
@@ -179,3 +243,156 @@ impl LinearGradientMask {
         result
     }
 }
+
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct BrushStroke {
+    // positions are relative to the image after geometry edits
+    pub points: Vec<(f32, f32)>,
+    // radius of the brush, relative to the image height
+    pub size: f32,
+    pub feather: f32,
+    pub flow: f32,
+    pub erase: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct BrushMask {
+    // strokes are applied in order, so that later erasing strokes can remove earlier painted ones
+    pub strokes: Vec<BrushStroke>,
+}
+
+impl BrushMask {
+    pub fn default() -> Self {
+        Self {
+            strokes: Vec::new(),
+        }
+    }
+
+    pub fn create_compute_mask_ops(&self, target: Id, module: &mut Module) -> Id {
+        let result = module.alloc_id();
+        module.push_op(Op::ComputeBrushMask(ComputeBrushMaskOp {
+            result,
+            mask: self.clone(),
+            target,
+        }));
+        result
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct PolygonMask {
+    // positions are relative to the image after geometry edits.
+    // the polygon is implicitly closed, and needs at least 3 vertices to cover anything.
+    pub vertices: Vec<(f32, f32)>,
+    // width of the soft transition across the edges, relative to the image height
+    pub feather: f32,
+}
+
+impl PolygonMask {
+    pub fn default() -> Self {
+        Self {
+            vertices: Vec::new(),
+            feather: 0.005,
+        }
+    }
+
+    pub fn create_compute_mask_ops(&self, target: Id, module: &mut Module) -> Id {
+        let result = module.alloc_id();
+        module.push_op(Op::ComputePolygonMask(ComputePolygonMaskOp {
+            result,
+            mask: self.clone(),
+            target,
+        }));
+        result
+    }
+}
+
+// selects pixels of the target image by their perceptual lightness (CIE L*, from 0 to 100)
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct LuminanceRangeMask {
+    pub low: f32,
+    pub high: f32,
+    pub low_feather: f32,
+    pub high_feather: f32,
+}
+
+impl LuminanceRangeMask {
+    pub fn default() -> Self {
+        Self {
+            low: 60.0,
+            high: 100.0,
+            low_feather: 10.0,
+            high_feather: 10.0,
+        }
+    }
+
+    pub fn create_compute_mask_ops(&self, target: Id, module: &mut Module) -> Id {
+        let result = module.alloc_id();
+        module.push_op(Op::ComputeLuminanceRangeMask(ComputeLuminanceRangeMaskOp {
+            result,
+            mask: self.clone(),
+            target,
+        }));
+        result
+    }
+}
+
+// selects pixels of the target image that are close to any of the reference colors, as measured in LCh
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ColorRangeMask {
+    // linear rgb, as sampled from the image
+    pub reference_colors: Vec<[f32; 3]>,
+    pub tolerance: f32,
+}
+
+impl ColorRangeMask {
+    pub fn default() -> Self {
+        Self {
+            reference_colors: Vec::new(),
+            tolerance: 20.0,
+        }
+    }
+
+    pub fn max_num_reference_colors() -> usize {
+        8
+    }
+
+    pub fn create_compute_mask_ops(&self, target: Id, module: &mut Module) -> Id {
+        let result = module.alloc_id();
+        module.push_op(Op::ComputeColorRangeMask(ComputeColorRangeMaskOp {
+            result,
+            mask: self.clone(),
+            target,
+        }));
+        result
+    }
+}
+
+// an external grayscale image, which is aligned with the original image before any geometry edits.
+// the geometry edits of the image are applied to it when the mask is computed.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ImageMask {
+    pub path: PathBuf,
+    // sha256 of the file, so that a file that changed since it was imported isn't silently used
+    pub content_hash: String,
+}
+
+impl ImageMask {
+    pub fn create_compute_mask_ops(
+        &self,
+        target: Id,
+        rotation_degrees: f32,
+        crop_rect: Rectangle,
+        module: &mut Module,
+    ) -> Id {
+        let result = module.alloc_id();
+        module.push_op(Op::ComputeImageMask(ComputeImageMaskOp {
+            result,
+            mask: self.clone(),
+            target,
+            rotation_degrees,
+            crop_rect,
+        }));
+        result
+    }
+}
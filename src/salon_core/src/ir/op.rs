@@ -1,6 +1,9 @@
 use crate::utils::rectangle::Rectangle;
 
-use super::{GlobalMask, Id, LinearGradientMask, RadialGradientMask};
+use super::{
+    BrushMask, ColorRangeMask, GlobalMask, Id, ImageMask, LinearGradientMask, LuminanceRangeMask, PolygonMask,
+    RadialGradientMask,
+};
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum Op {
@@ -17,16 +20,27 @@ pub enum Op {
     ApplyDehaze(ApplyDehazeOp),
     ComputeBasicStatistics(ComputeBasicStatisticsOp),
     ComputeHistogram(ComputeHistogramOp),
+    ComputeWaveform(ComputeWaveformOp),
+    ComputeVectorscope(ComputeVectorscopeOp),
     RotateAndCrop(RotateAndCropOp),
     Resize(ResizeOp),
     ComputeGlobalMask(ComputeGlobalMaskOp),
     ComputeRadialGradientMask(ComputeRadialGradientMaskOp),
     ComputeLinearGradientMask(ComputeLinearGradientMaskOp),
+    ComputeBrushMask(ComputeBrushMaskOp),
+    ComputePolygonMask(ComputePolygonMaskOp),
+    ComputeLuminanceRangeMask(ComputeLuminanceRangeMaskOp),
+    ComputeColorRangeMask(ComputeColorRangeMaskOp),
+    ComputeImageMask(ComputeImageMaskOp),
     AddMask(AddMaskOp),
     SubtractMask(SubtractMaskOp),
+    IntersectMask(IntersectMaskOp),
     InvertMask(InvertMaskOp),
+    RefineMask(RefineMaskOp),
     ApplyMaskedEdits(ApplyMaskedEditsOp),
     ApplyFraming(ApplyFramingOp),
+    ApplyRetouchSpot(ApplyRetouchSpotOp),
+    ApplyRedEyeCorrection(ApplyRedEyeCorrectionOp),
 }
 
 impl Op {
@@ -45,16 +59,27 @@ impl Op {
             Op::ApplyDehaze(ref o) => vec![o.arg],
             Op::ComputeBasicStatistics(ref o) => vec![o.arg],
             Op::ComputeHistogram(ref o) => vec![o.arg],
+            Op::ComputeWaveform(ref o) => vec![o.arg],
+            Op::ComputeVectorscope(ref o) => vec![o.arg],
             Op::RotateAndCrop(ref o) => vec![o.arg],
             Op::Resize(ref o) => vec![o.arg],
             Op::ComputeGlobalMask(ref o) => vec![o.target],
             Op::ComputeRadialGradientMask(ref o) => vec![o.target],
             Op::ComputeLinearGradientMask(ref o) => vec![o.target],
+            Op::ComputeBrushMask(ref o) => vec![o.target],
+            Op::ComputePolygonMask(ref o) => vec![o.target],
+            Op::ComputeLuminanceRangeMask(ref o) => vec![o.target],
+            Op::ComputeColorRangeMask(ref o) => vec![o.target],
+            Op::ComputeImageMask(ref o) => vec![o.target],
             Op::AddMask(ref o) => vec![o.mask_0, o.mask_1],
             Op::SubtractMask(ref o) => vec![o.mask_0, o.mask_1],
+            Op::IntersectMask(ref o) => vec![o.mask_0, o.mask_1],
             Op::InvertMask(ref o) => vec![o.mask_0],
+            Op::RefineMask(ref o) => vec![o.mask, o.guide],
             Op::ApplyMaskedEdits(ref o) => vec![o.original_target, o.edited, o.mask],
             Op::ApplyFraming(ref o) => vec![o.arg],
+            Op::ApplyRetouchSpot(ref o) => vec![o.arg],
+            Op::ApplyRedEyeCorrection(ref o) => vec![o.arg],
         }
     }
 
@@ -73,18 +98,37 @@ impl Op {
             Op::ApplyDehaze(ref o) => o.result,
             Op::ComputeBasicStatistics(ref o) => o.result,
             Op::ComputeHistogram(ref o) => o.result,
+            Op::ComputeWaveform(ref o) => o.result,
+            Op::ComputeVectorscope(ref o) => o.result,
             Op::RotateAndCrop(ref o) => o.result,
             Op::Resize(ref o) => o.result,
             Op::ComputeGlobalMask(ref o) => o.result,
             Op::ComputeRadialGradientMask(ref o) => o.result,
             Op::ComputeLinearGradientMask(ref o) => o.result,
+            Op::ComputeBrushMask(ref o) => o.result,
+            Op::ComputePolygonMask(ref o) => o.result,
+            Op::ComputeLuminanceRangeMask(ref o) => o.result,
+            Op::ComputeColorRangeMask(ref o) => o.result,
+            Op::ComputeImageMask(ref o) => o.result,
             Op::AddMask(ref o) => o.result,
             Op::SubtractMask(ref o) => o.result,
+            Op::IntersectMask(ref o) => o.result,
             Op::InvertMask(ref o) => o.result,
+            Op::RefineMask(ref o) => o.result,
             Op::ApplyMaskedEdits(ref o) => o.result,
             Op::ApplyFraming(ref o) => o.result,
+            Op::ApplyRetouchSpot(ref o) => o.result,
+            Op::ApplyRedEyeCorrection(ref o) => o.result,
         }
     }
+
+    // whether the result is a buffer that's read back on the host (by a `BufferReader` of the edit result)
+    pub fn result_is_read_by_host(&self) -> bool {
+        matches!(
+            self,
+            Op::ComputeHistogram(_) | Op::ComputeWaveform(_) | Op::ComputeVectorscope(_)
+        )
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -150,6 +194,7 @@ pub struct ColorMixOp {
 }
 
 #[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default = "ColorMixGroup::new")]
 pub struct ColorMixGroup {
     pub hue: f32,
     pub saturation: f32,
@@ -167,6 +212,7 @@ impl ColorMixGroup {
 }
 
 #[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default = "Vignette::new")]
 pub struct Vignette {
     pub vignette: f32,
     pub midpoint: f32,
@@ -216,6 +262,22 @@ pub struct ComputeBasicStatisticsOp {
 pub struct ComputeHistogramOp {
     pub result: Id,
     pub arg: Id,
+    // chosen based on the image size if None
+    pub num_bins: Option<u32>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComputeWaveformOp {
+    pub result: Id,
+    pub arg: Id,
+    // per-channel waveforms side by side, instead of a single luma waveform
+    pub parade: bool,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComputeVectorscopeOp {
+    pub result: Id,
+    pub arg: Id,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -254,6 +316,44 @@ pub struct ComputeLinearGradientMaskOp {
     pub target: Id,
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComputeBrushMaskOp {
+    pub result: Id,
+    pub mask: BrushMask,
+    pub target: Id,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComputePolygonMaskOp {
+    pub result: Id,
+    pub mask: PolygonMask,
+    pub target: Id,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComputeLuminanceRangeMaskOp {
+    pub result: Id,
+    pub mask: LuminanceRangeMask,
+    pub target: Id,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComputeColorRangeMaskOp {
+    pub result: Id,
+    pub mask: ColorRangeMask,
+    pub target: Id,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComputeImageMaskOp {
+    pub result: Id,
+    pub mask: ImageMask,
+    pub target: Id,
+    // the geometry edits of the image, as in `RotateAndCropOp`
+    pub rotation_degrees: f32,
+    pub crop_rect: Rectangle,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct AddMaskOp {
     pub result: Id,
@@ -268,18 +368,46 @@ pub struct SubtractMaskOp {
     pub mask_1: Id,
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct IntersectMaskOp {
+    pub result: Id,
+    pub mask_0: Id,
+    pub mask_1: Id,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct InvertMaskOp {
     pub result: Id,
     pub mask_0: Id,
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct RefineMaskOp {
+    pub result: Id,
+    pub mask: Id,
+    pub guide: Id,
+    pub strength: f32,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct ApplyMaskedEditsOp {
     pub result: Id,
     pub mask: Id,
     pub original_target: Id,
     pub edited: Id,
+    // 0 to 100
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    // only the lightness of the edited image is used
+    Luminosity,
+    // only the chroma and hue of the edited image are used
+    Color,
 }
 
 #[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
@@ -307,3 +435,81 @@ pub struct ApplyFramingOp {
     pub arg: Id,
     pub frame: Frame,
 }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum RetouchMode {
+    // blends the texture of the source with the colors around the destination
+    Heal,
+    // copies the source as-is
+    Clone,
+}
+
+// positions are relative to the image after geometry edits, radius is relative to the image height
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct RetouchSpot {
+    pub mode: RetouchMode,
+    pub source_x: f32,
+    pub source_y: f32,
+    pub destination_x: f32,
+    pub destination_y: f32,
+    pub radius: f32,
+    pub feather: f32,
+    pub opacity: f32,
+}
+
+impl RetouchSpot {
+    pub fn new(mode: RetouchMode, destination_x: f32, destination_y: f32, aspect_ratio: f32) -> Self {
+        let radius = 0.03;
+        // place the source next to the destination, on whichever side has more room
+        let offset_x = radius * 3.0 / aspect_ratio;
+        let source_x = if destination_x < 0.5 {
+            destination_x + offset_x
+        } else {
+            destination_x - offset_x
+        };
+        Self {
+            mode,
+            source_x,
+            source_y: destination_y,
+            destination_x,
+            destination_y,
+            radius,
+            feather: 50.0,
+            opacity: 100.0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ApplyRetouchSpotOp {
+    pub result: Id,
+    pub arg: Id,
+    pub spot: RetouchSpot,
+}
+
+// position is relative to the image after geometry edits, pupil_radius is relative to the image height
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct RedEyeCorrection {
+    pub center_x: f32,
+    pub center_y: f32,
+    pub pupil_radius: f32,
+    pub darken: f32,
+}
+
+impl RedEyeCorrection {
+    pub fn new(center_x: f32, center_y: f32) -> Self {
+        Self {
+            center_x,
+            center_y,
+            pupil_radius: 0.02,
+            darken: 50.0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ApplyRedEyeCorrectionOp {
+    pub result: Id,
+    pub arg: Id,
+    pub correction: RedEyeCorrection,
+}
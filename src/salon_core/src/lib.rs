@@ -2,6 +2,7 @@ pub mod editor;
 pub mod engine;
 pub mod ir;
 pub mod library;
+pub mod presets;
 pub mod runtime;
 pub mod services;
 pub mod session;
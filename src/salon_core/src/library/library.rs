@@ -22,15 +22,42 @@ use crate::services::thumbnail_generator::ThumbnailGeneratorService;
 pub enum LibraryImageIdentifier {
     Temp(Uuid), // images that we no longer have access to after the application closes
     Path(PathBuf),
+    // an additional, independently edited version of the image at the path
+    VirtualCopy(PathBuf, u32),
 }
 
 impl LibraryImageIdentifier {
+    // the path of the image file, which virtual copies share with the original
     pub fn get_path(&self) -> Option<PathBuf> {
         match self {
             LibraryImageIdentifier::Path(ref p) => Some(p.clone()),
+            LibraryImageIdentifier::VirtualCopy(ref p, _) => Some(p.clone()),
             _ => None,
         }
     }
+
+    // virtual copies keep their own edit and thumbnail, in a subdirectory named after this index
+    pub fn virtual_copy_index(&self) -> Option<u32> {
+        match self {
+            LibraryImageIdentifier::VirtualCopy(_, copy_index) => Some(*copy_index),
+            _ => None,
+        }
+    }
+
+    // for virtual copies, this is the identifier of the image that they are a copy of
+    pub fn get_original(&self) -> LibraryImageIdentifier {
+        match self {
+            LibraryImageIdentifier::VirtualCopy(ref p, _) => {
+                LibraryImageIdentifier::Path(p.clone())
+            }
+            _ => self.clone(),
+        }
+    }
+
+    pub fn is_virtual_copy(&self) -> bool {
+        matches!(self, LibraryImageIdentifier::VirtualCopy(_, _))
+    }
+
     pub fn is_temp(&self) -> bool {
         match self {
             LibraryImageIdentifier::Temp(_) => true,
@@ -64,6 +91,9 @@ pub struct Library {
 
     albums: Vec<Album>,
 
+    // the highest virtual copy index ever used for each original, so that deleted copies' indices aren't reused
+    last_virtual_copy_indices: HashMap<PathBuf, u32>,
+
     runtime: Arc<Runtime>,
     toolbox: Arc<Toolbox>,
     services: Arc<Services>,
@@ -74,12 +104,18 @@ struct LibraryPersistentState {
     version: Version,
     items: Vec<LibraryPersistentStateItem>,
     albums: Vec<AlbumPersistentState>,
+    // libraries saved before deleted virtual copy indices were remembered don't have this field
+    #[serde(default)]
+    last_virtual_copy_indices: HashMap<PathBuf, u32>,
 }
 
 #[derive(Clone, serde::Deserialize, serde::Serialize)]
 struct LibraryPersistentStateItem {
     pub path: PathBuf,
     pub rating: ImageRating,
+    // libraries saved before virtual copies existed don't have this field
+    #[serde(default)]
+    pub virtual_copy_index: Option<u32>,
 }
 
 impl Library {
@@ -93,6 +129,7 @@ impl Library {
             thumbnails_cache: ImageCache::new(300),
             unavailable_items: HashSet::new(),
             albums: Vec::new(),
+            last_virtual_copy_indices: HashMap::new(),
             runtime,
             toolbox,
             services,
@@ -331,6 +368,78 @@ impl Library {
         identifiers
     }
 
+    // creates a virtual copy of an image that has a path, which is also added into the albums containing the image
+    pub fn create_virtual_copy(
+        &mut self,
+        original: &LibraryImageIdentifier,
+    ) -> Option<LibraryImageIdentifier> {
+        let path = original.get_path()?;
+        let last_copy_index = self
+            .items
+            .keys()
+            .filter_map(|identifier| match identifier {
+                LibraryImageIdentifier::VirtualCopy(p, i) if *p == path => Some(*i),
+                _ => None,
+            })
+            .chain(self.last_virtual_copy_indices.get(&path).copied())
+            .max()
+            .unwrap_or(0);
+        let copy_index = last_copy_index + 1;
+        self.last_virtual_copy_indices
+            .insert(path.clone(), copy_index);
+        let identifier = self.add_virtual_copy_impl(path, copy_index, true);
+        for album_index in 0..self.albums.len() {
+            if self.albums[album_index].contains_image(&original.get_original()) {
+                self.add_existing_item_into_album(&identifier, album_index);
+                self.items
+                    .get_mut(&identifier)
+                    .unwrap()
+                    .albums
+                    .insert(album_index);
+            }
+        }
+        Some(identifier)
+    }
+
+    fn add_virtual_copy_impl(
+        &mut self,
+        path: PathBuf,
+        copy_index: u32,
+        ensure_order: bool,
+    ) -> LibraryImageIdentifier {
+        let mut metadata = LibraryImageMetaData { name: None };
+        if let Some(name) = path.file_name() {
+            if let Some(name) = name.to_str() {
+                metadata.name = Some(format!("{} (Virtual Copy {})", name, copy_index));
+            }
+        }
+
+        let id = LibraryImageIdentifier::VirtualCopy(path, copy_index);
+
+        let item = LibraryItem {
+            albums: HashSet::new(),
+            metadata,
+            rating: ImageRating::new(None),
+        };
+        self.add_item(item, None, None, id.clone(), None, ensure_order);
+        id
+    }
+
+    // the persisted edit of the copy is deleted as well, by the edit writer after any writes still queued for it
+    pub fn delete_virtual_copy(&mut self, identifier: &LibraryImageIdentifier) {
+        if !identifier.is_virtual_copy() || !self.items.contains_key(identifier) {
+            return;
+        }
+        let mut items_to_remove = HashSet::new();
+        items_to_remove.insert(identifier.clone());
+        self.remove_items(&items_to_remove);
+        self.thumbnails_cache.remove(identifier);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.services
+            .edit_writer
+            .request_delete_virtual_copy(identifier.clone());
+    }
+
     pub fn add_existing_item_into_album(&mut self, image: &LibraryImageIdentifier, album: usize) {
         if !self.albums[album].contains_image(image) {
             self.albums[album].additional_images.push(image.clone());
@@ -490,6 +599,12 @@ impl Library {
                 }
             }
         }
+        // virtual copies go away along with their originals
+        for identifier in self.items.keys() {
+            if identifier.is_virtual_copy() && removed_items.contains(&identifier.get_original()) {
+                removed_items.insert(identifier.clone());
+            }
+        }
         self.remove_items(&removed_items);
     }
 
@@ -514,13 +629,11 @@ impl Library {
             let index = self.item_indices.remove(item_identifier).unwrap();
             item_indices.push(index);
             #[cfg(not(target_arch = "wasm32"))]
-            if let Some(image_path) = item_identifier.get_path() {
-                if let Some(thumbnail_path) =
-                    ThumbnailGeneratorService::get_thumbnail_path_for_image_path(&image_path)
-                {
-                    if thumbnail_path.exists() {
-                        let _ = std::fs::remove_file(thumbnail_path);
-                    }
+            if let Some(thumbnail_path) =
+                ThumbnailGeneratorService::get_thumbnail_path_for_identifier(item_identifier)
+            {
+                if thumbnail_path.exists() {
+                    let _ = std::fs::remove_file(thumbnail_path);
                 }
             }
             for album_index in item.albums.iter() {
@@ -601,20 +714,18 @@ impl Library {
 
     fn maybe_load_thumbnail(&mut self, identifier: &LibraryImageIdentifier) -> Option<Arc<Image>> {
         if !self.thumbnails_cache.contains(identifier) {
-            if let Some(image_path) = identifier.get_path() {
-                #[cfg(not(target_arch = "wasm32"))]
-                if let Some(thumbnail_path) =
-                    ThumbnailGeneratorService::get_thumbnail_path_for_image_path(&image_path)
-                {
-                    if let Ok(thumbnail) = self.runtime.create_image_from_path(&thumbnail_path) {
-                        let thumbnail = Arc::new(thumbnail);
-                        self.toolbox.generate_mipmap(&thumbnail);
-                        self.thumbnails_cache
-                            .set(identifier.clone(), thumbnail.clone());
-                        return Some(thumbnail);
-                    } else {
-                        let _ = std::fs::remove_file(thumbnail_path);
-                    }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(thumbnail_path) =
+                ThumbnailGeneratorService::get_thumbnail_path_for_identifier(identifier)
+            {
+                if let Ok(thumbnail) = self.runtime.create_image_from_path(&thumbnail_path) {
+                    let thumbnail = Arc::new(thumbnail);
+                    self.toolbox.generate_mipmap(&thumbnail);
+                    self.thumbnails_cache
+                        .set(identifier.clone(), thumbnail.clone());
+                    return Some(thumbnail);
+                } else {
+                    let _ = std::fs::remove_file(thumbnail_path);
                 }
             }
         }
@@ -623,6 +734,13 @@ impl Library {
 
     // return the item or delete the identifier
     fn ensure_fully_loaded(&mut self, identifier: &LibraryImageIdentifier) {
+        if identifier.is_virtual_copy() {
+            // the image itself is shared with the original, only the thumbnail is separate
+            let _ = self.maybe_load_thumbnail(identifier);
+            self.ensure_fully_loaded(&identifier.get_original());
+            return;
+        }
+
         // If thumbnail file exists, load that first.
         let _ = self.maybe_load_thumbnail(identifier);
 
@@ -637,7 +755,7 @@ impl Library {
                             .thumbnail_generator
                             .generate_and_maybe_save_thumbnail_for_image(
                                 image.clone(),
-                                Some(path.clone()),
+                                Some(identifier),
                             );
                         self.thumbnails_cache.set(identifier.clone(), thumbnail);
                     }
@@ -669,7 +787,7 @@ impl Library {
             let thumbnail = self
                 .services
                 .thumbnail_generator
-                .generate_and_maybe_save_thumbnail_for_image(image, identifier.get_path());
+                .generate_and_maybe_save_thumbnail_for_image(image, Some(identifier));
             self.thumbnails_cache.set(identifier.clone(), thumbnail);
         }
     }
@@ -680,7 +798,7 @@ impl Library {
         identifier: &LibraryImageIdentifier,
     ) -> Option<Arc<Image>> {
         self.ensure_fully_loaded(identifier);
-        self.images_cache.get(identifier)
+        self.images_cache.get(&identifier.get_original())
     }
 
     // return the item or delete the identifier
@@ -697,11 +815,19 @@ impl Library {
             return Some(thumbnail);
         }
 
+        if identifier.is_virtual_copy() {
+            // until the copy has a thumbnail of its own, it looks the same as the original
+            if let Some(thumbnail) = self.maybe_load_thumbnail(identifier) {
+                return Some(thumbnail);
+            }
+            return self.get_thumbnail_from_identifier(&identifier.get_original());
+        }
+
         if let Some(image) = self.images_cache.get(identifier) {
             return Some(
                 self.services
                     .thumbnail_generator
-                    .generate_and_maybe_save_thumbnail_for_image(image, identifier.get_path()),
+                    .generate_and_maybe_save_thumbnail_for_image(image, Some(identifier)),
             );
         }
 
@@ -724,7 +850,7 @@ impl Library {
         let thumbnail = self
             .services
             .thumbnail_generator
-            .generate_and_maybe_save_thumbnail_for_image(editted_image, identifier.get_path());
+            .generate_and_maybe_save_thumbnail_for_image(editted_image, Some(identifier));
         self.thumbnails_cache.set(identifier.clone(), thumbnail);
     }
 
@@ -748,13 +874,17 @@ impl Library {
 
         let mut persistent_items = Vec::new();
         for identifier in self.items_ordered.iter() {
-            if let LibraryImageIdentifier::Path(ref path) = identifier {
-                let item = LibraryPersistentStateItem {
-                    path: path.clone(),
-                    rating: self.items[identifier].rating.clone(),
-                };
-                persistent_items.push(item);
-            }
+            let (path, virtual_copy_index) = match identifier {
+                LibraryImageIdentifier::Path(ref path) => (path, None),
+                LibraryImageIdentifier::VirtualCopy(ref path, i) => (path, Some(*i)),
+                LibraryImageIdentifier::Temp(_) => continue,
+            };
+            let item = LibraryPersistentStateItem {
+                path: path.clone(),
+                rating: self.items[identifier].rating.clone(),
+                virtual_copy_index,
+            };
+            persistent_items.push(item);
         }
         let mut persistent_albums = Vec::new();
         for album in self.albums.iter() {
@@ -764,6 +894,7 @@ impl Library {
             version: Version::current_build(),
             items: persistent_items,
             albums: persistent_albums,
+            last_virtual_copy_indices: self.last_virtual_copy_indices.clone(),
         }
     }
 
@@ -791,9 +922,25 @@ impl Library {
                     if let Ok(state) =
                         serde_json::from_str::<LibraryPersistentState>(state_json_str.as_str())
                     {
+                        // the state is loaded as far as it's understood, but the original is kept,
+                        // since saving will overwrite it with what this version knows about
+                        if state.version > Version::current_build() {
+                            let v = &state.version;
+                            let backup_name =
+                                format!("library.{}.{}.{}.json", v.major, v.minor, v.patch);
+                            log::warn!(
+                                "library was saved by a newer version, keeping {}",
+                                backup_name
+                            );
+                            let _ = std::fs::copy(&path, dir.join(backup_name));
+                        }
                         for item in state.items {
-                            let identifier =
-                                self.add_item_from_path_impl(item.path.clone(), None, false);
+                            let identifier = match item.virtual_copy_index {
+                                Some(i) => self.add_virtual_copy_impl(item.path.clone(), i, false),
+                                None => {
+                                    self.add_item_from_path_impl(item.path.clone(), None, false)
+                                }
+                            };
                             if let Some(loaded_item) = self.items.get_mut(&identifier) {
                                 loaded_item.rating = item.rating;
                             }
@@ -802,6 +949,7 @@ impl Library {
                                 .thumbnail_generator
                                 .request_thumbnail_for_image_at_path(item.path);
                         }
+                        self.last_virtual_copy_indices = state.last_virtual_copy_indices;
                         for album in state.albums {
                             self.albums.push(Album::from_persistent_state(album))
                         }
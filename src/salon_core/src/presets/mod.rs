@@ -0,0 +1,5 @@
+mod preset;
+mod preset_library;
+
+pub use preset::*;
+pub use preset_library::*;
@@ -0,0 +1,85 @@
+use serde_json::Value;
+
+use crate::editor::{AppliedLook, Edit, EditSections};
+use crate::utils::math::get_edited_aspect_ratio;
+use crate::versioning::Version;
+
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Preset {
+    pub version: Version,
+    pub name: String,
+    // the edit that the preset was created from, without geometry edits or image-specific retouching
+    pub edit: Edit,
+    // aspect ratio of the image that the preset was created from (after its geometry edits),
+    // which is what the positions of its masks are relative to.
+    pub image_aspect_ratio: f32,
+    // the parts of `edit` that the preset applies. this never includes geometry.
+    pub sections: EditSections,
+}
+
+impl Preset {
+    pub fn new(
+        name: String,
+        edit: &Edit,
+        original_image_aspect_ratio: f32,
+        sections: EditSections,
+    ) -> Self {
+        let image_aspect_ratio = get_edited_aspect_ratio(original_image_aspect_ratio, edit);
+        let edit = Edit {
+            resize_factor: None,
+            rotation_degrees: None,
+            crop_rect: None,
+            retouch_spots: Vec::new(),
+            red_eye_corrections: Vec::new(),
+            framing: None,
+            applied_look: None,
+            ..edit.clone()
+        };
+        Self {
+            version: Version::current_build(),
+            name,
+            edit,
+            image_aspect_ratio,
+            sections: EditSections {
+                geometry: false,
+                ..sections
+            },
+        }
+    }
+
+    // the result of applying the preset on top of `edit`, with `amount` (from 0 to 100) as its strength
+    pub fn apply(&self, edit: &Edit, original_image_aspect_ratio: f32, amount: f32) -> Edit {
+        let mut result = edit.clone();
+        self.sections.apply(
+            &self.edit,
+            self.image_aspect_ratio,
+            &mut result,
+            original_image_aspect_ratio,
+        );
+        AppliedLook::apply(edit, &result, amount)
+    }
+
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize to json")
+    }
+
+    pub fn from_json_str(json_str: &str) -> Result<Self, String> {
+        let mut value = serde_json::from_str::<Value>(json_str).map_err(|e| e.to_string())?;
+        let Value::Object(ref mut preset) = value else {
+            return Err("preset json is not an object".to_owned());
+        };
+        // the edit inside the preset is migrated like a persisted edit, according to the preset's version
+        if let Some(mut edit) = preset.remove("edit") {
+            if let (Value::Object(ref mut edit), Some(version)) = (&mut edit, preset.get("version"))
+            {
+                edit.insert("version".to_owned(), version.clone());
+            }
+            let edit = Edit::from_persisted_json_value(edit)?;
+            preset.insert(
+                "edit".to_owned(),
+                serde_json::to_value(edit).map_err(|e| e.to_string())?,
+            );
+        }
+        serde_json::from_value::<Preset>(value).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use crate::session::Session;
+
+use super::Preset;
+
+pub struct PresetGroup {
+    pub name: String,
+    pub presets: Vec<Preset>,
+}
+
+// presets are persisted as one json file per preset, in one directory per group
+pub struct PresetLibrary {
+    groups: Vec<PresetGroup>,
+}
+
+impl Default for PresetLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresetLibrary {
+    pub fn new() -> Self {
+        Self { groups: Vec::new() }
+    }
+
+    pub fn default_group_name() -> &'static str {
+        "User Presets"
+    }
+
+    pub fn groups(&self) -> &Vec<PresetGroup> {
+        &self.groups
+    }
+
+    // adds the preset into the group (creating the group if needed), replacing any preset of the same name
+    pub fn add_preset(&mut self, group_name: &str, preset: Preset) {
+        self.save_preset_file(group_name, &preset);
+        let group_index = match self.groups.iter().position(|g| g.name == group_name) {
+            Some(i) => i,
+            None => {
+                self.groups.push(PresetGroup {
+                    name: group_name.to_owned(),
+                    presets: Vec::new(),
+                });
+                self.groups.sort_by(|a, b| a.name.cmp(&b.name));
+                self.groups
+                    .iter()
+                    .position(|g| g.name == group_name)
+                    .unwrap()
+            }
+        };
+        let presets = &mut self.groups[group_index].presets;
+        if let Some(i) = presets.iter().position(|p| p.name == preset.name) {
+            presets[i] = preset;
+        } else {
+            presets.push(preset);
+            presets.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+    }
+
+    pub fn remove_preset(&mut self, group_index: usize, preset_index: usize) {
+        let group = &mut self.groups[group_index];
+        let preset = group.presets.remove(preset_index);
+        if let Some(path) = Self::preset_file_path(&group.name, &preset.name) {
+            let _ = std::fs::remove_file(path);
+        }
+        if group.presets.is_empty() {
+            if let Some(dir) = Self::group_dir(&group.name) {
+                let _ = std::fs::remove_dir(dir);
+            }
+            self.groups.remove(group_index);
+        }
+    }
+
+    pub fn import_preset(&mut self, json_str: &str, group_name: &str) -> Result<(), String> {
+        let preset = Preset::from_json_str(json_str)?;
+        self.add_preset(group_name, preset);
+        Ok(())
+    }
+
+    fn presets_dir() -> Option<PathBuf> {
+        Some(Session::get_persistent_storage_dir()?.join("presets"))
+    }
+
+    fn group_dir(group_name: &str) -> Option<PathBuf> {
+        Some(Self::presets_dir()?.join(Self::sanitize_file_name(group_name)))
+    }
+
+    fn preset_file_path(group_name: &str, preset_name: &str) -> Option<PathBuf> {
+        Some(Self::group_dir(group_name)?.join(Self::sanitize_file_name(preset_name) + ".json"))
+    }
+
+    fn sanitize_file_name(name: &str) -> String {
+        name.chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                _ => c,
+            })
+            .collect()
+    }
+
+    fn save_preset_file(&self, group_name: &str, preset: &Preset) {
+        if let Some(path) = Self::preset_file_path(group_name, &preset.name) {
+            if std::fs::create_dir_all(path.parent().unwrap()).is_ok() {
+                let _ = std::fs::write(&path, preset.to_json_string());
+            }
+        }
+    }
+
+    pub fn load_persistent_state(&mut self) {
+        let Some(presets_dir) = Self::presets_dir() else {
+            return;
+        };
+        let Ok(group_entries) = std::fs::read_dir(presets_dir) else {
+            return;
+        };
+        for group_entry in group_entries.flatten() {
+            if !group_entry.path().is_dir() {
+                continue;
+            }
+            let group_name = group_entry.file_name().to_string_lossy().to_string();
+            let Ok(preset_entries) = std::fs::read_dir(group_entry.path()) else {
+                continue;
+            };
+            let mut presets = Vec::new();
+            for preset_entry in preset_entries.flatten() {
+                if let Ok(json_str) = std::fs::read_to_string(preset_entry.path()) {
+                    if let Ok(preset) = Preset::from_json_str(json_str.as_str()) {
+                        presets.push(preset);
+                    }
+                }
+            }
+            if !presets.is_empty() {
+                presets.sort_by(|a, b| a.name.cmp(&b.name));
+                self.groups.push(PresetGroup {
+                    name: group_name,
+                    presets,
+                });
+            }
+        }
+        self.groups.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
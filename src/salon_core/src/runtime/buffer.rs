@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use crate::{runtime::Runtime, utils::uuid::Uuid};
+use crate::{
+    runtime::Runtime,
+    utils::uuid::{get_next_uuid, Uuid},
+};
 
 pub struct Buffer {
     pub properties: BufferProperties,
@@ -60,6 +63,8 @@ pub struct BufferReader<ValueType> {
     transform: Box<dyn FnOnce(Vec<u32>) -> ValueType>,
     value: Option<ValueType>,
     pending_read: bool,
+    // identifies the value read by this reader, as opposed to the initial value
+    uuid: Uuid,
 }
 
 impl<ValueType> BufferReader<ValueType> {
@@ -77,6 +82,7 @@ impl<ValueType> BufferReader<ValueType> {
             transform,
             value: initial_value,
             pending_read: true,
+            uuid: get_next_uuid(),
         }
     }
 
@@ -118,6 +124,10 @@ impl<ValueType> BufferReader<ValueType> {
         self.pending_read
     }
 
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
     pub fn buffer(&self) -> &Arc<Buffer> {
         &self.buffer
     }
@@ -1,7 +1,7 @@
 use crate::utils::uuid::Uuid;
 use std::sync::Arc;
 
-use super::{Buffer, Runtime, Toolbox};
+use super::{first_channel_u16_row_length, Buffer, Runtime, Toolbox};
 
 pub struct Image {
     pub properties: ImageProperties,
@@ -66,6 +66,14 @@ impl Image {
     pub fn aspect_ratio(&self) -> f32 {
         self.properties.dimensions.0 as f32 / self.properties.dimensions.1 as f32
     }
+    // the pixel at a position relative to the image size, clamped to the image
+    pub fn pixel_at(&self, (x, y): (f32, f32)) -> (i32, i32) {
+        let (w, h) = self.properties.dimensions;
+        (
+            ((x * w as f32) as i32).clamp(0, w as i32 - 1),
+            ((y * h as f32) as i32).clamp(0, h as i32 - 1),
+        )
+    }
     pub fn mip_level_count(dimensions: &(u32, u32)) -> u32 {
         let max_dim = std::cmp::max(dimensions.0, dimensions.1);
         let levels = (max_dim as f32).log2() as u32;
@@ -167,3 +175,81 @@ impl ImageReaderJpeg {
         &self.image
     }
 }
+
+// reads the first channel of an image (e.g. a mask) and encodes it as a 16-bit grayscale png
+pub struct ImageReaderPng16Grayscale {
+    runtime: Arc<Runtime>,
+    image: Arc<Image>,
+    buffer: Arc<Buffer>,
+    map_ready_receiver: flume::Receiver<()>,
+    result_png_data: Option<Vec<u8>>,
+    pending_read: bool,
+}
+
+impl ImageReaderPng16Grayscale {
+    pub fn new(runtime: Arc<Runtime>, toolbox: Arc<Toolbox>, image: Arc<Image>) -> Self {
+        let buffer = toolbox.copy_image_first_channel_u16_to_buffer(&image);
+        let map_ready_receiver: flume::Receiver<()> = runtime.map_host_readable_buffer(&buffer);
+        Self {
+            runtime,
+            image,
+            buffer,
+            map_ready_receiver,
+            result_png_data: None,
+            pending_read: true,
+        }
+    }
+
+    pub fn take_png_data(&mut self) -> Option<Vec<u8>> {
+        self.result_png_data.take()
+    }
+
+    pub fn poll_png_data(&mut self) -> Option<&Vec<u8>> {
+        if self.pending_read && self.map_ready_receiver.try_recv().is_ok() {
+            self.read_png_data_from_mapped_buffer();
+        }
+        self.result_png_data.as_ref()
+    }
+
+    pub async fn await_png_data(&mut self) -> &Vec<u8> {
+        if self.pending_read {
+            if self.map_ready_receiver.recv_async().await.is_ok() {
+                self.read_png_data_from_mapped_buffer();
+            } else {
+                panic!("recv_async().await failed")
+            }
+        }
+        self.result_png_data.as_ref().unwrap()
+    }
+
+    fn read_png_data_from_mapped_buffer(&mut self) {
+        let (w, h) = (
+            self.image.properties.dimensions.0,
+            self.image.properties.dimensions.1,
+        );
+        let data: Vec<u16> = self.runtime.read_mapped_buffer(&self.buffer);
+        // rows of the buffer are padded to an even width
+        let row_length = first_channel_u16_row_length(w) as usize * 2;
+        let gray: Vec<u16> = data
+            .chunks_exact(row_length)
+            .flat_map(|row| &row[..w as usize])
+            .copied()
+            .collect();
+        let image_buffer: image::ImageBuffer<image::Luma<u16>, Vec<u16>> =
+            image::ImageBuffer::from_raw(w, h, gray).unwrap();
+        let mut png: Vec<u8> = Vec::new();
+        image::DynamicImage::ImageLuma16(image_buffer)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageOutputFormat::Png)
+            .expect("Failed to encode image into png");
+        self.result_png_data = Some(png);
+        self.pending_read = false;
+    }
+
+    pub fn pending_read(&self) -> bool {
+        self.pending_read
+    }
+
+    pub fn image(&self) -> &Arc<Image> {
+        &self.image
+    }
+}
@@ -13,6 +13,7 @@ use crate::runtime::{
     image::{ColorSpace, Image, ImageFormat, ImageProperties},
     sampler::Sampler,
 };
+use crate::utils::math::f32_to_f16_bits;
 
 pub struct Runtime {
     pub adapter: Arc<wgpu::Adapter>,
@@ -236,25 +237,52 @@ impl Runtime {
         let result = self.create_image_of_properties(properties);
 
         let image_buffer_rgba8 = dynamic_image.to_rgba8();
-        let image_bytes = image_buffer_rgba8.as_raw();
+        self.write_image_data(&result, image_buffer_rgba8.as_raw());
+        result
+    }
+
+    // keeps more than 8 bits per channel of e.g. 16-bit images, whose values are stored as they are
+    pub fn create_rgba16float_image_from_dynamic_image(
+        &self,
+        dynamic_image: image::DynamicImage,
+    ) -> Image {
+        let properties = ImageProperties {
+            dimensions: dynamic_image.dimensions(),
+            format: ImageFormat::Rgba16Float,
+            color_space: ColorSpace::LinearRGB,
+        };
+        let result = self.create_image_of_properties(properties);
+
+        let halfs: Vec<u16> = dynamic_image
+            .to_rgba32f()
+            .as_raw()
+            .iter()
+            .map(|v| f32_to_f16_bits(*v))
+            .collect();
+        self.write_image_data(&result, bytemuck::cast_slice(halfs.as_slice()));
+        result
+    }
 
+    // writes the full-size mip level of the image, where data is in the format of the image
+    fn write_image_data(&self, image: &Image, data: &[u8]) {
+        let dimensions = image.properties.dimensions;
         let size = wgpu::Extent3d {
             width: dimensions.0,
             height: dimensions.1,
             depth_or_array_layers: 1,
         };
 
-        let bytes_per_row = dimensions.0 * result.properties.format.bytes_per_pixel();
+        let bytes_per_row = dimensions.0 * image.properties.format.bytes_per_pixel();
 
         self.queue.write_texture(
             // Tells wgpu where to copy the pixel data
             wgpu::ImageCopyTexture {
-                texture: &result.texture,
+                texture: &image.texture,
                 mip_level: 0,
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            image_bytes.as_slice(),
+            data,
             wgpu::ImageDataLayout {
                 offset: 0,
                 bytes_per_row: Some(bytes_per_row),
@@ -262,8 +290,6 @@ impl Runtime {
             },
             size,
         );
-
-        result
     }
 
     pub fn copy_image(&self, src: &Image, dest: &Image) {
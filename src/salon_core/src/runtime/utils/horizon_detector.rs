@@ -0,0 +1,128 @@
+use std::{mem::size_of, sync::Arc};
+
+use crate::runtime::{Buffer, BufferProperties, BufferReader, Image, Runtime};
+
+use crate::shader::{Shader, ShaderLibraryModule};
+use crate::utils::math::div_up;
+
+use super::{BindGroupDescriptor, BindGroupEntry, BindGroupManager, BindingResource};
+
+// finds the dominant near-horizontal/vertical edge angle of an image, via a histogram of gradient orientations
+pub struct HorizonDetector {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_manager: BindGroupManager,
+    runtime: Arc<Runtime>,
+}
+impl HorizonDetector {
+    pub fn new(runtime: Arc<Runtime>) -> Self {
+        let shader_code = Shader::from_code(include_str!("./horizon_detector.wgsl"))
+            .with_library(ShaderLibraryModule::ColorSpaces)
+            .full_code();
+
+        let (pipeline, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("HorizonDetector"));
+        let bind_group_manager = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        HorizonDetector {
+            runtime,
+            pipeline,
+            bind_group_manager,
+        }
+    }
+
+    pub fn num_bins() -> usize {
+        180
+    }
+
+    pub fn bin_size_degrees() -> f32 {
+        90.0 / Self::num_bins() as f32
+    }
+}
+impl HorizonDetector {
+    // the result is the rotation (in degrees) that levels the image, or None if the image has no usable edges
+    pub fn detect(&mut self, input_img: &Image) -> BufferReader<Option<f32>> {
+        let buffer_properties = BufferProperties {
+            size: Self::num_bins() * size_of::<u32>(),
+            host_readable: true,
+        };
+
+        // newly created buffers are zero-initialized, so no need to clear the histogram
+        let output_buffer: Arc<Buffer> =
+            Arc::new(self.runtime.create_buffer_of_properties(buffer_properties));
+
+        self.bind_group_manager.clear_cache();
+
+        let bind_group = self.bind_group_manager.get_or_create(BindGroupDescriptor {
+            entries: vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Texture(input_img),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Buffer(&output_buffer),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .runtime
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            cpass.set_pipeline(&self.pipeline);
+
+            cpass.set_bind_group(0, bind_group, &[]);
+
+            let num_workgroups_x = div_up(input_img.properties.dimensions.0, 16);
+            let num_workgroups_y = div_up(input_img.properties.dimensions.1, 16);
+            cpass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+        self.runtime.queue.submit(Some(encoder.finish()));
+
+        BufferReader::new(
+            self.runtime.clone(),
+            output_buffer,
+            None,
+            Box::new(|v| estimate_leveling_rotation(v.as_slice())),
+        )
+    }
+}
+
+fn estimate_leveling_rotation(bins: &[u32]) -> Option<f32> {
+    let n = bins.len() as i32;
+    let get_bin = |i: i32| bins[i.rem_euclid(n) as usize] as f32;
+
+    // the histogram wraps around, since -45 and 45 degrees are the same orientation (modulo 90)
+    let smoothed: Vec<f32> = (0..n)
+        .map(|i| 0.25 * get_bin(i - 1) + 0.5 * get_bin(i) + 0.25 * get_bin(i + 1))
+        .collect();
+    let get_smoothed = |i: i32| smoothed[i.rem_euclid(n) as usize];
+
+    let mut peak = 0;
+    for i in 0..n {
+        if smoothed[i as usize] > smoothed[peak as usize] {
+            peak = i;
+        }
+    }
+    if smoothed[peak as usize] == 0.0 {
+        return None;
+    }
+
+    // sub-bin refinement by fitting a parabola through the peak and its neighbors
+    let (left, center, right) = (get_smoothed(peak - 1), get_smoothed(peak), get_smoothed(peak + 1));
+    let denominator = left - 2.0 * center + right;
+    let mut offset = 0.0;
+    if denominator != 0.0 {
+        offset = (0.5 * (left - right) / denominator).clamp(-0.5, 0.5);
+    }
+
+    let bin_size = HorizonDetector::bin_size_degrees();
+    let edge_angle = -45.0 + (peak as f32 + offset) * bin_size;
+
+    // edges that are rotated clockwise by edge_angle are leveled by rotating the image counter-clockwise
+    Some(-edge_angle)
+}
@@ -0,0 +1,70 @@
+const num_bins: u32 = 180u;
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+struct Buffer {
+    // gradient-magnitude-weighted histogram of edge orientations, relative to the nearest horizontal/vertical direction
+    // bin i is centered at -45 + i * 0.5 degrees (which wraps around, since -45 and 45 are the same orientation)
+    bins: array<atomic<u32>, num_bins>,
+};
+
+@group(0) @binding(1)
+var<storage, read_write> buffer: Buffer;
+
+var<workgroup> buffer_local: Buffer;
+
+fn luma_at(xy: vec2<i32>, input_size: vec2<i32>) -> f32 {
+    let clamped = clamp(xy, vec2(0), input_size - 1);
+    let c = linear_to_srgb(textureLoad(input, clamped, 0).rgb);
+    return dot(c, vec3(0.2126, 0.7152, 0.0722));
+}
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(local_invocation_id) local_id: vec3<u32>) {
+    let input_size = vec2<i32>(textureDimensions(input));
+
+    let out_of_bounds = i32(global_id.x) >= input_size.x || i32(global_id.y) >= input_size.y;
+
+    if (!out_of_bounds) {
+        let xy = vec2<i32>(global_id.xy);
+
+        // sobel
+        let tl = luma_at(xy + vec2(-1, -1), input_size);
+        let t = luma_at(xy + vec2(0, -1), input_size);
+        let tr = luma_at(xy + vec2(1, -1), input_size);
+        let l = luma_at(xy + vec2(-1, 0), input_size);
+        let r = luma_at(xy + vec2(1, 0), input_size);
+        let bl = luma_at(xy + vec2(-1, 1), input_size);
+        let b = luma_at(xy + vec2(0, 1), input_size);
+        let br = luma_at(xy + vec2(1, 1), input_size);
+
+        let gx = (tr + 2.0 * r + br) - (tl + 2.0 * l + bl);
+        let gy = (bl + 2.0 * b + br) - (tl + 2.0 * t + tr);
+        let magnitude = length(vec2(gx, gy));
+
+        // ignore flat and noisy areas
+        if (magnitude > 0.1) {
+            // an edge is perpendicular to its gradient, which doesn't matter once the angle is taken modulo 90 degrees
+            var angle = degrees(atan2(gy, gx));
+            angle = angle - 90.0 * floor(angle / 90.0);
+            if (angle >= 45.0) {
+                angle = angle - 90.0;
+            }
+            let bin = u32(round((angle + 45.0) * 2.0)) % num_bins;
+            let weight = u32(min(magnitude, 4.0) * 16.0);
+            atomicAdd(&buffer_local.bins[bin], weight);
+        }
+    }
+
+    // this needs to be executed even for out-of-bounds threads;
+    workgroupBarrier();
+
+    // as is this, so that no bin is skipped for workgroups on the image border
+    var write_index = local_id.x * 16u + local_id.y;
+    while (write_index < num_bins) {
+        atomicAdd(&buffer.bins[write_index], atomicLoad(&buffer_local.bins[write_index]));
+        write_index = write_index + 16u * 16u;
+    }
+}
@@ -0,0 +1,143 @@
+use std::{mem::size_of, sync::Arc};
+
+use crate::runtime::{Buffer, BufferProperties, BufferReader, Image, Runtime};
+
+use crate::shader::{Shader, ShaderLibraryModule};
+use crate::utils::math::div_up;
+
+use super::{BindGroupDescriptor, BindGroupEntry, BindGroupManager, BindingResource};
+
+// estimates the color of the light in an image, by combining the gray-world and white-patch assumptions
+// (i.e. that the average color, and the color of the brightest areas, should be neutral)
+pub struct IlluminantEstimator {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_manager: BindGroupManager,
+    runtime: Arc<Runtime>,
+}
+impl IlluminantEstimator {
+    pub fn new(runtime: Arc<Runtime>) -> Self {
+        let shader_code = Shader::from_code(include_str!("./illuminant_estimator.wgsl"))
+            .with_library(ShaderLibraryModule::ColorSpaces)
+            .full_code();
+
+        let (pipeline, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("IlluminantEstimator"));
+        let bind_group_manager = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        IlluminantEstimator {
+            runtime,
+            pipeline,
+            bind_group_manager,
+        }
+    }
+
+    pub fn num_bins() -> usize {
+        256
+    }
+}
+impl IlluminantEstimator {
+    // the result is the linear rgb color of the light, normalized to a luminance of 1,
+    // or None if the image has no usable pixels (e.g. it's completely black or clipped)
+    pub fn estimate(&mut self, input_img: &Image) -> BufferReader<Option<[f32; 3]>> {
+        let buffer_properties = BufferProperties {
+            size: 4 * Self::num_bins() * size_of::<u32>(),
+            host_readable: true,
+        };
+
+        // newly created buffers are zero-initialized, so no need to clear the bins
+        let output_buffer: Arc<Buffer> =
+            Arc::new(self.runtime.create_buffer_of_properties(buffer_properties));
+
+        self.bind_group_manager.clear_cache();
+
+        let bind_group = self.bind_group_manager.get_or_create(BindGroupDescriptor {
+            entries: vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Texture(input_img),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Buffer(&output_buffer),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .runtime
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            cpass.set_pipeline(&self.pipeline);
+
+            cpass.set_bind_group(0, bind_group, &[]);
+
+            let num_workgroups_x = div_up(input_img.properties.dimensions.0, 16);
+            let num_workgroups_y = div_up(input_img.properties.dimensions.1, 16);
+            cpass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+        self.runtime.queue.submit(Some(encoder.finish()));
+
+        BufferReader::new(
+            self.runtime.clone(),
+            output_buffer,
+            None,
+            Box::new(|v| estimate_illuminant(v.as_slice())),
+        )
+    }
+}
+
+fn estimate_illuminant(data: &[u32]) -> Option<[f32; 3]> {
+    let n = IlluminantEstimator::num_bins();
+    let count = &data[0..n];
+    let sums = [&data[n..2 * n], &data[2 * n..3 * n], &data[3 * n..4 * n]];
+
+    // nearly black pixels are mostly noise, and clipped pixels don't tell the color of the light
+    let valid_bins = 8..n - 1;
+
+    let mut total_count = 0u64;
+    let mut total_sum = [0u64; 3];
+    for i in valid_bins.clone() {
+        total_count += count[i] as u64;
+        for c in 0..3 {
+            total_sum[c] += sums[c][i] as u64;
+        }
+    }
+    if total_count == 0 {
+        return None;
+    }
+    let gray_world = total_sum.map(|s| s as f32);
+
+    // the brightest 2% of the pixels
+    let mut white_patch_count = 0u64;
+    let mut white_patch_sum = [0u64; 3];
+    for i in valid_bins.rev() {
+        if white_patch_count * 50 >= total_count {
+            break;
+        }
+        white_patch_count += count[i] as u64;
+        for c in 0..3 {
+            white_patch_sum[c] += sums[c][i] as u64;
+        }
+    }
+    let white_patch = white_patch_sum.map(|s| s as f32);
+
+    let gray_world = normalize_luminance(gray_world)?;
+    let white_patch = normalize_luminance(white_patch)?;
+    let mut illuminant = [0.0; 3];
+    for c in 0..3 {
+        illuminant[c] = 0.5 * (gray_world[c] + white_patch[c]);
+    }
+    Some(illuminant)
+}
+
+fn normalize_luminance(rgb: [f32; 3]) -> Option<[f32; 3]> {
+    let luminance = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+    if luminance <= 0.0 {
+        return None;
+    }
+    Some(rgb.map(|c| c / luminance))
+}
@@ -0,0 +1,49 @@
+const num_bins: u32 = 256u;
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+struct Buffer {
+    // pixels are binned by their (sRGB encoded) luminance,
+    // and the sums of their colors (in fixed point) are kept per bin
+    count: array<atomic<u32>, num_bins>,
+    sum_r: array<atomic<u32>, num_bins>,
+    sum_g: array<atomic<u32>, num_bins>,
+    sum_b: array<atomic<u32>, num_bins>,
+};
+
+@group(0) @binding(1)
+var<storage, read_write> buffer: Buffer;
+
+var<workgroup> buffer_local: Buffer;
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(local_invocation_id) local_id: vec3<u32>) {
+    let input_size = textureDimensions(input);
+
+    let out_of_bounds = global_id.x >= input_size.x || global_id.y >= input_size.y;
+
+    if (!out_of_bounds) {
+        let c = clamp(textureLoad(input, global_id.xy, 0).rgb, vec3(0.0), vec3(1.0));
+        let luma = dot(c, vec3(0.2126, 0.7152, 0.0722));
+        let bin = min(u32(linear_to_srgb_channel(luma) * f32(num_bins)), num_bins - 1u);
+        atomicAdd(&buffer_local.count[bin], 1u);
+        atomicAdd(&buffer_local.sum_r[bin], u32(c.r * 1024.0));
+        atomicAdd(&buffer_local.sum_g[bin], u32(c.g * 1024.0));
+        atomicAdd(&buffer_local.sum_b[bin], u32(c.b * 1024.0));
+    }
+
+    // this needs to be executed even for out-of-bounds threads;
+    workgroupBarrier();
+
+    // as is this, so that no bin is skipped for workgroups on the image border
+    var write_index = local_id.x * 16u + local_id.y;
+    while (write_index < num_bins) {
+        atomicAdd(&buffer.count[write_index], atomicLoad(&buffer_local.count[write_index]));
+        atomicAdd(&buffer.sum_r[write_index], atomicLoad(&buffer_local.sum_r[write_index]));
+        atomicAdd(&buffer.sum_g[write_index], atomicLoad(&buffer_local.sum_g[write_index]));
+        atomicAdd(&buffer.sum_b[write_index], atomicLoad(&buffer_local.sum_b[write_index]));
+        write_index = write_index + 16u * 16u;
+    }
+}
@@ -14,6 +14,8 @@ use super::{
 pub struct ImageToBufferCopier {
     pipeline: wgpu::ComputePipeline,
     bind_group_manager: BindGroupManager,
+    pipeline_u16: wgpu::ComputePipeline,
+    bind_group_manager_u16: BindGroupManager,
     uniform_buffer: Buffer,
     runtime: Arc<Runtime>,
 }
@@ -31,10 +33,19 @@ impl ImageToBufferCopier {
             runtime.create_compute_pipeline(shader_code.as_str(), Some("ImageToBufferCopier"));
         let bind_group_manager = BindGroupManager::new(runtime.clone(), bind_group_layout);
 
+        let shader_code_u16 =
+            Shader::from_code(include_str!("./image_to_buffer_copier_u16.wgsl")).full_code();
+
+        let (pipeline_u16, bind_group_layout_u16) = runtime
+            .create_compute_pipeline(shader_code_u16.as_str(), Some("ImageToBufferCopierU16"));
+        let bind_group_manager_u16 = BindGroupManager::new(runtime.clone(), bind_group_layout_u16);
+
         ImageToBufferCopier {
             runtime,
             pipeline,
             bind_group_manager,
+            pipeline_u16,
+            bind_group_manager_u16,
             uniform_buffer,
         }
     }
@@ -100,4 +111,70 @@ impl ImageToBufferCopier {
         self.runtime.queue.submit(Some(encoder.finish()));
         output_buffer
     }
+
+    // copies the first channel of the image as 16-bit values, two per u32,
+    // with each row padded to an even width (see first_channel_u16_row_length)
+    pub fn copy_first_channel_u16(&mut self, input_img: &Image) -> Arc<Buffer> {
+        let w = input_img.properties.dimensions.0;
+        let h = input_img.properties.dimensions.1;
+        let row_length = first_channel_u16_row_length(w);
+
+        let buffer_properties = BufferProperties {
+            size: (row_length * h) as usize * size_of::<u32>(),
+            host_readable: true,
+        };
+
+        let output_buffer = Arc::new(self.runtime.create_buffer_of_properties(buffer_properties));
+
+        self.runtime.queue.write_buffer(
+            &self.uniform_buffer.buffer,
+            0,
+            bytemuck::cast_slice(&[w, h]),
+        );
+
+        self.bind_group_manager_u16.clear_cache();
+
+        let bind_group = self
+            .bind_group_manager_u16
+            .get_or_create(BindGroupDescriptor {
+                entries: vec![
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Texture(input_img),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Buffer(&output_buffer),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: BindingResource::Buffer(&self.uniform_buffer),
+                    },
+                ],
+            });
+
+        let mut encoder = self
+            .runtime
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            cpass.set_pipeline(&self.pipeline_u16);
+
+            cpass.set_bind_group(0, bind_group, &[]);
+
+            let num_workgroups_x = div_up(row_length, 16);
+            let num_workgroups_y = div_up(h, 16);
+            cpass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+        self.runtime.queue.submit(Some(encoder.finish()));
+        output_buffer
+    }
+}
+
+// the number of u32s per row of a buffer written by copy_first_channel_u16
+pub fn first_channel_u16_row_length(width: u32) -> u32 {
+    div_up(width, 2)
 }
@@ -0,0 +1,38 @@
+
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+// two 16-bit values per element, each row padded to an even number of values
+@group(0) @binding(1)
+var<storage, read_write> output: array<u32>;
+
+struct Params {
+    width: u32,
+    height: u32,
+};
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+fn to_u16(v: f32) -> u32 {
+    return u32(clamp(v, 0.0, 1.0) * 65535.0 + 0.5);
+}
+
+@compute
+@workgroup_size(16, 16)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let row_length = (params.width + 1u) / 2u;
+    if(global_id.x >= row_length || global_id.y >= params.height){
+        return;
+    }
+    let x = global_id.x * 2u;
+    let low = to_u16(textureLoad(input, vec2<u32>(x, global_id.y), 0).r);
+    var high = 0u;
+    if(x + 1u < params.width){
+        high = to_u16(textureLoad(input, vec2<u32>(x + 1u, global_id.y), 0).r);
+    }
+
+    let index = global_id.y * row_length + global_id.x;
+    output[index] = low | (high << 16u);
+}
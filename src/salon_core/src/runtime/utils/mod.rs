@@ -1,10 +1,15 @@
 mod bind_group_manager;
-mod mipmap_generator;
 mod color_space_converter;
+mod horizon_detector;
+mod illuminant_estimator;
 mod image_format_converter;
-mod image_to_buffer_copier;
 mod image_resizer;
+mod image_to_buffer_copier;
+mod mipmap_generator;
+mod pixel_reader;
 mod toolbox;
 
 pub use bind_group_manager::*;
-pub use toolbox::*;
\ No newline at end of file
+pub use toolbox::*;
+pub use image_to_buffer_copier::first_channel_u16_row_length;
+pub use pixel_reader::PixelRegion;
\ No newline at end of file
@@ -0,0 +1,158 @@
+use std::{mem::size_of, sync::Arc};
+
+use crate::runtime::{Buffer, BufferProperties, BufferReader, Image, Runtime};
+
+use crate::shader::Shader;
+use crate::utils::math::div_up;
+
+use super::{BindGroupDescriptor, BindGroupEntry, BindGroupManager, BindingResource};
+
+// the pixels of a rectangular region of an image, as read back from the GPU
+#[derive(Clone, Debug, PartialEq)]
+pub struct PixelRegion {
+    // the top-left pixel of the region, which may lie outside of the image
+    pub origin: (i32, i32),
+    pub size: (u32, u32),
+    // row-major rgba, in the color space of the image.
+    // parts of the region outside of the image repeat the pixels on the border.
+    pub pixels: Vec<[f32; 4]>,
+}
+
+impl PixelRegion {
+    // x and y are relative to the origin of the region
+    pub fn pixel(&self, x: u32, y: u32) -> [f32; 4] {
+        self.pixels[(y * self.size.0 + x) as usize]
+    }
+
+    pub fn center(&self) -> [f32; 4] {
+        self.pixel(self.size.0 / 2, self.size.1 / 2)
+    }
+
+    pub fn average(&self) -> [f32; 4] {
+        let mut sum = [0.0; 4];
+        for p in self.pixels.iter() {
+            for c in 0..4 {
+                sum[c] += p[c];
+            }
+        }
+        let count = self.pixels.len().max(1) as f32;
+        sum.map(|s| s / count)
+    }
+}
+
+// reads back the pixels of an image in a (small) region
+pub struct PixelReader {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_manager: BindGroupManager,
+    runtime: Arc<Runtime>,
+}
+impl PixelReader {
+    pub fn new(runtime: Arc<Runtime>) -> Self {
+        let shader_code = Shader::from_code(include_str!("./pixel_reader.wgsl")).full_code();
+
+        let (pipeline, bind_group_layout) =
+            runtime.create_compute_pipeline(shader_code.as_str(), Some("PixelReader"));
+        let bind_group_manager = BindGroupManager::new(runtime.clone(), bind_group_layout);
+
+        PixelReader {
+            runtime,
+            pipeline,
+            bind_group_manager,
+        }
+    }
+}
+impl PixelReader {
+    // origin and size are in pixels. the region is handed to `transform` once it's read back,
+    // so that callers interested in e.g. only the average don't need to keep all pixels.
+    pub fn read<T: 'static>(
+        &mut self,
+        input_img: &Image,
+        origin: (i32, i32),
+        size: (u32, u32),
+        transform: impl FnOnce(PixelRegion) -> T + 'static,
+    ) -> BufferReader<T> {
+        let size = (size.0.max(1), size.1.max(1));
+
+        let params_buffer = self.runtime.create_buffer_of_properties(BufferProperties {
+            size: size_of::<i32>() * 2 + size_of::<u32>() * 2,
+            host_readable: false,
+        });
+        self.runtime.queue.write_buffer(
+            &params_buffer.buffer,
+            0,
+            bytemuck::cast_slice(&[origin.0, origin.1]),
+        );
+        self.runtime.queue.write_buffer(
+            &params_buffer.buffer,
+            (size_of::<i32>() * 2) as u64,
+            bytemuck::cast_slice(&[size.0, size.1]),
+        );
+
+        let num_pixels = (size.0 * size.1) as usize;
+        let output_buffer: Arc<Buffer> =
+            Arc::new(self.runtime.create_buffer_of_properties(BufferProperties {
+                size: size_of::<f32>() * 4 * num_pixels,
+                host_readable: true,
+            }));
+
+        self.bind_group_manager.clear_cache();
+
+        let bind_group = self.bind_group_manager.get_or_create(BindGroupDescriptor {
+            entries: vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Texture(input_img),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Buffer(&params_buffer),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Buffer(&output_buffer),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .runtime
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                ..Default::default()
+            });
+            cpass.set_pipeline(&self.pipeline);
+            cpass.set_bind_group(0, bind_group, &[]);
+            let num_workgroups_x = div_up(size.0, 8);
+            let num_workgroups_y = div_up(size.1, 8);
+            cpass.dispatch_workgroups(num_workgroups_x, num_workgroups_y, 1);
+        }
+        self.runtime.queue.submit(Some(encoder.finish()));
+
+        BufferReader::new(
+            self.runtime.clone(),
+            output_buffer,
+            None,
+            Box::new(move |v| {
+                let pixels = v
+                    .chunks_exact(4)
+                    .take(num_pixels)
+                    .map(|p| {
+                        [
+                            f32::from_bits(p[0]),
+                            f32::from_bits(p[1]),
+                            f32::from_bits(p[2]),
+                            f32::from_bits(p[3]),
+                        ]
+                    })
+                    .collect();
+                transform(PixelRegion {
+                    origin,
+                    size,
+                    pixels,
+                })
+            }),
+        )
+    }
+}
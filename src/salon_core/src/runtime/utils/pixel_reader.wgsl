@@ -0,0 +1,36 @@
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+
+struct Params {
+    origin_x: i32,
+    origin_y: i32,
+    width: u32,
+    height: u32,
+};
+
+@group(0) @binding(1)
+var<uniform> params: Params;
+
+struct Buffer {
+    // row-major rgba of the pixels in the region
+    pixels: array<vec4<f32>>,
+};
+
+@group(0) @binding(2)
+var<storage, read_write> buffer: Buffer;
+
+@compute
+@workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if (global_id.x >= params.width || global_id.y >= params.height) {
+        return;
+    }
+    let input_size = vec2<i32>(textureDimensions(input));
+    // parts of the region outside of the image repeat the pixels on the border
+    let xy = clamp(
+        vec2(params.origin_x, params.origin_y) + vec2<i32>(global_id.xy),
+        vec2(0),
+        input_size - 1
+    );
+    buffer.pixels[global_id.y * params.width + global_id.x] = textureLoad(input, xy, 0);
+}
@@ -1,11 +1,13 @@
 use std::sync::{Arc, RwLock};
 
-use crate::runtime::{Buffer, ColorSpace, Image, ImageFormat, Runtime};
+use crate::runtime::{Buffer, BufferReader, ColorSpace, Image, ImageFormat, Runtime};
 
 use super::{
-    color_space_converter::ColorSpaceConverter, image_format_converter::ImageFormatConverter,
+    color_space_converter::ColorSpaceConverter, horizon_detector::HorizonDetector,
+    illuminant_estimator::IlluminantEstimator, image_format_converter::ImageFormatConverter,
     image_resizer::ImageResizer, image_to_buffer_copier::ImageToBufferCopier,
     mipmap_generator::MipmapGenerator,
+    pixel_reader::{PixelReader, PixelRegion},
 };
 
 pub struct Toolbox {
@@ -14,6 +16,9 @@ pub struct Toolbox {
     image_format_converter: RwLock<ImageFormatConverter>,
     image_resizer: RwLock<ImageResizer>,
     image_to_buffer_copier: RwLock<ImageToBufferCopier>,
+    horizon_detector: RwLock<HorizonDetector>,
+    pixel_reader: RwLock<PixelReader>,
+    illuminant_estimator: RwLock<IlluminantEstimator>,
 }
 
 impl Toolbox {
@@ -24,6 +29,9 @@ impl Toolbox {
             image_format_converter: RwLock::new(ImageFormatConverter::new(runtime.clone())),
             image_to_buffer_copier: RwLock::new(ImageToBufferCopier::new(runtime.clone())),
             image_resizer: RwLock::new(ImageResizer::new(runtime.clone())),
+            horizon_detector: RwLock::new(HorizonDetector::new(runtime.clone())),
+            pixel_reader: RwLock::new(PixelReader::new(runtime.clone())),
+            illuminant_estimator: RwLock::new(IlluminantEstimator::new(runtime.clone())),
         }
     }
 
@@ -68,4 +76,66 @@ impl Toolbox {
         let mut copier = self.image_to_buffer_copier.write().unwrap();
         copier.copy(input_img)
     }
+
+    pub fn copy_image_first_channel_u16_to_buffer(&self, input_img: &Image) -> Arc<Buffer> {
+        let mut copier = self.image_to_buffer_copier.write().unwrap();
+        copier.copy_first_channel_u16(input_img)
+    }
+
+    // the rotation (in degrees) that makes the dominant edges of the image horizontal/vertical
+    pub fn detect_leveling_rotation(&self, input_img: Arc<Image>) -> BufferReader<Option<f32>> {
+        // a downsized image is sufficient, and less noisy
+        let max_dimension = 1024.0;
+        let (w, h) = input_img.properties.dimensions;
+        let factor = max_dimension / w.max(h) as f32;
+        let mut img = input_img;
+        if factor < 1.0 {
+            self.generate_mipmap(&img);
+            img = self.resize_image(img, factor);
+        }
+        let mut detector = self.horizon_detector.write().unwrap();
+        detector.detect(&img)
+    }
+
+    // the pixels of a region of the image, with origin and size in pixels
+    pub fn read_pixels(
+        &self,
+        input_img: &Image,
+        origin: (i32, i32),
+        size: (u32, u32),
+    ) -> BufferReader<PixelRegion> {
+        let mut reader = self.pixel_reader.write().unwrap();
+        reader.read(input_img, origin, size, |region| region)
+    }
+
+    // the average color of the (2 * radius + 1)^2 pixels around a position relative to the image size
+    pub fn sample_pixels(
+        &self,
+        input_img: &Image,
+        position: (f32, f32),
+        radius: u32,
+    ) -> BufferReader<[f32; 4]> {
+        let (x, y) = input_img.pixel_at(position);
+        let r = radius as i32;
+        let size = 2 * radius + 1;
+        let mut reader = self.pixel_reader.write().unwrap();
+        reader.read(input_img, (x - r, y - r), (size, size), |region| {
+            region.average()
+        })
+    }
+
+    // the linear rgb color of the light in the image (normalized to a luminance of 1), for auto white balance
+    pub fn estimate_illuminant(&self, input_img: Arc<Image>) -> BufferReader<Option<[f32; 3]>> {
+        // as with leveling, a downsized image is sufficient
+        let max_dimension = 512.0;
+        let (w, h) = input_img.properties.dimensions;
+        let factor = max_dimension / w.max(h) as f32;
+        let mut img = input_img;
+        if factor < 1.0 {
+            self.generate_mipmap(&img);
+            img = self.resize_image(img, factor);
+        }
+        let mut estimator = self.illuminant_estimator.write().unwrap();
+        estimator.estimate(&img)
+    }
 }
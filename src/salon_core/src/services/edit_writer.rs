@@ -1,8 +1,16 @@
-use std::{path::PathBuf, thread::JoinHandle};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+};
 
 use sha256::TrySha256Digest;
 
-use crate::{editor::Edit, session::Session};
+use crate::{
+    editor::{Edit, PersistedEditHistory, Snapshot},
+    library::LibraryImageIdentifier,
+    session::Session,
+};
 
 pub struct EditWriterService {
     request_sender: std::sync::mpsc::Sender<Request>,
@@ -23,10 +31,55 @@ impl EditWriterService {
         }
     }
 
-    pub fn request_update(&self, edit: Edit, original_image_path: PathBuf) {
+    pub fn request_update(&self, edit: Edit, identifier: LibraryImageIdentifier) {
         let _ = self
             .request_sender
-            .send(Request::Write(edit, original_image_path));
+            .send(Request::Write(Box::new(edit), identifier));
+    }
+
+    pub fn request_history_update(
+        &self,
+        history: PersistedEditHistory,
+        identifier: LibraryImageIdentifier,
+    ) {
+        let _ = self
+            .request_sender
+            .send(Request::WriteHistory(history, identifier));
+    }
+
+    pub fn request_snapshots_update(
+        &self,
+        snapshots: Vec<Snapshot>,
+        identifier: LibraryImageIdentifier,
+    ) {
+        let _ = self
+            .request_sender
+            .send(Request::WriteSnapshots(snapshots, identifier));
+    }
+
+    // removes the persisted edit, history and snapshots of a virtual copy, after the writes requested before it.
+    // writes requested afterwards are dropped, virtual copy indices are never reused
+    pub fn request_delete_virtual_copy(&self, identifier: LibraryImageIdentifier) {
+        if identifier.is_virtual_copy() {
+            let _ = self
+                .request_sender
+                .send(Request::DeleteVirtualCopy(identifier));
+        }
+    }
+
+    // virtual copies keep their edits in a subdirectory of the original image's directory
+    pub fn get_edit_path_for_identifier(identifier: &LibraryImageIdentifier) -> Option<PathBuf> {
+        let edit_path = Self::get_edit_path_for_image_path(&identifier.get_path()?)?;
+        match identifier.virtual_copy_index() {
+            Some(copy_index) => {
+                let dir = edit_path.parent()?;
+                Some(
+                    dir.join(format!("virtual_copy_{}", copy_index))
+                        .join("edit.json"),
+                )
+            }
+            None => Some(edit_path),
+        }
     }
 
     pub fn get_edit_path_for_image_path(image_path: &PathBuf) -> Option<PathBuf> {
@@ -41,6 +94,30 @@ impl EditWriterService {
         }
         None
     }
+
+    pub fn get_history_path_for_edit_path(edit_path: &Path) -> PathBuf {
+        edit_path.with_file_name("history.json")
+    }
+
+    pub fn get_snapshots_path_for_edit_path(edit_path: &Path) -> PathBuf {
+        edit_path.with_file_name("snapshots.json")
+    }
+
+    // moves a file that can't be read out of the way, so that it isn't overwritten by the next write.
+    // e.g. "edit.json" becomes "edit.unreadable.json" (or "edit.unreadable.1.json", etc)
+    pub fn quarantine_file(path: &Path) {
+        let (Some(dir), Some(stem)) = (path.parent(), path.file_stem().and_then(|s| s.to_str()))
+        else {
+            return;
+        };
+        let mut quarantine_path = dir.join(format!("{}.unreadable.json", stem));
+        let mut index = 1;
+        while quarantine_path.exists() {
+            quarantine_path = dir.join(format!("{}.unreadable.{}.json", stem, index));
+            index += 1;
+        }
+        let _ = std::fs::rename(path, quarantine_path);
+    }
 }
 
 impl Drop for EditWriterService {
@@ -56,45 +133,187 @@ impl Drop for EditWriterService {
 
 struct Worker {
     request_receiver: std::sync::mpsc::Receiver<Request>,
+    deleted_virtual_copies: HashSet<LibraryImageIdentifier>,
 }
 
 impl Worker {
     fn new(request_receiver: std::sync::mpsc::Receiver<Request>) -> Self {
-        Self { request_receiver }
+        Self {
+            request_receiver,
+            deleted_virtual_copies: HashSet::new(),
+        }
     }
 
     fn run(&mut self) {
         loop {
             let req = self.request_receiver.recv();
-            if let Ok(req) = req {
-                match req {
+            let Ok(req) = req else {
+                break;
+            };
+            // requests that queued up in the meantime (e.g. while a slider was dragged) are handled together,
+            // where only the last write of each file is done
+            let mut requests = vec![req];
+            while let Ok(req) = self.request_receiver.try_recv() {
+                requests.push(req);
+            }
+            for i in 0..requests.len() {
+                if requests[i + 1..]
+                    .iter()
+                    .any(|later| later.supersedes(&requests[i]))
+                {
+                    continue;
+                }
+                if let Some(identifier) = requests[i].written_identifier() {
+                    if self.deleted_virtual_copies.contains(identifier) {
+                        continue;
+                    }
+                }
+                match &requests[i] {
                     Request::Stop => {
-                        break;
+                        return;
                     }
-                    Request::Write(edit, original_image_path) => {
-                        self.write(edit, original_image_path);
+                    Request::Write(edit, identifier) => {
+                        self.write(edit, identifier);
+                    }
+                    Request::WriteHistory(history, identifier) => {
+                        self.write_history(history, identifier);
+                    }
+                    Request::WriteSnapshots(snapshots, identifier) => {
+                        self.write_snapshots(snapshots, identifier);
+                    }
+                    Request::DeleteVirtualCopy(identifier) => {
+                        self.delete_virtual_copy(identifier);
                     }
                 }
-            } else {
-                break;
             }
         }
     }
 
-    fn write(&mut self, edit: Edit, original_image_path: PathBuf) {
-        if let Ok(edit_json_str) = serde_json::to_string_pretty(&edit) {
-            if let Some(edit_path) =
-                EditWriterService::get_edit_path_for_image_path(&original_image_path)
-            {
+    fn write(&mut self, edit: &Edit, identifier: &LibraryImageIdentifier) {
+        if let Ok(edit_json_str) = edit.to_persisted_json_string() {
+            if let Some(edit_path) = EditWriterService::get_edit_path_for_identifier(identifier) {
                 if let Ok(_) = std::fs::create_dir_all(edit_path.parent().unwrap()) {
                     let _ = std::fs::write(&edit_path, edit_json_str);
                 }
             }
         }
     }
+
+    fn write_history(
+        &mut self,
+        history: &PersistedEditHistory,
+        identifier: &LibraryImageIdentifier,
+    ) {
+        if let Ok(history_json_str) = history.to_json_string() {
+            if let Some(edit_path) = EditWriterService::get_edit_path_for_identifier(identifier) {
+                if std::fs::create_dir_all(edit_path.parent().unwrap()).is_ok() {
+                    let history_path =
+                        EditWriterService::get_history_path_for_edit_path(&edit_path);
+                    let _ = std::fs::write(history_path, history_json_str);
+                }
+            }
+        }
+    }
+
+    fn write_snapshots(&mut self, snapshots: &[Snapshot], identifier: &LibraryImageIdentifier) {
+        if let Ok(snapshots_json_str) = Snapshot::list_to_json_string(snapshots) {
+            if let Some(edit_path) = EditWriterService::get_edit_path_for_identifier(identifier) {
+                if std::fs::create_dir_all(edit_path.parent().unwrap()).is_ok() {
+                    let snapshots_path =
+                        EditWriterService::get_snapshots_path_for_edit_path(&edit_path);
+                    let _ = std::fs::write(snapshots_path, snapshots_json_str);
+                }
+            }
+        }
+    }
+
+    fn delete_virtual_copy(&mut self, identifier: &LibraryImageIdentifier) {
+        if let Some(edit_path) = EditWriterService::get_edit_path_for_identifier(identifier) {
+            let _ = std::fs::remove_dir_all(edit_path.parent().unwrap());
+        }
+        self.deleted_virtual_copies.insert(identifier.clone());
+    }
 }
 
 enum Request {
     Stop,
-    Write(Edit, PathBuf),
+    Write(Box<Edit>, LibraryImageIdentifier),
+    WriteHistory(PersistedEditHistory, LibraryImageIdentifier),
+    WriteSnapshots(Vec<Snapshot>, LibraryImageIdentifier),
+    DeleteVirtualCopy(LibraryImageIdentifier),
+}
+
+impl Request {
+    // whether `earlier` writes the same file as this, so that it doesn't need to be done
+    fn supersedes(&self, earlier: &Request) -> bool {
+        match (self, earlier) {
+            (Request::Write(_, a), Request::Write(_, b)) => a == b,
+            (Request::WriteHistory(_, a), Request::WriteHistory(_, b)) => a == b,
+            (Request::WriteSnapshots(_, a), Request::WriteSnapshots(_, b)) => a == b,
+            // the files would be deleted right after being written
+            (Request::DeleteVirtualCopy(a), earlier) => earlier.written_identifier() == Some(a),
+            _ => false,
+        }
+    }
+
+    fn written_identifier(&self) -> Option<&LibraryImageIdentifier> {
+        match self {
+            Request::Write(_, identifier)
+            | Request::WriteHistory(_, identifier)
+            | Request::WriteSnapshots(_, identifier) => Some(identifier),
+            Request::Stop | Request::DeleteVirtualCopy(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarantined_edits_are_not_overwritten() {
+        let dir = std::env::temp_dir().join(format!("salon_quarantine_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let edit_path = dir.join("edit.json");
+
+        for content in ["first", "second", "third"] {
+            std::fs::write(&edit_path, content).unwrap();
+            EditWriterService::quarantine_file(&edit_path);
+            assert!(!edit_path.exists());
+        }
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("edit.unreadable.json"), "first");
+        assert_eq!(read("edit.unreadable.1.json"), "second");
+        assert_eq!(read("edit.unreadable.2.json"), "third");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn deleting_a_virtual_copy_supersedes_its_earlier_writes() {
+        let copy = LibraryImageIdentifier::VirtualCopy(PathBuf::from("image.jpg"), 1);
+        let other_copy = LibraryImageIdentifier::VirtualCopy(PathBuf::from("image.jpg"), 2);
+        let delete = Request::DeleteVirtualCopy(copy.clone());
+        assert!(delete.supersedes(&Request::WriteSnapshots(Vec::new(), copy.clone())));
+        assert!(!delete.supersedes(&Request::WriteSnapshots(Vec::new(), other_copy)));
+        assert!(!Request::WriteSnapshots(Vec::new(), copy).supersedes(&delete));
+    }
+
+    #[test]
+    fn quarantined_files_keep_their_name() {
+        let dir =
+            std::env::temp_dir().join(format!("salon_quarantine_name_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let snapshots_path = dir.join("snapshots.json");
+
+        std::fs::write(&snapshots_path, "snapshots").unwrap();
+        EditWriterService::quarantine_file(&snapshots_path);
+        assert!(!snapshots_path.exists());
+        let quarantined = std::fs::read_to_string(dir.join("snapshots.unreadable.json")).unwrap();
+        assert_eq!(quarantined, "snapshots");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -12,15 +12,15 @@ use std::{
 use sha256::TrySha256Digest;
 
 use crate::{
-    library::is_supported_image_file,
+    library::{is_supported_image_file, LibraryImageIdentifier},
     runtime::{ColorSpace, Image, ImageFormat, ImageReaderJpeg, Runtime, Toolbox},
     session::Session,
 };
 
 /**
  * This service can either
- * 1.   Generate a thumbnail given an `Arc<Image>` and its original path, and write the generated thumbnail
- *      (whose path is comptuted based on the path of the original image)
+ * 1.   Generate a thumbnail given an `Arc<Image>` and its library identifier, and write the generated thumbnail
+ *      (whose path is comptuted based on the path of the original image, and the virtual copy index if any)
  * OR
  * 2.   Generate a thumbnail given the path of the original image alone.
  *
@@ -117,21 +117,23 @@ impl ThumbnailGeneratorService {
     pub fn generate_and_maybe_save_thumbnail_for_image(
         &self,
         image: Arc<Image>,
-        image_original_path: Option<PathBuf>,
+        identifier: Option<&LibraryImageIdentifier>,
     ) -> Arc<Image> {
         let thumbnail_image = Self::compute_thumbnail(&self.toolbox, image);
         self.toolbox.generate_mipmap(&thumbnail_image);
 
         #[cfg(not(target_arch = "wasm32"))]
-        if let Some(image_original_path) = image_original_path {
+        if let Some(identifier) = identifier {
             if let Some(thumbnail_path) =
-                ThumbnailGeneratorService::get_thumbnail_path_for_image_path(&image_original_path)
+                ThumbnailGeneratorService::get_thumbnail_path_for_identifier(identifier)
             {
                 let _ = self
                     .write_request_sender
                     .send(WriteRequest::new(thumbnail_image.clone(), thumbnail_path));
             }
         }
+        #[cfg(target_arch = "wasm32")]
+        let _ = identifier;
         thumbnail_image
     }
 
@@ -163,6 +165,24 @@ impl ThumbnailGeneratorService {
         None
     }
 
+    // virtual copies keep their thumbnails in a subdirectory of the original image's directory
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn get_thumbnail_path_for_identifier(
+        identifier: &LibraryImageIdentifier,
+    ) -> Option<PathBuf> {
+        let thumbnail_path = Self::get_thumbnail_path_for_image_path(&identifier.get_path()?)?;
+        match identifier.virtual_copy_index() {
+            Some(copy_index) => {
+                let dir = thumbnail_path.parent()?;
+                Some(
+                    dir.join(format!("virtual_copy_{}", copy_index))
+                        .join("thumbnail.jpg"),
+                )
+            }
+            None => Some(thumbnail_path),
+        }
+    }
+
     const THUMBNAIL_MIN_DIMENSION_SIZE: f32 = 400.0;
 
     const THUMBNAIL_JPEG_QUALITY: u8 = 80;
@@ -2,8 +2,9 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::editor::{Editor};
+use crate::editor::{AppliedLook, Edit, EditSections, Editor};
 use crate::library::{Library, LibraryImageIdentifier};
+use crate::presets::PresetLibrary;
 use crate::runtime::{Runtime, Toolbox};
 use crate::services::services::Services;
 
@@ -11,6 +12,7 @@ use crate::services::services::Services;
 pub struct Session {
     pub library: Library,
     pub editor: Editor,
+    pub presets: PresetLibrary,
     pub runtime: Arc<Runtime>,
     pub toolbox: Arc<Toolbox>,
     pub services: Arc<Services>,
@@ -23,6 +25,7 @@ impl Session {
         let mut session = Session {
             library: Library::new(runtime.clone(), toolbox.clone(), services.clone()),
             editor: Editor::new(runtime.clone(), toolbox.clone(), services.clone()),
+            presets: PresetLibrary::new(),
             toolbox,
             runtime,
             services,
@@ -61,6 +64,53 @@ impl Session {
         }
     }
 
+    // writes the selected sections of `source_edit` into the edit history (and the persisted edit) of each target,
+    // and updates their thumbnails. the current image stays as it is.
+    pub fn sync_edit_sections(
+        &mut self,
+        source_edit: &Edit,
+        source_image_aspect_ratio: f32,
+        sections: &EditSections,
+        amount: f32,
+        targets: &[LibraryImageIdentifier],
+    ) {
+        for identifier in targets.iter() {
+            let Some(image) = self.library.get_image_from_identifier(identifier) else {
+                continue;
+            };
+            let target_image_aspect_ratio = image.aspect_ratio();
+            let original_edit = self.editor.get_edit_of_image(identifier);
+            let mut edit = original_edit.clone();
+            sections.apply(
+                source_edit,
+                source_image_aspect_ratio,
+                &mut edit,
+                target_image_aspect_ratio,
+            );
+            let edit = AppliedLook::apply(&original_edit, &edit, amount);
+            if let Some(editted_image) = self.editor.commit_edit_to_image(identifier, image, edit) {
+                self.library
+                    .update_thumbnail_for_editted_image(identifier, editted_image);
+            }
+        }
+    }
+
+    // the copy starts off with the current edit of the original, and is added into the same albums
+    pub fn create_virtual_copy(
+        &mut self,
+        original: &LibraryImageIdentifier,
+    ) -> Option<LibraryImageIdentifier> {
+        let virtual_copy = self.library.create_virtual_copy(original)?;
+        self.editor
+            .init_virtual_copy(original, virtual_copy.clone());
+        Some(virtual_copy)
+    }
+
+    pub fn delete_virtual_copy(&mut self, virtual_copy: &LibraryImageIdentifier) {
+        self.editor.forget_image(virtual_copy);
+        self.library.delete_virtual_copy(virtual_copy);
+    }
+
     pub fn get_persistent_storage_dir() -> Option<PathBuf> {
         if let Some(proj_dirs) = directories::ProjectDirs::from("com", "LightSalon", "LightSalon") {
             let path = proj_dirs.data_local_dir().to_path_buf();
@@ -73,10 +123,12 @@ impl Session {
     }
 
     pub fn on_exit(&mut self) {
+        self.editor.on_exit();
         self.library.save_persistent_state();
     }
 
     fn on_start(&mut self) {
         self.library.load_persistent_state();
+        self.presets.load_persistent_state();
     }
 }
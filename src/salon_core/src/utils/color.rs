@@ -0,0 +1,111 @@
+// the same conversions as in shader/color_spaces.wgsl, for values read back from the GPU
+
+pub fn linear_to_srgb_channel(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn srgb_to_linear_channel(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// linearized sRGB to CIE XYZ 1931
+pub fn rgb_to_xyz(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    [
+        0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+        0.2126729 * r + 0.7151522 * g + 0.0721750 * b,
+        0.0193339 * r + 0.119192 * g + 0.9503041 * b,
+    ]
+}
+
+// the CIE xy chromaticity of a XYZ color, or None for black
+pub fn xyz_to_xy(xyz: [f32; 3]) -> Option<(f32, f32)> {
+    let sum = xyz[0] + xyz[1] + xyz[2];
+    if sum <= 0.0 {
+        return None;
+    }
+    Some((xyz[0] / sum, xyz[1] / sum))
+}
+
+// CIE 1960 UCS
+pub fn xy_to_uv((x, y): (f32, f32)) -> (f32, f32) {
+    let denominator = -2.0 * x + 12.0 * y + 3.0;
+    (4.0 * x / denominator, 6.0 * y / denominator)
+}
+
+// the uv of the Planckian locus at a color temperature (in Kelvin)
+pub fn planckian_uv_krystek(t: f32) -> (f32, f32) {
+    // the coefficients have more precision than f32
+    let t = t as f64;
+    let u = (0.860117757 + 1.54118254e-4 * t + 1.28641212e-7 * t * t)
+        / (1.0 + 8.42420235e-4 * t + 7.08145163e-7 * t * t);
+    let v = (0.317398726 + 4.22806245e-5 * t + 4.20481691e-8 * t * t)
+        / (1.0 - 2.89741816e-5 * t + 1.61456053e-7 * t * t);
+    (u as f32, v as f32)
+}
+
+// CIE L*, from the relative luminance Y (with Yn = 1.0)
+pub fn y_to_l(y: f32) -> f32 {
+    if y <= 0.008856452 {
+        y * 903.2963
+    } else {
+        116.0 * y.cbrt() - 16.0
+    }
+}
+
+// linearized sRGB to LCh (the cylindrical form of CIELUV), with the hue in radians
+pub fn rgb_to_lch(rgb: [f32; 3]) -> [f32; 3] {
+    const REF_U: f32 = 0.19783;
+    const REF_V: f32 = 0.46832;
+
+    let [x, y, z] = rgb_to_xyz(rgb);
+    let l = y_to_l(y);
+    let denominator = x + 15.0 * y + 3.0 * z;
+    if l == 0.0 || denominator == 0.0 {
+        return [0.0, 0.0, 0.0];
+    }
+    let u = 13.0 * l * (4.0 * x / denominator - REF_U);
+    let v = 13.0 * l * (9.0 * y / denominator - REF_V);
+
+    let c = (u * u + v * v).sqrt();
+    let mut h = v.atan2(u);
+    if h < 0.0 {
+        h += 2.0 * std::f32::consts::PI;
+    }
+    if c < 0.1 {
+        h = 0.0;
+    }
+    [l, c, h]
+}
+
+// rgb to HSL, with all components in [0, 1]
+pub fn rgb_to_hsl(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let lightness = (max + min) * 0.5;
+    if chroma <= 0.0 {
+        return [0.0, 0.0, lightness];
+    }
+    let mut hue = if max == r {
+        (g - b) / chroma
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    } / 6.0;
+    if hue < 0.0 {
+        hue += 1.0;
+    }
+    let saturation = chroma / (1.0 - (2.0 * lightness - 1.0).abs()).max(0.001);
+    [hue, saturation, lightness]
+}
@@ -1,4 +1,7 @@
-use crate::{editor::Edit, ir::MaskPrimitive};
+use crate::{
+    editor::Edit,
+    ir::{Mask, MaskPrimitive},
+};
 
 use super::{
     mat::Mat2x2,
@@ -26,6 +29,21 @@ pub fn clamp(x: f32, min: f32, max: f32) -> f32 {
     x.max(min).min(max)
 }
 
+// the bits of the half-precision float closest to x, for non-negative x up to the largest half (65504).
+// e.g. for uploading data to Rgba16Float textures.
+pub fn f32_to_f16_bits(x: f32) -> u16 {
+    let x = x.clamp(0.0, 65504.0);
+    // below the smallest normal half, which is 2^-14
+    if x < 1.0 / 16384.0 {
+        return (x * 16777216.0).round() as u16;
+    }
+    let bits = x.to_bits();
+    let exponent = ((bits >> 23) & 0xff) + 15 - 127;
+    let mantissa = bits & 0x7fffff;
+    // rounding the mantissa can carry over into the exponent, which is still the correct result
+    ((exponent << 10) + ((mantissa + 0x1000) >> 13)) as u16
+}
+
 pub fn get_rotation_mat(rotation_radians: f32) -> Mat2x2<f32> {
     Mat2x2::from_rows(
         vec2((rotation_radians.cos(), -rotation_radians.sin())),
@@ -419,6 +437,37 @@ pub fn get_max_crop_rect_with_aspect_ratio(
     new_crop_rect
 }
 
+// maps an angle to [-180, 180], which is the range of the rotation slider
+pub fn wrap_rotation_degrees(degrees: f32) -> f32 {
+    let wrapped = (degrees + 180.0).rem_euclid(360.0) - 180.0;
+    if wrapped == -180.0 && degrees > 0.0 {
+        180.0
+    } else {
+        wrapped
+    }
+}
+
+// maps an angle to (-45, 45], i.e. its deviation from the nearest horizontal/vertical direction
+pub fn get_deviation_from_right_angle(degrees: f32) -> f32 {
+    let deviation = degrees.rem_euclid(90.0);
+    if deviation > 45.0 {
+        deviation - 90.0
+    } else {
+        deviation
+    }
+}
+
+// the rotation that would make the line from start to end perfectly horizontal (or vertical, whichever is closer)
+// positions are in a y-down coordinate system, as the image appears on screen.
+pub fn get_rotation_degrees_to_level_line(start: Vec2<f32>, end: Vec2<f32>) -> Option<f32> {
+    let dir = end - start;
+    if dir.length() == 0.0 {
+        return None;
+    }
+    let line_degrees = dir.y.atan2(dir.x).to_degrees();
+    Some(-get_deviation_from_right_angle(line_degrees))
+}
+
 pub fn handle_new_crop_rect(
     original_image_aspect_ratio: f32,
     transient_edit: &mut Edit,
@@ -469,10 +518,36 @@ pub fn handle_new_crop_rect(
                         transform_xy(&mut m.begin_x, &mut m.begin_y);
                         transform_xy(&mut m.saturate_x, &mut m.saturate_y);
                     }
-                    MaskPrimitive::Global(_) => {}
+                    MaskPrimitive::Brush(ref mut m) => {
+                        for stroke in m.strokes.iter_mut() {
+                            for (x, y) in stroke.points.iter_mut() {
+                                transform_xy(x, y);
+                            }
+                            stroke.size *= old_crop_rect.size.y / new_crop_rect.size.y;
+                        }
+                    }
+                    MaskPrimitive::Polygon(ref mut m) => {
+                        for (x, y) in m.vertices.iter_mut() {
+                            transform_xy(x, y);
+                        }
+                        m.feather *= old_crop_rect.size.y / new_crop_rect.size.y;
+                    }
+                    MaskPrimitive::Global(_)
+                    | MaskPrimitive::Image(_)
+                    | MaskPrimitive::LuminanceRange(_)
+                    | MaskPrimitive::ColorRange(_) => {}
                 }
             }
         }
+        for spot in transient_edit.retouch_spots.iter_mut() {
+            transform_xy(&mut spot.source_x, &mut spot.source_y);
+            transform_xy(&mut spot.destination_x, &mut spot.destination_y);
+            spot.radius *= old_crop_rect.size.y / new_crop_rect.size.y;
+        }
+        for correction in transient_edit.red_eye_corrections.iter_mut() {
+            transform_xy(&mut correction.center_x, &mut correction.center_y);
+            correction.pupil_radius *= old_crop_rect.size.y / new_crop_rect.size.y;
+        }
         if new_crop_rect != Rectangle::regular() {
             transient_edit.crop_rect = Some(new_crop_rect);
         } else {
@@ -546,10 +621,36 @@ pub fn handle_new_rotation(
                         transform_xy(&mut m.begin_x, &mut m.begin_y);
                         transform_xy(&mut m.saturate_x, &mut m.saturate_y);
                     }
-                    MaskPrimitive::Global(_) => {}
+                    MaskPrimitive::Brush(ref mut m) => {
+                        for stroke in m.strokes.iter_mut() {
+                            for (x, y) in stroke.points.iter_mut() {
+                                transform_xy(x, y);
+                            }
+                            stroke.size *= old_crop_rect.size.y / new_crop_rect.size.y;
+                        }
+                    }
+                    MaskPrimitive::Polygon(ref mut m) => {
+                        for (x, y) in m.vertices.iter_mut() {
+                            transform_xy(x, y);
+                        }
+                        m.feather *= old_crop_rect.size.y / new_crop_rect.size.y;
+                    }
+                    MaskPrimitive::Global(_)
+                    | MaskPrimitive::Image(_)
+                    | MaskPrimitive::LuminanceRange(_)
+                    | MaskPrimitive::ColorRange(_) => {}
                 }
             }
         }
+        for spot in transient_edit.retouch_spots.iter_mut() {
+            transform_xy(&mut spot.source_x, &mut spot.source_y);
+            transform_xy(&mut spot.destination_x, &mut spot.destination_y);
+            spot.radius *= old_crop_rect.size.y / new_crop_rect.size.y;
+        }
+        for correction in transient_edit.red_eye_corrections.iter_mut() {
+            transform_xy(&mut correction.center_x, &mut correction.center_y);
+            correction.pupil_radius *= old_crop_rect.size.y / new_crop_rect.size.y;
+        }
         if new_rotation_degrees != 0.0 {
             transient_edit.rotation_degrees = Some(new_rotation_degrees);
         } else {
@@ -557,3 +658,237 @@ pub fn handle_new_rotation(
         }
     }
 }
+
+// the aspect ratio of the image after geometry edits, which is what mask positions are relative to
+pub fn get_edited_aspect_ratio(original_image_aspect_ratio: f32, edit: &Edit) -> f32 {
+    let mut aspect_ratio = original_image_aspect_ratio;
+    if let Some(crop_rect) = edit.crop_rect {
+        aspect_ratio *= crop_rect.size.x / crop_rect.size.y
+    }
+    aspect_ratio
+}
+
+// adapts a mask copied from another image to the geometry of the target edit.
+// positions are relative to the image after geometry edits, so each shape stays centered on the same region
+// of the new image, but it's scaled horizontally around its center so that it keeps its proportions
+// (and a linear gradient keeps its angle). image masks are left as they are, see `remove_image_mask_terms`.
+pub fn remap_mask_to_new_image(
+    mask: &mut Mask,
+    source_aspect_ratio: f32,
+    target_original_image_aspect_ratio: f32,
+    target_edit: &Edit,
+) {
+    let target_aspect_ratio =
+        get_edited_aspect_ratio(target_original_image_aspect_ratio, target_edit);
+    let x_scale = source_aspect_ratio / target_aspect_ratio;
+    let scale_x_around = |center_x: f32, x: &mut f32| {
+        *x = center_x + (*x - center_x) * x_scale;
+    };
+    for term in mask.terms.iter_mut() {
+        match term.primitive {
+            MaskPrimitive::RadialGradient(ref mut m) => {
+                m.radius_x *= x_scale;
+            }
+            MaskPrimitive::LinearGradient(ref mut m) => {
+                let center_x = (m.begin_x + m.saturate_x) * 0.5;
+                scale_x_around(center_x, &mut m.begin_x);
+                scale_x_around(center_x, &mut m.saturate_x);
+            }
+            // brush sizes and polygon feathers are relative to the image height, and need no change
+            MaskPrimitive::Brush(ref mut m) => {
+                let points = m.strokes.iter().flat_map(|stroke| stroke.points.iter());
+                if let Some(center_x) = get_center_x(points) {
+                    for stroke in m.strokes.iter_mut() {
+                        for (x, _) in stroke.points.iter_mut() {
+                            scale_x_around(center_x, x);
+                        }
+                    }
+                }
+            }
+            MaskPrimitive::Polygon(ref mut m) => {
+                if let Some(center_x) = get_center_x(m.vertices.iter()) {
+                    for (x, _) in m.vertices.iter_mut() {
+                        scale_x_around(center_x, x);
+                    }
+                }
+            }
+            MaskPrimitive::Image(_)
+            | MaskPrimitive::Global(_)
+            | MaskPrimitive::LuminanceRange(_)
+            | MaskPrimitive::ColorRange(_) => {}
+        }
+    }
+}
+
+// the horizontal center of the bounding box of the points
+fn get_center_x<'a>(points: impl Iterator<Item = &'a (f32, f32)>) -> Option<f32> {
+    let mut min_max: Option<(f32, f32)> = None;
+    for (x, _) in points {
+        min_max = match min_max {
+            Some((min, max)) => Some((min.min(*x), max.max(*x))),
+            None => Some((*x, *x)),
+        };
+    }
+    min_max.map(|(min, max)| (min + max) * 0.5)
+}
+
+// image masks are aligned to the image they were imported for, so they can't be used for another image.
+// returns whether anything is left of the mask.
+pub fn remove_image_mask_terms(mask: &mut Mask) -> bool {
+    mask.terms
+        .retain(|term| !matches!(term.primitive, MaskPrimitive::Image(_)));
+    !mask.terms.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn f32_to_f16_bits_of_exact_values() {
+        assert_eq!(f32_to_f16_bits(0.0), 0x0000);
+        assert_eq!(f32_to_f16_bits(1.0), 0x3c00);
+        assert_eq!(f32_to_f16_bits(0.5), 0x3800);
+        assert_eq!(f32_to_f16_bits(65504.0), 0x7bff);
+        // the smallest normal and subnormal halfs
+        assert_eq!(f32_to_f16_bits(1.0 / 16384.0), 0x0400);
+        assert_eq!(f32_to_f16_bits(1.0 / 16777216.0), 0x0001);
+    }
+
+    #[test]
+    fn f32_to_f16_bits_rounds_to_nearest() {
+        // halfs between 1 and 2 are 1/1024 apart
+        assert_eq!(f32_to_f16_bits(1.0 + 0.4 / 1024.0), 0x3c00);
+        assert_eq!(f32_to_f16_bits(1.0 + 0.6 / 1024.0), 0x3c01);
+        assert_eq!(f32_to_f16_bits(2.0 - 0.1 / 1024.0), 0x4000);
+        // 16-bit mask values keep more precision than 8 bits
+        assert_ne!(
+            f32_to_f16_bits(65000.0 / 65535.0),
+            f32_to_f16_bits(65100.0 / 65535.0)
+        );
+    }
+
+    // the angle of the line between two points, after being stretched to an image of the aspect ratio
+    fn angle_in_image(aspect_ratio: f32, a: (f32, f32), b: (f32, f32)) -> f32 {
+        ((b.1 - a.1) / ((b.0 - a.0) * aspect_ratio)).atan()
+    }
+
+    #[test]
+    fn remapped_shapes_keep_their_proportions() {
+        use crate::ir::{LinearGradientMask, MaskTerm, MaskTermMode, PolygonMask};
+
+        let linear = LinearGradientMask {
+            begin_x: 0.4,
+            begin_y: 0.4,
+            saturate_x: 0.6,
+            saturate_y: 0.6,
+        };
+        let polygon = PolygonMask {
+            vertices: vec![(0.2, 0.2), (0.4, 0.2), (0.4, 0.6)],
+            feather: 0.01,
+        };
+        let term = |primitive| MaskTerm {
+            primitive,
+            inverted: false,
+            mode: MaskTermMode::Add,
+        };
+        let mut mask = Mask {
+            terms: vec![
+                term(MaskPrimitive::LinearGradient(linear.clone())),
+                term(MaskPrimitive::Polygon(polygon.clone())),
+            ],
+            refine_edges: 0.0,
+        };
+        remap_mask_to_new_image(&mut mask, 1.5, 1.0, &Edit::trivial());
+
+        let MaskPrimitive::LinearGradient(ref remapped) = mask.terms[0].primitive else {
+            panic!("expecting a linear gradient");
+        };
+        assert_close(remapped.begin_x + remapped.saturate_x, 1.0);
+        assert_close(
+            angle_in_image(
+                1.0,
+                (remapped.begin_x, remapped.begin_y),
+                (remapped.saturate_x, remapped.saturate_y),
+            ),
+            angle_in_image(
+                1.5,
+                (linear.begin_x, linear.begin_y),
+                (linear.saturate_x, linear.saturate_y),
+            ),
+        );
+
+        let MaskPrimitive::Polygon(ref remapped) = mask.terms[1].primitive else {
+            panic!("expecting a polygon");
+        };
+        assert_close(remapped.vertices[0].0 + remapped.vertices[1].0, 0.6);
+        for i in 0..3 {
+            let j = (i + 1) % 3;
+            assert_close(
+                angle_in_image(1.0, remapped.vertices[i], remapped.vertices[j]),
+                angle_in_image(1.5, polygon.vertices[i], polygon.vertices[j]),
+            );
+        }
+    }
+
+    #[test]
+    fn wrap_rotation_degrees_keeps_slider_range() {
+        assert_close(wrap_rotation_degrees(0.0), 0.0);
+        assert_close(wrap_rotation_degrees(179.0), 179.0);
+        assert_close(wrap_rotation_degrees(190.0), -170.0);
+        assert_close(wrap_rotation_degrees(-190.0), 170.0);
+        // both ends of the range are kept as they are
+        assert_close(wrap_rotation_degrees(180.0), 180.0);
+        assert_close(wrap_rotation_degrees(-180.0), -180.0);
+        assert_close(wrap_rotation_degrees(540.0), 180.0);
+        assert_close(wrap_rotation_degrees(-360.0), 0.0);
+    }
+
+    #[test]
+    fn deviation_from_right_angle_near_45_and_90() {
+        assert_close(get_deviation_from_right_angle(44.0), 44.0);
+        assert_close(get_deviation_from_right_angle(45.0), 45.0);
+        assert_close(get_deviation_from_right_angle(46.0), -44.0);
+        assert_close(get_deviation_from_right_angle(-44.0), -44.0);
+        assert_close(get_deviation_from_right_angle(-46.0), 44.0);
+        assert_close(get_deviation_from_right_angle(89.0), -1.0);
+        assert_close(get_deviation_from_right_angle(90.0), 0.0);
+        assert_close(get_deviation_from_right_angle(91.0), 1.0);
+        assert_close(get_deviation_from_right_angle(-89.0), 1.0);
+        assert_close(get_deviation_from_right_angle(-91.0), -1.0);
+        assert_close(get_deviation_from_right_angle(179.0), -1.0);
+        assert_close(get_deviation_from_right_angle(-179.0), 1.0);
+    }
+
+    #[test]
+    fn rotation_to_level_line() {
+        let level =
+            |x: f32, y: f32| get_rotation_degrees_to_level_line(vec2((0.0, 0.0)), vec2((x, y)));
+        let degrees = |x: f32, y: f32| level(x, y).unwrap();
+
+        assert_eq!(level(0.0, 0.0), None);
+        assert_close(degrees(1.0, 0.0), 0.0);
+        assert_close(degrees(0.0, 1.0), 0.0);
+
+        // y is down, so a line going down to the right needs a negative rotation
+        let small = 1f32.atan2(10.0).to_degrees();
+        assert_close(degrees(10.0, 1.0), -small);
+        assert_close(degrees(10.0, -1.0), small);
+        // the direction of the line doesn't matter
+        assert_close(degrees(-10.0, -1.0), -small);
+        assert_close(degrees(-10.0, 1.0), small);
+
+        // nearly vertical lines are leveled to vertical
+        assert_close(degrees(1.0, 10.0), small);
+        assert_close(degrees(-1.0, 10.0), -small);
+
+        // on either side of 45 degrees, the closer of horizontal and vertical is chosen
+        let below = 9.9f32.atan2(10.0).to_degrees();
+        assert_close(degrees(10.0, 9.9), -below);
+        assert_close(degrees(9.9, 10.0), below);
+    }
+}
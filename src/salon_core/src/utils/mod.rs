@@ -4,3 +4,4 @@ pub mod spline;
 pub mod vec;
 pub mod mat;
 pub mod rectangle;
+pub mod color;
@@ -9,7 +9,7 @@ impl Version {
     pub fn current_build() -> Self {
         Version {
             major: 0,
-            minor: 1,
+            minor: 3,
             patch: 0,
         }
     }
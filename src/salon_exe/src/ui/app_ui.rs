@@ -5,7 +5,8 @@ use salon_core::{library::LibraryImageIdentifier, session::Session};
 
 use super::{
     bottom_bar, editor, export_panel::export_panel, keyboard_response, library_albums_browser,
-    library_images_browser, library_side_panel, main_image, menu_bar, AppPage, AppUiState,
+    library_images_browser, library_side_panel, main_image, menu_bar, paste_settings_dialog, AppPage,
+    AppUiState,
 };
 
 pub fn app_ui(ctx: &egui::Context, session: &mut Session, ui_state: &mut AppUiState) {
@@ -67,6 +68,7 @@ pub fn app_ui(ctx: &egui::Context, session: &mut Session, ui_state: &mut AppUiSt
             });
         }
     }
+    paste_settings_dialog(ctx, session, ui_state);
     keyboard_response(ctx, session, ui_state);
 }
 
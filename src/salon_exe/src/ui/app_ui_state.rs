@@ -4,10 +4,15 @@ use std::sync::Arc;
 use std::{fmt};
 
 use eframe::egui;
-use salon_core::library::{LibraryImageMetaData};
-use salon_core::runtime::{Image, Runtime, Toolbox};
-
-use super::file_dialogues::ImageImportDialog;
+use salon_core::editor::{Edit, EditSections, MaskedEdit};
+use salon_core::engine::common::ImageHistogram;
+use salon_core::ir::{MaskTermMode, RetouchMode};
+use salon_core::library::{LibraryImageIdentifier, LibraryImageMetaData};
+use salon_core::presets::PresetLibrary;
+use salon_core::runtime::{BufferReader, Image, PixelRegion, Runtime, Toolbox};
+use salon_core::utils::uuid::Uuid;
+
+use super::file_dialogues::{ImageImportDialog, MaskImageImportDialog, PresetImportDialog};
 use super::utils::AnimatedValue;
 
 pub struct AppUiState {
@@ -20,6 +25,13 @@ pub struct AppUiState {
 
     pub show_grid: bool,
     pub show_comparison: bool,
+    pub show_loupe: bool,
+    pub loupe_state: LoupeState,
+    pub clipping_warning: ClippingWarning,
+    pub histogram_display: HistogramDisplay,
+    pub scope_texture: Option<egui::TextureHandle>,
+    // the uuid of the `BufferReader` whose value is in the scope texture
+    pub scope_texture_reader_uuid: Option<Uuid>,
 
     pub editor_panel: EditorPanel,
 
@@ -29,10 +41,34 @@ pub struct AppUiState {
     pub color_mixer_color_index: usize,
 
     pub crop_drag_state: CropDragState,
+    pub straighten_tool_enabled: bool,
+    pub pending_leveling_rotation: Option<BufferReader<Option<f32>>>,
+
+    pub pending_auto_tone_histogram: Option<BufferReader<ImageHistogram>>,
+    pub pending_auto_white_balance_illuminant: Option<BufferReader<Option<[f32; 3]>>>,
+    pub white_balance_picker_enabled: bool,
+    pub pending_white_balance_sample: Option<BufferReader<[f32; 4]>>,
 
     pub selected_mask_index: usize,
     pub selected_mask_term_index: Option<usize>,
     pub mask_edit_state: MaskEditState,
+    pub import_mask_image_dialog: MaskImageImportDialog,
+    pub copied_masked_edit: Option<CopiedMaskedEdit>,
+
+    pub new_preset_name: String,
+    pub new_preset_group: String,
+    pub new_preset_sections: EditSections,
+    pub import_preset_dialog: PresetImportDialog,
+    pub preset_import_error_msg: Option<String>,
+    // whether the transient edit currently shows a hovered preset
+    pub previewing_preset: bool,
+
+    pub new_snapshot_name: String,
+
+    pub retouch_tool: RetouchTool,
+    pub selected_retouch_spot_index: Option<usize>,
+    pub retouch_mode: RetouchMode,
+    pub selected_red_eye_index: Option<usize>,
 
     pub import_image_dialog: ImageImportDialog,
 
@@ -42,6 +78,11 @@ pub struct AppUiState {
     pub library_images_browser_requested_row: Option<usize>,
     pub library_side_panel_requested_row: Option<usize>,
     pub library_side_panel_current_row: Option<usize>,
+    pub selected_library_images: Vec<LibraryImageIdentifier>,
+    pub last_clicked_library_image_index: Option<usize>,
+
+    pub copied_edit: Option<CopiedEdit>,
+    pub paste_settings_dialog: Option<PasteSettingsDialogState>,
 
     pub new_album_name: Option<String>,
     pub export_file_name: Option<String>,
@@ -61,14 +102,42 @@ impl AppUiState {
             selected_album: None,
             show_grid: false,
             show_comparison: false,
+            show_loupe: false,
+            loupe_state: LoupeState::new(),
+            clipping_warning: ClippingWarning::new(),
+            histogram_display: HistogramDisplay::new(),
+            scope_texture: None,
+            scope_texture_reader_uuid: None,
             editor_panel: EditorPanel::LightAndColor,
             selected_curve_control_point_index: None,
             curve_scope: CurveScope::RGB,
             color_mixer_color_index: 0,
             crop_drag_state: CropDragState::new(),
+            straighten_tool_enabled: false,
+            pending_leveling_rotation: None,
+            pending_auto_tone_histogram: None,
+            pending_auto_white_balance_illuminant: None,
+            white_balance_picker_enabled: false,
+            pending_white_balance_sample: None,
             selected_mask_index: 0,
             selected_mask_term_index: None,
             mask_edit_state: MaskEditState::new(),
+            import_mask_image_dialog: MaskImageImportDialog::new(),
+            copied_masked_edit: None,
+            new_preset_name: String::new(),
+            new_preset_group: PresetLibrary::default_group_name().to_owned(),
+            new_preset_sections: EditSections {
+                geometry: false,
+                ..EditSections::all()
+            },
+            import_preset_dialog: PresetImportDialog::new(),
+            preset_import_error_msg: None,
+            previewing_preset: false,
+            new_snapshot_name: String::new(),
+            retouch_tool: RetouchTool::Spot,
+            selected_retouch_spot_index: None,
+            retouch_mode: RetouchMode::Heal,
+            selected_red_eye_index: None,
             import_image_dialog: ImageImportDialog::new(
                 runtime.clone(),
                 toolbox.clone(),
@@ -78,6 +147,10 @@ impl AppUiState {
             library_images_browser_requested_row: None,
             library_side_panel_requested_row: None,
             library_side_panel_current_row: None,
+            selected_library_images: Vec::new(),
+            last_clicked_library_image_index: None,
+            copied_edit: None,
+            paste_settings_dialog: None,
             new_album_name: None,
             export_file_name: None,
             export_image_full_resolution: None,
@@ -93,7 +166,18 @@ impl AppUiState {
         self.selected_mask_index = 0;
         self.selected_mask_term_index = None;
         self.mask_edit_state.dragged_control_point_index = None;
+        self.mask_edit_state.pending_color_sample = None;
+        self.selected_retouch_spot_index = None;
+        self.selected_red_eye_index = None;
         self.main_image_zoom = None;
+        self.straighten_tool_enabled = false;
+        self.crop_drag_state.straighten_line_start = None;
+        self.pending_leveling_rotation = None;
+        self.pending_auto_tone_histogram = None;
+        self.pending_auto_white_balance_illuminant = None;
+        self.white_balance_picker_enabled = false;
+        self.pending_white_balance_sample = None;
+        self.loupe_state = LoupeState::new();
         self.export_file_name = None;
         self.export_image_full_resolution = None;
         self.export_image_selected_resolution = None;
@@ -145,12 +229,22 @@ impl fmt::Display for CurveScope {
 pub enum EditorPanel {
     LightAndColor,
     CropAndRotate,
+    Retouch,
     Framing,
+    Presets,
+    History,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum RetouchTool {
+    Spot,
+    RedEye,
 }
 
 pub struct CropDragState {
     pub edge_or_corner: Option<CropDragEdgeOrCorner>,
     pub translation: bool,
+    pub straighten_line_start: Option<egui::Pos2>,
 }
 
 impl CropDragState {
@@ -158,6 +252,7 @@ impl CropDragState {
         Self {
             edge_or_corner: None,
             translation: false,
+            straighten_line_start: None,
         }
     }
 }
@@ -224,16 +319,129 @@ impl CropDragEdgeOrCorner {
 
 pub struct MaskEditState {
     pub dragged_control_point_index: Option<usize>,
+    pub brush_size: f32,
+    pub brush_feather: f32,
+    pub brush_flow: f32,
+    pub brush_erase: bool,
+    pub pending_color_sample: Option<BufferReader<[f32; 4]>>,
 }
 
 impl MaskEditState {
     pub fn new() -> Self {
         Self {
             dragged_control_point_index: None,
+            brush_size: 0.05,
+            brush_feather: 50.0,
+            brush_flow: 100.0,
+            brush_erase: false,
+            pending_color_sample: None,
+        }
+    }
+}
+
+// the pixels around the cursor, before and after color adjustments
+pub struct LoupeState {
+    pub pending_before: Option<BufferReader<PixelRegion>>,
+    pub pending_after: Option<BufferReader<PixelRegion>>,
+    pub before: Option<PixelRegion>,
+    pub after: Option<PixelRegion>,
+}
+
+impl LoupeState {
+    pub fn new() -> Self {
+        Self {
+            pending_before: None,
+            pending_after: None,
+            before: None,
+            after: None,
         }
     }
 }
 
+// painting of clipped pixels over the main image
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ClippingWarning {
+    pub show_shadows: bool,
+    pub show_highlights: bool,
+    pub shadow_color: egui::Color32,
+    pub highlight_color: egui::Color32,
+}
+
+impl ClippingWarning {
+    pub fn new() -> Self {
+        Self {
+            show_shadows: false,
+            show_highlights: false,
+            shadow_color: egui::Color32::from_rgb(0, 80, 255),
+            highlight_color: egui::Color32::from_rgb(255, 0, 0),
+        }
+    }
+
+    // turns both warnings on, unless either is on already, in which case both are turned off
+    pub fn toggle(&mut self) {
+        let show = !(self.show_shadows || self.show_highlights);
+        self.show_shadows = show;
+        self.show_highlights = show;
+    }
+}
+
+// how the histogram is plotted
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HistogramDisplay {
+    pub log_scale: bool,
+    pub show_r: bool,
+    pub show_g: bool,
+    pub show_b: bool,
+    pub show_luma: bool,
+}
+
+impl HistogramDisplay {
+    pub fn new() -> Self {
+        Self {
+            log_scale: false,
+            show_r: true,
+            show_g: true,
+            show_b: true,
+            show_luma: true,
+        }
+    }
+}
+
+// the full edit of an image, of which selected sections can be pasted into others
+pub struct CopiedEdit {
+    pub edit: Edit,
+    // aspect ratio of the source image before geometry edits
+    pub image_aspect_ratio: f32,
+}
+
+pub struct PasteSettingsDialogState {
+    pub title: String,
+    pub source: CopiedEdit,
+    pub targets: Vec<LibraryImageIdentifier>,
+    pub sections: EditSections,
+    // from 0 to 100
+    pub amount: f32,
+}
+
+// a masked edit copied from one image, which can be pasted into others
+pub struct CopiedMaskedEdit {
+    pub masked_edit: MaskedEdit,
+    // aspect ratio of the source image after its geometry edits
+    pub aspect_ratio: f32,
+    pub source: Option<LibraryImageIdentifier>,
+}
+
+// where a picked mask image should be added. masks can't be picked from the web build.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MaskImageImportTarget {
+    NewMask,
+    NewTerm {
+        mask_index: usize,
+        mode: MaskTermMode,
+    },
+}
+
 #[allow(dead_code)]
 pub enum AddedImageOrAlbum {
     Image(Arc<Image>, LibraryImageMetaData),
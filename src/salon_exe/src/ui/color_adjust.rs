@@ -1,21 +1,65 @@
 use eframe::{
-    egui::{CollapsingHeader, Ui},
+    egui::{Button, CollapsingHeader, Ui},
 };
 
-use salon_core::{editor::GlobalEdit, runtime::ColorSpace, session::Session};
+use salon_core::{
+    editor::{neutralizing_temperature_and_tint, GlobalEdit},
+    runtime::ColorSpace,
+    session::Session,
+};
 
 use super::{widgets::EditorSlider, AppUiState};
 
 pub fn color_adjust(
     ui: &mut Ui,
-    _session: &mut Session,
-    _ui_state: &mut AppUiState,
+    session: &mut Session,
+    ui_state: &mut AppUiState,
     edit: &mut GlobalEdit,
 ) {
+    if let Some(ref mut pending) = ui_state.pending_white_balance_sample {
+        if let Some(rgba) = pending.poll_value() {
+            if let Some((temperature, tint)) =
+                neutralizing_temperature_and_tint([rgba[0], rgba[1], rgba[2]])
+            {
+                edit.temperature = temperature;
+                edit.tint = tint;
+            }
+            ui_state.pending_white_balance_sample = None;
+            ui_state.white_balance_picker_enabled = false;
+        } else {
+            ui.ctx().request_repaint();
+        }
+    }
+
     CollapsingHeader::new("Color")
         .default_open(true)
         .show(ui, |ui| {
             ui.spacing_mut().slider_width = ui.available_width() * 0.6;
+            ui.horizontal(|ui| {
+                // auto white balance applies to the global edit, so it's only offered when that's the one being edited
+                let is_global_edit_selected = session
+                    .editor
+                    .current_edit_context_ref()
+                    .unwrap()
+                    .transient_edit_ref()
+                    .global_masked_edit_index()
+                    == Some(ui_state.selected_mask_index);
+                if is_global_edit_selected {
+                    let button = ui.add_enabled(
+                        ui_state.pending_auto_white_balance_illuminant.is_none(),
+                        Button::new("Auto"),
+                    );
+                    if button
+                        .on_hover_text("Set temperature and tint automatically")
+                        .clicked()
+                    {
+                        ui_state.pending_auto_white_balance_illuminant =
+                            session.editor.estimate_geometry_only_illuminant();
+                    }
+                }
+                ui.toggle_value(&mut ui_state.white_balance_picker_enabled, "Pick Neutral")
+                    .on_hover_text("Click on an area of the image that should be white or gray");
+            });
             ui.add(
                 EditorSlider::new(&mut edit.temperature, -100.0..=100.0)
                     .color_override([0.2, 0.5, 0.9], [1.0, 0.9, 0.2], ColorSpace::LinearRGB)
@@ -40,3 +84,20 @@ pub fn color_adjust(
             );
         });
 }
+
+// applies the auto white balance once the illuminant it's based on has been read back.
+// this commits a new edit, so it needs to happen before the transient edit is taken for the rest of the ui.
+pub fn poll_auto_white_balance(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    if let Some(ref mut pending) = ui_state.pending_auto_white_balance_illuminant {
+        let Some(illuminant) = pending.poll_value() else {
+            ui.ctx().request_repaint();
+            return;
+        };
+        if let Some(illuminant) = *illuminant {
+            if session.editor.apply_auto_white_balance(illuminant) {
+                session.update_thumbnail_for_current_image();
+            }
+        }
+        ui_state.pending_auto_white_balance_illuminant = None;
+    }
+}
@@ -4,7 +4,10 @@ use eframe::{
 
 use salon_core::session::Session;
 
-use super::{utils::legalize_ui_state, AppUiState};
+use super::{
+    copy_settings_action, open_paste_settings_dialog, ui_set_current_editor_image,
+    utils::legalize_ui_state, AppUiState,
+};
 
 pub fn edit_menu(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
     ui.menu_button("Edit", |ui| {
@@ -23,9 +26,55 @@ pub fn edit_menu(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState)
         {
             redo_action(session, ui_state);
         }
+
+        ui.separator();
+
+        if ui.button("Copy Settings").clicked() {
+            copy_settings_action(session, ui_state);
+            ui.close_menu();
+        }
+
+        let can_paste = ui_state.copied_edit.is_some();
+        if ui
+            .add_enabled(can_paste, egui::Button::new("Paste Settings..."))
+            .clicked()
+        {
+            if let Some(identifier) = session.editor.current_image_identifier() {
+                open_paste_settings_dialog(ui_state, vec![identifier]);
+            }
+            ui.close_menu();
+        }
+
+        ui.separator();
+
+        let can_create_virtual_copy = session
+            .editor
+            .current_image_identifier()
+            .is_some_and(|identifier| identifier.get_path().is_some());
+        if ui
+            .add_enabled(
+                can_create_virtual_copy,
+                egui::Button::new("Create Virtual Copy"),
+            )
+            .clicked()
+        {
+            create_virtual_copy_action(session, ui_state);
+            ui.close_menu();
+        }
     });
 }
 
+// creates a virtual copy of the current image, and switches to editing the copy
+pub fn create_virtual_copy_action(session: &mut Session, ui_state: &mut AppUiState) {
+    if let Some(identifier) = session.editor.current_image_identifier() {
+        session.editor.commit_transient_edit(false);
+        if let Some(virtual_copy) = session.create_virtual_copy(&identifier) {
+            ui_state.selected_library_images = vec![virtual_copy.clone()];
+            ui_set_current_editor_image(session, ui_state, virtual_copy);
+        }
+    }
+}
+
 pub fn undo_action(session: &mut Session, ui_state: &mut AppUiState) {
     session.editor.maybe_undo();
     session.update_thumbnail_for_current_image();
@@ -5,8 +5,9 @@ use eframe::{
 use salon_core::{editor::GlobalEdit, session::Session};
 
 use super::{
-    color_adjust, color_mixer, curve, effects, framing, histogram, light_adjust, masking,
-    rotate_and_crop, AppUiState, EditorPanel,
+    color_adjust, color_mixer, curve, effects, framing, histogram, history, light_adjust,
+    masking, poll_auto_tone, poll_auto_white_balance, presets, retouch, rotate_and_crop,
+    scopes, AppUiState, EditorPanel,
 };
 
 pub fn editor(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
@@ -27,7 +28,13 @@ pub fn editor(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
             "Crop and Rotate",
         );
         ui.separator();
+        ui.selectable_value(&mut ui_state.editor_panel, EditorPanel::Retouch, "Retouch");
+        ui.separator();
         ui.selectable_value(&mut ui_state.editor_panel, EditorPanel::Framing, "Framing");
+        ui.separator();
+        ui.selectable_value(&mut ui_state.editor_panel, EditorPanel::Presets, "Presets");
+        ui.separator();
+        ui.selectable_value(&mut ui_state.editor_panel, EditorPanel::History, "History");
 
         if old_panel != ui_state.editor_panel {
             if session.editor.commit_transient_edit(true) {
@@ -38,6 +45,9 @@ pub fn editor(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
 
     ui.separator();
 
+    poll_auto_tone(ui, session, ui_state);
+    poll_auto_white_balance(ui, session, ui_state);
+
     let mut transient_edit = session
         .editor
         .current_edit_context_ref()
@@ -48,6 +58,7 @@ pub fn editor(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
     match ui_state.editor_panel {
         EditorPanel::LightAndColor => {
             histogram(ui, session, ui_state);
+            scopes(ui, session, ui_state);
             ui.separator();
             ScrollArea::vertical().show(ui, |ui| {
                 masking(ui, session, ui_state, &mut transient_edit);
@@ -75,9 +86,32 @@ pub fn editor(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
             rotate_and_crop(ui, session, ui_state, &mut transient_edit);
             session.editor.update_transient_edit(transient_edit, false);
         }
+        EditorPanel::Retouch => {
+            ScrollArea::vertical().show(ui, |ui| {
+                retouch(ui, session, ui_state, &mut transient_edit);
+            });
+            session.editor.update_transient_edit(transient_edit, true);
+            ui.input(|i| {
+                if !i.pointer.any_down() {
+                    if session.editor.commit_transient_edit(false) {
+                        session.update_thumbnail_for_current_image();
+                    }
+                }
+            });
+        }
         EditorPanel::Framing => {
             framing(ui, session, ui_state, &mut transient_edit);
             session.editor.update_transient_edit(transient_edit, false);
         }
+        EditorPanel::Presets => {
+            ScrollArea::vertical().show(ui, |ui| {
+                presets(ui, session, ui_state);
+            });
+        }
+        EditorPanel::History => {
+            ScrollArea::vertical().show(ui, |ui| {
+                history(ui, session, ui_state);
+            });
+        }
     }
 }
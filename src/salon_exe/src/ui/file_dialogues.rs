@@ -1,13 +1,14 @@
-use super::{AddedImageOrAlbum, AppUiState};
+use super::{AddedImageOrAlbum, AppUiState, MaskImageImportTarget};
 
 #[cfg(target_arch = "wasm32")]
 use salon_core::library::LibraryImageMetaData;
 
 use salon_core::{
-    runtime::{ColorSpace, ImageFormat, ImageReaderJpeg, Runtime, Toolbox},
+    presets::Preset,
+    runtime::{ColorSpace, ImageFormat, ImageReaderJpeg, ImageReaderPng16Grayscale, Runtime, Toolbox},
     session::Session,
 };
-use std::{future::Future, sync::Arc};
+use std::{future::Future, path::PathBuf, sync::Arc};
 
 #[cfg(not(target_arch = "wasm32"))]
 pub fn file_dialogue_export_image(session: &mut Session, ui_state: &mut AppUiState) {
@@ -87,6 +88,57 @@ pub fn file_dialogue_export_edit(session: &mut Session, _ui_state: &mut AppUiSta
     });
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn file_dialogue_export_mask(
+    session: &mut Session,
+    masked_edit_index: usize,
+    term_index: Option<usize>,
+) {
+    session.editor.commit_transient_edit(false);
+    let mask = session
+        .editor
+        .get_full_size_mask(masked_edit_index, term_index);
+    let mut image_reader = ImageReaderPng16Grayscale::new(
+        session.runtime.clone(),
+        session.toolbox.clone(),
+        mask,
+    );
+
+    let mut task = rfd::AsyncFileDialog::new().add_filter("extension", &["png"]);
+    if let Some(name) = session
+        .library
+        .get_metadata(&session.editor.current_image_identifier().unwrap())
+        .name
+    {
+        task = task.set_file_name(mask_png_file_name(&name, masked_edit_index, term_index));
+    }
+    let file_handle = task.save_file();
+    execute(async move {
+        let file = file_handle.await;
+        let png_data = image_reader.await_png_data().await;
+        if let Some(file) = file {
+            file.write(&png_data).await.expect("Write file failed");
+        }
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn file_dialogue_export_preset(preset: &Preset) {
+    let preset_json_str = preset.to_json_string();
+    let task = rfd::AsyncFileDialog::new()
+        .add_filter("extension", &["json"])
+        .set_file_name(preset_json_file_name(&preset.name));
+    let file_handle = task.save_file();
+    execute(async move {
+        let file = file_handle.await;
+        if let Some(file) = file {
+            file.write(preset_json_str.as_bytes())
+                .await
+                .expect("Write file failed");
+        }
+    });
+}
+
 #[cfg(target_arch = "wasm32")]
 pub fn file_dialogue_export_image(session: &mut Session, ui_state: &mut AppUiState) {
     session.editor.commit_transient_edit(false);
@@ -183,6 +235,91 @@ pub fn file_dialogue_export_edit(session: &mut Session, ui_state: &mut AppUiStat
     });
 }
 
+#[cfg(target_arch = "wasm32")]
+pub fn file_dialogue_export_mask(
+    session: &mut Session,
+    masked_edit_index: usize,
+    term_index: Option<usize>,
+) {
+    session.editor.commit_transient_edit(false);
+    let mask = session
+        .editor
+        .get_full_size_mask(masked_edit_index, term_index);
+    let mut image_reader = ImageReaderPng16Grayscale::new(
+        session.runtime.clone(),
+        session.toolbox.clone(),
+        mask,
+    );
+
+    let mut output_file_name = "mask.png".to_owned();
+    if let Some(identifier) = session.editor.current_image_identifier() {
+        if let Some(name) = session.library.get_metadata(&identifier).name {
+            output_file_name = mask_png_file_name(&name, masked_edit_index, term_index);
+        }
+    }
+
+    execute(async move {
+        let png_data = image_reader.await_png_data().await;
+        let array = Uint8Array::from(png_data.as_slice());
+        let blob_parts = Array::new();
+        blob_parts.push(&array.buffer());
+        let file = File::new_with_blob_sequence_and_options(
+            &blob_parts.into(),
+            output_file_name.as_str(),
+            web_sys::FilePropertyBag::new().type_("image/png"),
+        )
+        .unwrap();
+        let url = Url::create_object_url_with_blob(&file);
+        if let Some(window) = web_sys::window() {
+            let document = window.document().unwrap();
+            let body = document.body().unwrap();
+            let a = document
+                .create_element("a")
+                .unwrap()
+                .dyn_into::<web_sys::HtmlAnchorElement>()
+                .unwrap();
+            a.set_href(&url.unwrap());
+            a.set_download(output_file_name.as_str());
+            body.append_child(&a).unwrap();
+            a.click();
+            body.remove_child(&a).unwrap();
+        }
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn file_dialogue_export_preset(preset: &Preset) {
+    let preset_json_str = preset.to_json_string();
+    let output_file_name = preset_json_file_name(&preset.name);
+
+    execute(async move {
+        let array = Uint8Array::from(preset_json_str.as_bytes());
+        let blob_parts = Array::new();
+        blob_parts.push(&array.buffer());
+        let file = File::new_with_blob_sequence_and_options(
+            &blob_parts.into(),
+            output_file_name.as_str(),
+            web_sys::FilePropertyBag::new().type_("text/json"),
+        )
+        .unwrap();
+        let url = Url::create_object_url_with_blob(&file);
+        if let Some(window) = web_sys::window() {
+            let document = window.document().unwrap();
+            let body = document.body().unwrap();
+            let a = document
+                .create_element("a")
+                .unwrap()
+                .dyn_into::<web_sys::HtmlAnchorElement>()
+                .unwrap();
+            a.set_href(&url.unwrap());
+            a.set_download(output_file_name.as_str());
+            body.append_child(&a).unwrap();
+            a.click();
+            body.remove_child(&a).unwrap();
+        }
+    });
+}
+
 fn edit_json_file_name(name: &String) -> String {
     let parts: Vec<&str> = name.rsplitn(2, '.').collect();
     if parts.len() == 2 {
@@ -192,6 +329,18 @@ fn edit_json_file_name(name: &String) -> String {
     }
 }
 
+fn preset_json_file_name(name: &String) -> String {
+    format!("{}.json", name)
+}
+
+fn mask_png_file_name(name: &String, masked_edit_index: usize, term_index: Option<usize>) -> String {
+    let stem = name.rsplitn(2, '.').last().unwrap_or(name);
+    match term_index {
+        Some(t) => format!("{}_mask_{}_term_{}.png", stem, masked_edit_index, t),
+        None => format!("{}_mask_{}.png", stem, masked_edit_index),
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn execute<F: Future<Output = ()> + Send + 'static>(f: F) {
     // this is stupid... use any executor of your choice instead
@@ -403,3 +552,84 @@ impl ImageImportDialog {
         }
     }
 }
+
+// mask images are referenced by path, so they can only be imported on native
+pub struct MaskImageImportDialog {
+    channel: (
+        std::sync::mpsc::Sender<(PathBuf, MaskImageImportTarget)>,
+        std::sync::mpsc::Receiver<(PathBuf, MaskImageImportTarget)>,
+    ),
+}
+
+impl MaskImageImportDialog {
+    pub fn new() -> Self {
+        Self {
+            channel: std::sync::mpsc::channel(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_pick_mask_image(&mut self, target: MaskImageImportTarget) {
+        let task = rfd::AsyncFileDialog::new()
+            .add_filter("extension", &["png", "tif", "tiff"])
+            .pick_file();
+
+        let sender = self.channel.0.clone();
+
+        execute(async move {
+            let file = task.await;
+            if let Some(file) = file {
+                sender
+                    .send((file.path().to_path_buf(), target))
+                    .expect("failed to send picked mask image");
+            }
+        });
+    }
+
+    pub fn get_picked_mask_image(&mut self) -> Option<(PathBuf, MaskImageImportTarget)> {
+        self.channel.1.try_recv().ok()
+    }
+}
+
+// picked preset files are read on the background, and their contents are sent back as json strings
+pub struct PresetImportDialog {
+    channel: (
+        std::sync::mpsc::Sender<String>,
+        std::sync::mpsc::Receiver<String>,
+    ),
+}
+
+impl PresetImportDialog {
+    pub fn new() -> Self {
+        Self {
+            channel: std::sync::mpsc::channel(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_pick_presets(&mut self) {
+        let task = rfd::AsyncFileDialog::new()
+            .add_filter("extension", &["json"])
+            .pick_files();
+
+        let sender = self.channel.0.clone();
+
+        execute(async move {
+            let files = task.await;
+            if let Some(files) = files {
+                for file in files {
+                    let bytes = file.read().await;
+                    if let Ok(json_str) = String::from_utf8(bytes) {
+                        sender
+                            .send(json_str)
+                            .expect("failed to send picked preset");
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn get_picked_preset(&mut self) -> Option<String> {
+        self.channel.1.try_recv().ok()
+    }
+}
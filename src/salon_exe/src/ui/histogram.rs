@@ -1,9 +1,10 @@
 use eframe::{
-    egui::{CollapsingHeader, Ui},
+    egui::{self, color_picker, CollapsingHeader, Ui},
     emath::Vec2b,
-    epaint::Color32,
+    epaint::{Color32, Stroke},
 };
 use egui_plot::{Line, Plot, PlotBounds};
+use salon_core::engine::common::{HistogramChannel, ImageHistogram};
 use salon_core::session::Session;
 
 use super::AppUiState;
@@ -16,10 +17,18 @@ pub fn histogram(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState)
                 if let Some(ref mut result) = context.current_result {
                     if let Some(ref mut histogram_final) = result.histogram_final {
                         if let Some(hist) = histogram_final.poll_value() {
+                            let display = ui_state.histogram_display;
+                            let scale = |count: u32| {
+                                if display.log_scale {
+                                    (count as f64).ln_1p()
+                                } else {
+                                    count as f64
+                                }
+                            };
                             let get_line_data = |v: &Vec<u32>| {
                                 let line_data: Vec<[f64; 2]> = (0..hist.num_bins)
                                     .map(|i| {
-                                        [i as f64 / hist.num_bins as f64, v[i as usize] as f64]
+                                        [i as f64 / hist.num_bins as f64, scale(v[i as usize])]
                                     })
                                     .collect();
                                 line_data
@@ -46,7 +55,13 @@ pub fn histogram(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState)
                             let img_dim = result.final_image.properties.dimensions;
                             let num_pixels = img_dim.0 * img_dim.1;
                             let mut y_top = hist.max_value() as f32;
-                            y_top = y_top.min(10.0 * num_pixels as f32 / hist.num_bins as f32);
+                            if display.log_scale {
+                                // the log scale makes room for the peaks already
+                                y_top = y_top.ln_1p();
+                            } else {
+                                y_top = y_top.min(10.0 * num_pixels as f32 / hist.num_bins as f32);
+                            }
+                            y_top = y_top.max(1.0);
 
                             let plot = Plot::new("histogram")
                                 .height(ui_state.last_frame_size.unwrap().1 * 0.1)
@@ -63,19 +78,165 @@ pub fn histogram(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState)
                                 .include_y(y_top)
                                 .show_axes([false, false])
                                 .show_grid([false, false]);
-                            plot.show(ui, |plot_ui| {
+                            let plot_response = plot.show(ui, |plot_ui| {
                                 plot_ui.set_plot_bounds(PlotBounds::from_min_max(
                                     [0.0, 0.0],
                                     [1.0, y_top as f64],
                                 ));
-                                plot_ui.line(r_line);
-                                plot_ui.line(g_line);
-                                plot_ui.line(b_line);
-                                plot_ui.line(luma_line);
+                                if display.show_r {
+                                    plot_ui.line(r_line);
+                                }
+                                if display.show_g {
+                                    plot_ui.line(g_line);
+                                }
+                                if display.show_b {
+                                    plot_ui.line(b_line);
+                                }
+                                if display.show_luma {
+                                    plot_ui.line(luma_line);
+                                }
                             });
+                            clipping_warning_corners(ui, &plot_response.response, ui_state);
+                            histogram_statistics(ui, hist);
                         }
                     }
                 }
             }
+            histogram_display_options(ui, session, ui_state);
+        });
+}
+
+// statistics of the luminance, and the share of pixels clipped in the most clipped channel
+fn histogram_statistics(ui: &mut Ui, hist: &ImageHistogram) {
+    let total = hist.num_pixels(HistogramChannel::Luma);
+    if total == 0 {
+        return;
+    }
+    let channels = [HistogramChannel::R, HistogramChannel::G, HistogramChannel::B];
+    let percentage = |count: u32| count as f64 / total as f64 * 100.0;
+    let clipped_shadows = channels.map(|c| hist.clipped_shadows(c)).into_iter().max().unwrap();
+    let clipped_highlights = channels.map(|c| hist.clipped_highlights(c)).into_iter().max().unwrap();
+    ui.horizontal(|ui| {
+        ui.small(format!(
+            "Mean {:.0}%  Median {:.0}%  P5-P95 {:.0}-{:.0}%",
+            hist.mean(HistogramChannel::Luma) * 100.0,
+            hist.median(HistogramChannel::Luma) * 100.0,
+            hist.percentile(HistogramChannel::Luma, 0.05) * 100.0,
+            hist.percentile(HistogramChannel::Luma, 0.95) * 100.0,
+        ));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.small(format!(
+                "Clipped {:.1}% / {:.1}%",
+                percentage(clipped_shadows),
+                percentage(clipped_highlights)
+            ))
+            .on_hover_text("Pixels at or beyond black / white, in the most clipped channel");
+        });
+    });
+}
+
+fn histogram_display_options(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    let display = &mut ui_state.histogram_display;
+    ui.horizontal(|ui| {
+        ui.toggle_value(&mut display.show_r, "R");
+        ui.toggle_value(&mut display.show_g, "G");
+        ui.toggle_value(&mut display.show_b, "B");
+        ui.toggle_value(&mut display.show_luma, "L");
+        ui.separator();
+        ui.toggle_value(&mut display.log_scale, "Log");
+        ui.separator();
+
+        let mut num_bins = session.editor.histogram_num_bins();
+        let mut auto_bins = num_bins.is_none();
+        ui.checkbox(&mut auto_bins, "Auto Bins");
+        if auto_bins {
+            num_bins = None;
+        } else {
+            let mut n = num_bins.unwrap_or(ImageHistogram::max_bins() as u32);
+            ui.add(
+                egui::DragValue::new(&mut n)
+                    .range(ImageHistogram::min_bins() as u32..=ImageHistogram::max_bins() as u32),
+            );
+            num_bins = Some(n);
+        }
+        session.editor.set_histogram_num_bins(num_bins);
+    });
+}
+
+// the top corners of the histogram toggle the shadow (left) and highlight (right) clipping warnings,
+// and their colors can be changed from the context menu
+fn clipping_warning_corners(ui: &mut Ui, response: &egui::Response, ui_state: &mut AppUiState) {
+    let size = 12.0;
+    let rect = response.rect;
+    let left_corner = egui::Rect::from_min_size(rect.left_top(), egui::Vec2::splat(size));
+    let right_corner = egui::Rect::from_min_size(
+        rect.right_top() - egui::vec2(size, 0.0),
+        egui::Vec2::splat(size),
+    );
+
+    if response.clicked() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            if left_corner.contains(pos) {
+                ui_state.clipping_warning.show_shadows = !ui_state.clipping_warning.show_shadows;
+            } else if right_corner.contains(pos) {
+                ui_state.clipping_warning.show_highlights =
+                    !ui_state.clipping_warning.show_highlights;
+            }
+        }
+    }
+    if let Some(pos) = response.hover_pos() {
+        if left_corner.contains(pos) || right_corner.contains(pos) {
+            ui.output_mut(|out| out.cursor_icon = egui::CursorIcon::PointingHand);
+        }
+    }
+
+    let warning = ui_state.clipping_warning;
+    for (corner, show, color, points) in [
+        (
+            left_corner,
+            warning.show_shadows,
+            warning.shadow_color,
+            [left_corner.left_top(), left_corner.right_top(), left_corner.left_bottom()],
+        ),
+        (
+            right_corner,
+            warning.show_highlights,
+            warning.highlight_color,
+            [right_corner.left_top(), right_corner.right_top(), right_corner.right_bottom()],
+        ),
+    ] {
+        let fill = if show { color } else { Color32::TRANSPARENT };
+        ui.painter_at(corner).add(egui::Shape::convex_polygon(
+            points.to_vec(),
+            fill,
+            Stroke::new(1.0, Color32::GRAY),
+        ));
+    }
+
+    response.context_menu(|ui| {
+        ui.checkbox(
+            &mut ui_state.clipping_warning.show_shadows,
+            "Show Shadow Clipping (J)",
+        );
+        ui.horizontal(|ui| {
+            color_picker::color_edit_button_srgba(
+                ui,
+                &mut ui_state.clipping_warning.shadow_color,
+                color_picker::Alpha::Opaque,
+            );
+            ui.label("Shadow Clipping Color");
+        });
+        ui.checkbox(
+            &mut ui_state.clipping_warning.show_highlights,
+            "Show Highlight Clipping (J)",
+        );
+        ui.horizontal(|ui| {
+            color_picker::color_edit_button_srgba(
+                ui,
+                &mut ui_state.clipping_warning.highlight_color,
+                color_picker::Alpha::Opaque,
+            );
+            ui.label("Highlight Clipping Color");
         });
+    });
 }
@@ -0,0 +1,96 @@
+use eframe::egui::{self, CollapsingHeader, RichText, Ui};
+
+use salon_core::session::Session;
+
+use super::{utils::legalize_ui_state, AppUiState};
+
+pub fn history(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    let context = session.editor.current_edit_context_ref().unwrap();
+    let current_edit_index = context.current_edit_index();
+    let mut clicked_index = None;
+
+    CollapsingHeader::new("History")
+        .default_open(true)
+        .show(ui, |ui| {
+            // the most recent step goes on top
+            for (i, entry) in context.edit_history_ref().iter().enumerate().rev() {
+                let mut text = RichText::new(entry.description.as_str());
+                if i > current_edit_index {
+                    // steps that have been undone, which can still be redone
+                    text = text.weak();
+                }
+                if ui
+                    .selectable_label(i == current_edit_index, text)
+                    .clicked()
+                {
+                    clicked_index = Some(i);
+                }
+            }
+        });
+
+    if let Some(i) = clicked_index {
+        if session.editor.jump_to_edit(i) {
+            session.update_thumbnail_for_current_image();
+        }
+        if let Some(context) = session.editor.current_edit_context_ref() {
+            legalize_ui_state(ui_state, context.current_edit_ref());
+        }
+    }
+
+    snapshots(ui, session, ui_state);
+}
+
+fn snapshots(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    let context = session.editor.current_edit_context_ref().unwrap();
+    let mut restored_index = None;
+    let mut deleted_index = None;
+    let mut create_requested = false;
+
+    CollapsingHeader::new("Snapshots")
+        .default_open(true)
+        .show(ui, |ui| {
+            if context.snapshots_ref().is_empty() {
+                ui.label("No snapshots yet");
+            }
+            for (i, snapshot) in context.snapshots_ref().iter().enumerate() {
+                let is_current = snapshot.edit == *context.current_edit_ref();
+                let response = ui.selectable_label(is_current, snapshot.name.as_str());
+                if response.clicked() {
+                    restored_index = Some(i);
+                }
+                response.context_menu(|ui| {
+                    if ui.button("Delete").clicked() {
+                        deleted_index = Some(i);
+                        ui.close_menu();
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut ui_state.new_snapshot_name);
+                let can_create = !ui_state.new_snapshot_name.trim().is_empty();
+                if ui
+                    .add_enabled(can_create, egui::Button::new("Create Snapshot"))
+                    .clicked()
+                {
+                    create_requested = true;
+                }
+            });
+        });
+
+    if let Some(i) = restored_index {
+        if session.editor.restore_snapshot(i) {
+            session.update_thumbnail_for_current_image();
+        }
+        if let Some(context) = session.editor.current_edit_context_ref() {
+            legalize_ui_state(ui_state, context.current_edit_ref());
+        }
+    }
+    if let Some(i) = deleted_index {
+        session.editor.delete_snapshot(i);
+    }
+    if create_requested {
+        let name = ui_state.new_snapshot_name.trim().to_owned();
+        session.editor.create_snapshot(name);
+        ui_state.new_snapshot_name.clear();
+    }
+}
@@ -9,6 +9,8 @@ use super::{
 };
 
 pub fn keyboard_response(ctx: &egui::Context, session: &mut Session, ui_state: &mut AppUiState) {
+    // single-key shortcuts shouldn't fire while typing into a text field
+    let typing = ctx.wants_keyboard_input();
     ctx.input(|i| {
         if i.key_pressed(egui::Key::Escape) {
             match ui_state.app_page {
@@ -30,6 +32,12 @@ pub fn keyboard_response(ctx: &egui::Context, session: &mut Session, ui_state: &
                     }
                 }
             }
+            if i.key_pressed(egui::Key::L) && !typing {
+                ui_state.show_loupe = !ui_state.show_loupe;
+            }
+            if i.key_pressed(egui::Key::J) && !typing {
+                ui_state.clipping_warning.toggle();
+            }
             let control_or_comand = i.modifiers.command || i.modifiers.ctrl || i.modifiers.mac_cmd;
             if i.key_pressed(egui::Key::Z) && control_or_comand {
                 if i.modifiers.shift {
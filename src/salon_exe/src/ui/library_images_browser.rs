@@ -9,7 +9,7 @@ use salon_core::{
 };
 
 use super::{
-    ui_set_current_editor_image,
+    open_paste_settings_dialog, open_sync_settings_dialog, ui_set_current_editor_image,
     utils::{get_album_name_text_with_emoji_and_count, get_max_image_size},
     widgets::{ThumbnailCallback, ThumbnailClip},
     AppPage, AppUiState,
@@ -46,6 +46,11 @@ pub fn library_images_browser(ui: &mut Ui, session: &mut Session, ui_state: &mut
     }
 
     let mut removed_image = None;
+    let mut clicked_image = None;
+    let mut paste_settings_requested = false;
+    let mut sync_settings_requested = false;
+    let mut virtual_copy_original = None;
+    let mut deleted_virtual_copy = None;
 
     table.body(|body| {
         body.rows(row_height, num_rows, |mut row| {
@@ -56,14 +61,7 @@ pub fn library_images_browser(ui: &mut Ui, session: &mut Session, ui_state: &mut
                     if image_index >= num_images {
                         return;
                     }
-                    let image_identifier = if let Some(album_index) = ui_state.selected_album {
-                        session
-                            .library
-                            .get_identifier_at_index_for_album(image_index, album_index)
-                            .clone()
-                    } else {
-                        session.library.get_identifier_at_index(image_index).clone()
-                    };
+                    let image_identifier = identifier_at_index(session, ui_state, image_index);
                     if let Some(image) = session
                         .library
                         .get_thumbnail_from_identifier(&image_identifier)
@@ -94,6 +92,9 @@ pub fn library_images_browser(ui: &mut Ui, session: &mut Session, ui_state: &mut
                         if image_frame_hovered {
                             image_framing_color = egui::Color32::from_gray(60);
                         }
+                        if ui_state.selected_library_images.contains(&image_identifier) {
+                            image_framing_color = egui::Color32::from_gray(90);
+                        }
                         ui.painter().rect_filled(
                             image_frame_rect,
                             egui::Rounding::ZERO,
@@ -163,16 +164,53 @@ pub fn library_images_browser(ui: &mut Ui, session: &mut Session, ui_state: &mut
                         }
 
                         if image_frame_clicked && !rating_clicked {
-                            ui_state.app_page = AppPage::Editor;
-                            ui_state.library_side_panel_requested_row = Some(image_index);
-                            ui_state.library_images_browser_requested_row = Some(row_index);
-                            ui_set_current_editor_image(
-                                session,
-                                ui_state,
-                                image_identifier.clone(),
-                            );
+                            clicked_image = Some((image_index, row_index));
                         }
                         image_frame_response.context_menu(|ui| {
+                            if !ui_state.selected_library_images.contains(&image_identifier) {
+                                ui_state.selected_library_images = vec![image_identifier.clone()];
+                            }
+                            let num_selected = ui_state.selected_library_images.len();
+                            if ui
+                                .add_enabled(
+                                    ui_state.copied_edit.is_some(),
+                                    egui::Button::new("Paste Settings..."),
+                                )
+                                .clicked()
+                            {
+                                paste_settings_requested = true;
+                                ui.close_menu();
+                            }
+                            let can_sync = session.editor.current_image_identifier().is_some()
+                                && num_selected > 1;
+                            if ui
+                                .add_enabled(
+                                    can_sync,
+                                    egui::Button::new("Sync Settings from Current Image..."),
+                                )
+                                .clicked()
+                            {
+                                sync_settings_requested = true;
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            if ui
+                                .add_enabled(
+                                    image_identifier.get_path().is_some(),
+                                    egui::Button::new("Create Virtual Copy"),
+                                )
+                                .clicked()
+                            {
+                                virtual_copy_original = Some(image_identifier.clone());
+                                ui.close_menu();
+                            }
+                            if image_identifier.is_virtual_copy()
+                                && ui.button("Delete Virtual Copy").clicked()
+                            {
+                                deleted_virtual_copy = Some(image_identifier.clone());
+                                ui.close_menu();
+                            }
+                            ui.separator();
                             ui.menu_button("Add to album", |ui| {
                                 for i in 0..session.library.albums().len() {
                                     let can_add = !session.library.albums()[i]
@@ -209,6 +247,59 @@ pub fn library_images_browser(ui: &mut Ui, session: &mut Session, ui_state: &mut
         });
     });
 
+    if let Some((image_index, row_index)) = clicked_image {
+        let modifiers = ui.input(|i| i.modifiers);
+        let image_identifier = identifier_at_index(session, ui_state, image_index);
+        if modifiers.command {
+            // toggle the image in the selection
+            let selected = &mut ui_state.selected_library_images;
+            if let Some(i) = selected.iter().position(|id| *id == image_identifier) {
+                selected.remove(i);
+            } else {
+                selected.push(image_identifier);
+            }
+            ui_state.last_clicked_library_image_index = Some(image_index);
+        } else if let (true, Some(last_index)) =
+            (modifiers.shift, ui_state.last_clicked_library_image_index)
+        {
+            // select the range between the last clicked image and this one
+            let range = image_index.min(last_index)..=image_index.max(last_index);
+            let mut selected = Vec::new();
+            for i in range {
+                selected.push(identifier_at_index(session, ui_state, i));
+            }
+            ui_state.selected_library_images = selected;
+        } else {
+            ui_state.selected_library_images = vec![image_identifier.clone()];
+            ui_state.last_clicked_library_image_index = Some(image_index);
+            ui_state.app_page = AppPage::Editor;
+            ui_state.library_side_panel_requested_row = Some(image_index);
+            ui_state.library_images_browser_requested_row = Some(row_index);
+            ui_set_current_editor_image(session, ui_state, image_identifier);
+        }
+    }
+
+    if paste_settings_requested {
+        open_paste_settings_dialog(ui_state, ui_state.selected_library_images.clone());
+    } else if sync_settings_requested {
+        open_sync_settings_dialog(session, ui_state, ui_state.selected_library_images.clone());
+    }
+
+    if let Some(original) = virtual_copy_original {
+        session.editor.commit_transient_edit(false);
+        if let Some(virtual_copy) = session.create_virtual_copy(&original) {
+            ui_state.selected_library_images = vec![virtual_copy];
+            ui_state.last_clicked_library_image_index = None;
+        }
+    }
+    if let Some(virtual_copy) = deleted_virtual_copy {
+        session.delete_virtual_copy(&virtual_copy);
+        ui_state
+            .selected_library_images
+            .retain(|identifier| *identifier != virtual_copy);
+        ui_state.last_clicked_library_image_index = None;
+    }
+
     if let Some(removed_image) = removed_image {
         session
             .library
@@ -216,6 +307,21 @@ pub fn library_images_browser(ui: &mut Ui, session: &mut Session, ui_state: &mut
     }
 }
 
+fn identifier_at_index(
+    session: &mut Session,
+    ui_state: &AppUiState,
+    image_index: usize,
+) -> LibraryImageIdentifier {
+    if let Some(album_index) = ui_state.selected_album {
+        session
+            .library
+            .get_identifier_at_index_for_album(image_index, album_index)
+            .clone()
+    } else {
+        session.library.get_identifier_at_index(image_index).clone()
+    }
+}
+
 fn image_rating(
     ui: &mut Ui,
     session: &mut Session,
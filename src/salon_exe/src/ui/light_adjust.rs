@@ -1,5 +1,5 @@
 use eframe::{
-    egui::{CollapsingHeader, Ui},
+    egui::{Button, CollapsingHeader, Ui},
 };
 
 use salon_core::{editor::GlobalEdit, session::Session};
@@ -8,14 +8,35 @@ use super::{widgets::EditorSlider, AppUiState};
 
 pub fn light_adjust(
     ui: &mut Ui,
-    _session: &mut Session,
-    _ui_state: &mut AppUiState,
+    session: &mut Session,
+    ui_state: &mut AppUiState,
     edit: &mut GlobalEdit,
 ) {
     CollapsingHeader::new("Light")
         .default_open(true)
         .show(ui, |ui| {
             ui.spacing_mut().slider_width = ui.available_width() * 0.6;
+            // auto tone applies to the global edit, so it's only offered when that's the one being edited
+            let is_global_edit_selected = session
+                .editor
+                .current_edit_context_ref()
+                .unwrap()
+                .transient_edit_ref()
+                .global_masked_edit_index()
+                == Some(ui_state.selected_mask_index);
+            if is_global_edit_selected {
+                let button = ui.add_enabled(
+                    ui_state.pending_auto_tone_histogram.is_none(),
+                    Button::new("Auto"),
+                );
+                if button
+                    .on_hover_text("Set exposure, contrast, highlights and shadows automatically")
+                    .clicked()
+                {
+                    ui_state.pending_auto_tone_histogram =
+                        session.editor.compute_geometry_only_histogram();
+                }
+            }
             ui.add(
                 EditorSlider::new(&mut edit.exposure, -4.0..=4.0)
                     .double_click_reset_value(0.0)
@@ -41,3 +62,20 @@ pub fn light_adjust(
             );
         });
 }
+
+// applies the auto tone once the histogram it's based on has been read back.
+// this commits a new edit, so it needs to happen before the transient edit is taken for the rest of the ui.
+pub fn poll_auto_tone(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    if let Some(ref mut pending) = ui_state.pending_auto_tone_histogram {
+        if pending.poll_value().is_none() {
+            ui.ctx().request_repaint();
+            return;
+        }
+        if let Some(histogram) = pending.take_value() {
+            if session.editor.apply_auto_tone(&histogram) {
+                session.update_thumbnail_for_current_image();
+            }
+        }
+        ui_state.pending_auto_tone_histogram = None;
+    }
+}
@@ -3,18 +3,27 @@ use std::f32::consts::PI;
 use eframe::egui::{CursorIcon, Ui};
 use eframe::epaint::{Color32, Pos2, Stroke};
 use eframe::{egui, egui_wgpu};
-use salon_core::ir::{LinearGradientMask, MaskPrimitive, RadialGradientMask};
+use salon_core::ir::{
+    BrushMask, BrushStroke, ColorRangeMask, LinearGradientMask, MaskPrimitive, PolygonMask,
+    RadialGradientMask, RedEyeCorrection, RetouchSpot,
+};
+use salon_core::runtime::{BufferReader, Image, Toolbox};
 use salon_core::session::Session;
+use salon_core::utils::color::{linear_to_srgb_channel, rgb_to_hsl, rgb_to_lch};
 use salon_core::utils::math::{
     get_crop_rect_translation_bounds, get_crop_rect_upscale_bounds, get_rotation_mat,
-    handle_new_crop_rect,
+    get_rotation_degrees_to_level_line, handle_new_crop_rect, handle_new_rotation,
+    wrap_rotation_degrees,
 };
 use salon_core::utils::rectangle::Rectangle;
 use salon_core::utils::vec::{vec2, Vec2};
 
 use super::utils::{get_max_image_size, pos2_to_vec2, AnimatedValue};
 use super::widgets::{ImageFramingCallback, ImageGeometryEditCallback, MainImageCallback};
-use super::{AppPage, AppUiState, CropDragEdgeOrCorner, EditorPanel, MainImageZoom, MaskEditState};
+use super::{
+    AppPage, AppUiState, CropDragEdgeOrCorner, EditorPanel, LoupeState, MainImageZoom,
+    MaskEditState, RetouchTool,
+};
 
 pub fn main_image(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
     if ui_state.app_page == AppPage::Export {
@@ -47,7 +56,10 @@ fn show_main_image(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
         EditorPanel::Framing => {
             image_framing(ui, session, ui_state);
         }
-        EditorPanel::LightAndColor => {
+        EditorPanel::LightAndColor
+        | EditorPanel::Retouch
+        | EditorPanel::Presets
+        | EditorPanel::History => {
             show_edited_image(ui, session, ui_state);
         }
     }
@@ -61,6 +73,9 @@ fn show_comparison_reference_image(ui: &mut Ui, session: &mut Session, ui_state:
                 EditorPanel::CropAndRotate => context.input_image().clone(),
                 EditorPanel::Framing => result.before_framing.clone(),
                 EditorPanel::LightAndColor => result.geometry_only.clone(),
+                EditorPanel::Retouch => result.geometry_only.clone(),
+                EditorPanel::Presets => result.geometry_only.clone(),
+                EditorPanel::History => result.geometry_only.clone(),
             };
 
             let main_image_callback = MainImageCallback {
@@ -68,6 +83,7 @@ fn show_comparison_reference_image(ui: &mut Ui, session: &mut Session, ui_state:
                 mask: None,
                 zoom: None,
                 ui_max_rect: ui.max_rect(),
+                clipping_warning: None,
             };
 
             let main_image_rect = main_image_callback.image_ui_rect();
@@ -124,8 +140,13 @@ fn show_edited_image(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiSta
 
         let context = session.editor.current_edit_context_mut().unwrap();
         if let Some(ref result) = context.current_result {
+            let is_retouching = ui_state.editor_panel == EditorPanel::Retouch;
+            let is_browsing_presets = ui_state.editor_panel == EditorPanel::Presets;
+            let is_browsing_history = ui_state.editor_panel == EditorPanel::History;
             let mut mask = None;
-            if let Some(term_index) = ui_state.selected_mask_term_index {
+            if is_retouching || is_browsing_presets || is_browsing_history {
+                // masks are not shown while retouching or browsing presets or history
+            } else if let Some(term_index) = ui_state.selected_mask_term_index {
                 mask = Some(
                     result.masked_edit_results[ui_state.selected_mask_index].mask_terms[term_index]
                         .clone(),
@@ -137,16 +158,36 @@ fn show_edited_image(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiSta
                 mask,
                 zoom: ui_state.main_image_zoom.clone(),
                 ui_max_rect: ui.max_rect(),
+                clipping_warning: Some(ui_state.clipping_warning),
             };
 
-            let is_editing_mask_term = ui_state.selected_mask_term_index.is_some();
-            let sense = if is_editing_mask_term {
+            let is_picking_white_balance = ui_state.white_balance_picker_enabled
+                && ui_state.editor_panel == EditorPanel::LightAndColor;
+            let is_editing_mask_term = !is_retouching
+                && !is_browsing_presets
+                && !is_browsing_history
+                && !is_picking_white_balance
+                && ui_state.selected_mask_term_index.is_some();
+            // sampling colors and adding polygon vertices are done by clicking
+            let mut mask_term_needs_clicks = false;
+            if let Some(term_index) = ui_state.selected_mask_term_index {
+                mask_term_needs_clicks = is_editing_mask_term
+                    && matches!(
+                        context.transient_edit_ref().masked_edits[ui_state.selected_mask_index]
+                            .mask
+                            .terms[term_index]
+                            .primitive,
+                        MaskPrimitive::ColorRange(_) | MaskPrimitive::Polygon(_)
+                    );
+            }
+            let sense = if is_editing_mask_term && !mask_term_needs_clicks {
                 egui::Sense::drag()
             } else {
                 egui::Sense::click_and_drag()
             };
 
             let main_image_rect = main_image_callback.image_ui_rect();
+            let full_image_rect = main_image_callback.full_image_ui_rect();
             let response = ui.allocate_rect(main_image_rect, sense);
             ui.painter_at(ui.max_rect())
                 .add(egui_wgpu::Callback::new_paint_callback(
@@ -157,20 +198,91 @@ fn show_edited_image(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiSta
             if ui_state.show_grid {
                 draw_grid_impl(ui, main_image_rect, ui_state);
             }
-            if is_editing_mask_term {
+            if ui_state.show_loupe {
+                loupe(
+                    ui,
+                    full_image_rect,
+                    &response,
+                    &mut ui_state.loupe_state,
+                    &result.geometry_only,
+                    &result.before_framing,
+                    &session.toolbox,
+                );
+            }
+            if is_retouching {
+                let aspect_ratio = main_image_rect.width() / main_image_rect.height();
+                let mut spots = context.transient_edit_ref().retouch_spots.clone();
+                let mut corrections = context.transient_edit_ref().red_eye_corrections.clone();
+                let should_commit = match ui_state.retouch_tool {
+                    RetouchTool::Spot => retouch_spot_control_points(
+                        ui,
+                        main_image_rect,
+                        &response,
+                        &mut spots,
+                        aspect_ratio,
+                        ui_state,
+                    ),
+                    RetouchTool::RedEye => red_eye_control_points(
+                        ui,
+                        main_image_rect,
+                        &response,
+                        &mut corrections,
+                        ui_state,
+                    ),
+                };
+                // only re-executed when a spot or correction was actually added or moved
+                let transient_edit = context.transient_edit_ref();
+                if spots != transient_edit.retouch_spots
+                    || corrections != transient_edit.red_eye_corrections
+                {
+                    let mut transient_edit = transient_edit.clone();
+                    transient_edit.retouch_spots = spots;
+                    transient_edit.red_eye_corrections = corrections;
+                    session.editor.update_transient_edit(transient_edit, true);
+                }
+                if should_commit {
+                    if session.editor.commit_transient_edit(false) {
+                        session.update_thumbnail_for_current_image();
+                    }
+                }
+            } else if is_picking_white_balance {
+                // neutralizing is based on the colors before any color adjustments
+                white_balance_picking(
+                    ui,
+                    main_image_rect,
+                    &response,
+                    &mut ui_state.pending_white_balance_sample,
+                    &result.geometry_only,
+                    &session.toolbox,
+                );
+            } else if is_editing_mask_term {
                 let term_index = ui_state.selected_mask_term_index.unwrap();
                 let mut transient_edit = context.transient_edit_ref().clone();
                 let primitive = &mut transient_edit.masked_edits[ui_state.selected_mask_index]
                     .mask
                     .terms[term_index]
                     .primitive;
-                let should_commit = mask_primitive_control_points(
-                    ui,
-                    main_image_rect,
-                    &response,
-                    primitive,
-                    &mut ui_state.mask_edit_state,
-                );
+                let should_commit = if let MaskPrimitive::ColorRange(ref mut m) = primitive {
+                    let sampling_image =
+                        result.masked_edit_results[ui_state.selected_mask_index].target.clone();
+                    color_range_sampling(
+                        ui,
+                        main_image_rect,
+                        &response,
+                        m,
+                        &mut ui_state.mask_edit_state,
+                        &sampling_image,
+                        &session.toolbox,
+                    )
+                } else {
+                    mask_primitive_control_points(
+                        ui,
+                        main_image_rect,
+                        &response,
+                        primitive,
+                        &mut ui_state.mask_edit_state,
+                    )
+                };
                 session.editor.update_transient_edit(transient_edit, true);
                 if should_commit {
                     if session.editor.commit_transient_edit(false) {
@@ -195,6 +307,7 @@ fn image_framing(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState)
                     mask: None,
                     zoom: None,
                     ui_max_rect: ui.max_rect(),
+                    clipping_warning: None,
                 };
 
                 let main_image_rect = main_image_callback.image_ui_rect();
@@ -261,6 +374,21 @@ fn image_crop_and_rotate(ui: &mut Ui, session: &mut Session, ui_state: &mut AppU
             .clone()
             .unwrap_or(Rectangle::regular());
         let original_rotation_degrees = transient_edit.rotation_degrees.clone().unwrap_or(0.0);
+
+        if ui_state.straighten_tool_enabled {
+            draw_grid_impl(ui, cropped_image_ui_rect, ui_state);
+            let rotation_delta = handle_straighten_response(ui, &response, ui_state);
+            if let Some(delta) = rotation_delta {
+                handle_new_rotation(
+                    original_image.aspect_ratio(),
+                    &mut transient_edit,
+                    wrap_rotation_degrees(original_rotation_degrees + delta),
+                );
+                session.editor.update_transient_edit(transient_edit, false);
+            }
+            return;
+        }
+
         let new_crop_rect = handle_crop_and_rotate_response(
             ui,
             &response,
@@ -297,6 +425,7 @@ fn show_image_to_be_exported(ui: &mut Ui, _session: &mut Session, ui_state: &mut
             mask: None,
             zoom: None,
             ui_max_rect: ui.max_rect(),
+            clipping_warning: None,
         };
 
         let main_image_rect = main_image_callback.image_ui_rect();
@@ -414,10 +543,53 @@ fn mask_primitive_control_points(
         MaskPrimitive::LinearGradient(ref mut m) => {
             linear_gradient_control_points(ui, rect, response, m, mask_edit_state)
         }
+        MaskPrimitive::Brush(ref mut m) => brush_mask_painting(ui, rect, response, m, mask_edit_state),
+        MaskPrimitive::Polygon(ref mut m) => {
+            polygon_control_points(ui, rect, response, m, mask_edit_state)
+        }
         _ => false,
     }
 }
 
+// returns whether newly sampled reference colors should be committed
+fn color_range_sampling(
+    ui: &mut Ui,
+    rect: egui::Rect,
+    response: &egui::Response,
+    color_range: &mut ColorRangeMask,
+    mask_edit_state: &mut MaskEditState,
+    sampling_image: &Image,
+    toolbox: &Toolbox,
+) -> bool {
+    if let Some(ref mut reader) = mask_edit_state.pending_color_sample {
+        if let Some(rgba) = reader.poll_value() {
+            if color_range.reference_colors.len() < ColorRangeMask::max_num_reference_colors() {
+                color_range.reference_colors.push([rgba[0], rgba[1], rgba[2]]);
+            }
+            mask_edit_state.pending_color_sample = None;
+            return true;
+        }
+        ui.ctx().request_repaint();
+        return false;
+    }
+
+    let Some(hover_pos) = response.hover_pos() else {
+        return false;
+    };
+    if color_range.reference_colors.len() >= ColorRangeMask::max_num_reference_colors() {
+        return false;
+    }
+    ui.output_mut(|out| out.cursor_icon = CursorIcon::Crosshair);
+    if response.clicked() {
+        let position = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+        // average over a few pixels, so that noise doesn't affect the sampled color too much
+        mask_edit_state.pending_color_sample =
+            Some(toolbox.sample_pixels(sampling_image, position, 2));
+        ui.ctx().request_repaint();
+    }
+    false
+}
+
 fn get_absolute_pos(rect: egui::Rect, (relative_x, relative_y): (f32, f32)) -> (f32, f32) {
     (
         rect.min.x + relative_x * rect.width(),
@@ -425,6 +597,138 @@ fn get_absolute_pos(rect: egui::Rect, (relative_x, relative_y): (f32, f32)) -> (
     )
 }
 
+fn white_balance_picking(
+    ui: &mut Ui,
+    rect: egui::Rect,
+    response: &egui::Response,
+    pending_sample: &mut Option<BufferReader<[f32; 4]>>,
+    sampling_image: &Image,
+    toolbox: &Toolbox,
+) {
+    let Some(hover_pos) = response.hover_pos() else {
+        return;
+    };
+    ui.output_mut(|out| out.cursor_icon = CursorIcon::Crosshair);
+    if response.clicked() && pending_sample.is_none() {
+        let position = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+        // average over a few pixels, so that noise doesn't affect the sampled color too much
+        *pending_sample = Some(toolbox.sample_pixels(sampling_image, position, 2));
+        ui.ctx().request_repaint();
+    }
+}
+
+// the number of pixels shown in the loupe on each side of the one under the cursor
+const LOUPE_RADIUS: i32 = 4;
+
+// magnified pixels under the cursor, with their values before and after color adjustments.
+// `rect` is the whole image, which may extend beyond the ui when zoomed-in.
+fn loupe(
+    ui: &mut Ui,
+    rect: egui::Rect,
+    response: &egui::Response,
+    loupe_state: &mut LoupeState,
+    before_image: &Image,
+    after_image: &Image,
+    toolbox: &Toolbox,
+) {
+    for (pending, region) in [
+        (&mut loupe_state.pending_before, &mut loupe_state.before),
+        (&mut loupe_state.pending_after, &mut loupe_state.after),
+    ] {
+        if let Some(ref mut reader) = pending {
+            if let Some(value) = reader.poll_value() {
+                *region = Some(value.clone());
+                *pending = None;
+            }
+        }
+    }
+
+    let Some(hover_pos) = response.hover_pos() else {
+        return;
+    };
+    let position = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+    if !(0.0..1.0).contains(&position.0) || !(0.0..1.0).contains(&position.1) {
+        return;
+    }
+    // the image keeps changing while editing, so the pixels are read again as soon as the last read completes
+    if loupe_state.pending_before.is_none() && loupe_state.pending_after.is_none() {
+        let (x, y) = after_image.pixel_at(position);
+        let origin = (x - LOUPE_RADIUS, y - LOUPE_RADIUS);
+        let size = (2 * LOUPE_RADIUS + 1) as u32;
+        loupe_state.pending_before = Some(toolbox.read_pixels(before_image, origin, (size, size)));
+        loupe_state.pending_after = Some(toolbox.read_pixels(after_image, origin, (size, size)));
+    }
+    ui.ctx().request_repaint();
+
+    let (Some(ref before), Some(ref after)) = (&loupe_state.before, &loupe_state.after) else {
+        return;
+    };
+    egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), response.id.with("loupe"), |ui| {
+        let cell_size = 10.0;
+        let (grid_rect, _) = ui.allocate_exact_size(
+            egui::Vec2::splat(cell_size * after.size.0 as f32),
+            egui::Sense::hover(),
+        );
+        for y in 0..after.size.1 {
+            for x in 0..after.size.0 {
+                let cell = egui::Rect::from_min_size(
+                    grid_rect.min + egui::vec2(x as f32, y as f32) * cell_size,
+                    egui::Vec2::splat(cell_size),
+                );
+                let [r, g, b] = display_rgb(after.pixel(x, y));
+                ui.painter().rect_filled(cell, 0.0, Color32::from_rgb(r, g, b));
+            }
+        }
+        let center_cell = egui::Rect::from_min_size(
+            grid_rect.min + egui::Vec2::splat(LOUPE_RADIUS as f32 * cell_size),
+            egui::Vec2::splat(cell_size),
+        );
+        ui.painter()
+            .rect_stroke(center_cell, 0.0, Stroke::new(1.0, Color32::WHITE));
+
+        ui.label(format!(
+            "x: {}, y: {}",
+            after.origin.0 + LOUPE_RADIUS,
+            after.origin.1 + LOUPE_RADIUS
+        ));
+        egui::Grid::new("loupe_readout").show(ui, |ui| {
+            ui.label("");
+            ui.label("Before");
+            ui.label("After");
+            ui.end_row();
+            let rows: [(&str, fn([f32; 4]) -> String); 4] = [
+                ("RGB", |p| {
+                    let [r, g, b] = display_rgb(p);
+                    format!("{} {} {}", r, g, b)
+                }),
+                ("Linear", |p| format!("{:.3} {:.3} {:.3}", p[0], p[1], p[2])),
+                ("LCh", |p| {
+                    let [l, c, h] = rgb_to_lch([p[0], p[1], p[2]]);
+                    format!("{:.1} {:.1} {:.0}°", l, c, h.to_degrees())
+                }),
+                ("HSL", |p| {
+                    // of the sRGB encoded values, as with the 0-255 values
+                    let srgb = [p[0], p[1], p[2]].map(|c| linear_to_srgb_channel(c.clamp(0.0, 1.0)));
+                    let [h, s, l] = rgb_to_hsl(srgb);
+                    format!("{:.0}° {:.0}% {:.0}%", h * 360.0, s * 100.0, l * 100.0)
+                }),
+            ];
+            for (name, format_pixel) in rows {
+                ui.label(name);
+                ui.monospace(format_pixel(before.center()));
+                ui.monospace(format_pixel(after.center()));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+// 8-bit sRGB, clipped to the displayable range
+fn display_rgb(pixel: [f32; 4]) -> [u8; 3] {
+    [pixel[0], pixel[1], pixel[2]]
+        .map(|c| (linear_to_srgb_channel(c.clamp(0.0, 1.0)) * 255.0).round() as u8)
+}
+
 fn get_relative_pos(rect: egui::Rect, (abs_x, abs_y): (f32, f32)) -> (f32, f32) {
     (
         (abs_x - rect.min.x) / rect.width(),
@@ -619,6 +923,312 @@ fn linear_gradient_control_points(
     false
 }
 
+// returns whether pending changes to the brush strokes should be committed
+fn brush_mask_painting(
+    ui: &mut Ui,
+    rect: egui::Rect,
+    response: &egui::Response,
+    brush: &mut BrushMask,
+    mask_edit_state: &mut MaskEditState,
+) -> bool {
+    let Some(hover_pos) = response.hover_pos() else {
+        return false;
+    };
+
+    // brush outline
+    ui.output_mut(|out| out.cursor_icon = CursorIcon::Crosshair);
+    let radius = mask_edit_state.brush_size * rect.height();
+    let stroke = Stroke {
+        color: Color32::from_gray(230),
+        width: 1.0,
+    };
+    ui.painter().circle_stroke(hover_pos, radius, stroke);
+    let feather_radius = radius * (1.0 - mask_edit_state.brush_feather * 0.01);
+    if feather_radius > 1.0 {
+        ui.painter().circle_stroke(hover_pos, feather_radius, stroke);
+    }
+
+    let point = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+    if response.drag_started() {
+        brush.strokes.push(BrushStroke {
+            points: vec![point],
+            size: mask_edit_state.brush_size,
+            feather: mask_edit_state.brush_feather,
+            flow: mask_edit_state.brush_flow,
+            erase: mask_edit_state.brush_erase,
+        });
+    } else if response.dragged() {
+        if let Some(stroke) = brush.strokes.last_mut() {
+            let last = *stroke.points.last().unwrap();
+            let last_abs = vec2(get_absolute_pos(rect, last));
+            // avoid piling up points while the cursor barely moves
+            if (last_abs - pos2_to_vec2(hover_pos)).length() > 2.0 {
+                stroke.points.push(point);
+            }
+        }
+    }
+    response.drag_stopped()
+}
+
+// returns whether pending changes to the polygon vertices should be committed
+fn polygon_control_points(
+    ui: &mut Ui,
+    rect: egui::Rect,
+    response: &egui::Response,
+    polygon: &mut PolygonMask,
+    mask_edit_state: &mut MaskEditState,
+) -> bool {
+    // outline of the polygon, closed once it can cover an area
+    let vertices_abs: Vec<Pos2> = polygon
+        .vertices
+        .iter()
+        .map(|v| {
+            let (x, y) = get_absolute_pos(rect, *v);
+            Pos2 { x, y }
+        })
+        .collect();
+    let stroke = Stroke {
+        width: rect.width().min(rect.height()) * 0.002,
+        color: Color32::WHITE,
+    };
+    let painter = ui.painter_at(rect);
+    for i in 1..vertices_abs.len() {
+        painter.line_segment([vertices_abs[i - 1], vertices_abs[i]], stroke);
+    }
+    if vertices_abs.len() >= 3 {
+        painter.line_segment([vertices_abs[vertices_abs.len() - 1], vertices_abs[0]], stroke);
+    }
+
+    let mut hovered_index = None;
+    for i in 0..polygon.vertices.len() {
+        let p_abs = vec2(get_absolute_pos(rect, polygon.vertices[i]));
+        if let Some(dragged_index) = mask_edit_state.dragged_control_point_index {
+            if dragged_index == i {
+                ui.output_mut(|out| out.cursor_icon = CursorIcon::Grabbing);
+                if response.dragged() {
+                    if let Some(hover_pos) = response.hover_pos() {
+                        polygon.vertices[i] = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+                    }
+                }
+            }
+        } else if let Some(hover_pos) = response.hover_pos() {
+            let dist = (p_abs - vec2((hover_pos.x, hover_pos.y))).length();
+            if dist < rect.width().min(rect.height()) * 0.012 {
+                hovered_index = Some(i);
+                ui.output_mut(|out| out.cursor_icon = CursorIcon::Grab);
+                if response.drag_started() {
+                    mask_edit_state.dragged_control_point_index = Some(i);
+                }
+            }
+        }
+        draw_control_point_circle(ui, rect, p_abs.xy());
+    }
+
+    if response.drag_stopped() && mask_edit_state.dragged_control_point_index.is_some() {
+        mask_edit_state.dragged_control_point_index = None;
+        return true;
+    }
+
+    if let Some(i) = hovered_index {
+        // right clicking a vertex removes it
+        if response.secondary_clicked() {
+            polygon.vertices.remove(i);
+            return true;
+        }
+    } else if mask_edit_state.dragged_control_point_index.is_none() {
+        if let Some(hover_pos) = response.hover_pos() {
+            if rect.contains(hover_pos) {
+                ui.output_mut(|out| out.cursor_icon = CursorIcon::Crosshair);
+                if response.clicked() {
+                    let vertex = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+                    polygon.vertices.push(vertex);
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+// returns whether pending changes to retouch spots should be committed
+fn retouch_spot_control_points(
+    ui: &mut Ui,
+    rect: egui::Rect,
+    response: &egui::Response,
+    spots: &mut Vec<RetouchSpot>,
+    aspect_ratio: f32,
+    ui_state: &mut AppUiState,
+) -> bool {
+    // control point 2 * i is the destination of spot i, and 2 * i + 1 is its source
+    let mask_edit_state = &mut ui_state.mask_edit_state;
+    let mut hovering_control_point = false;
+    for i in 0..spots.len() {
+        let spot = &mut spots[i];
+        let control_points = [
+            (spot.destination_x, spot.destination_y),
+            (spot.source_x, spot.source_y),
+        ];
+        for j in 0..control_points.len() {
+            let index = 2 * i + j;
+            let p_abs = vec2(get_absolute_pos(rect, control_points[j]));
+            if let Some(dragged_index) = mask_edit_state.dragged_control_point_index {
+                if dragged_index == index {
+                    ui.output_mut(|out| out.cursor_icon = CursorIcon::Grabbing);
+                    if response.dragged() {
+                        if let Some(hover_pos) = response.hover_pos() {
+                            let new_p = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+                            if j == 0 {
+                                spot.destination_x = new_p.0;
+                                spot.destination_y = new_p.1;
+                            } else {
+                                spot.source_x = new_p.0;
+                                spot.source_y = new_p.1;
+                            }
+                        }
+                    }
+                }
+            } else if let Some(hover_pos) = response.hover_pos() {
+                let dist = (p_abs - vec2((hover_pos.x, hover_pos.y))).length();
+                if dist < rect.width().min(rect.height()) * 0.012 {
+                    hovering_control_point = true;
+                    ui.output_mut(|out| out.cursor_icon = CursorIcon::Grab);
+                    if response.drag_started() {
+                        mask_edit_state.dragged_control_point_index = Some(index);
+                        ui_state.selected_retouch_spot_index = Some(i);
+                        ui_state.retouch_mode = spot.mode;
+                    }
+                }
+            }
+        }
+
+        let is_selected = ui_state.selected_retouch_spot_index == Some(i);
+        let color = if is_selected {
+            Color32::from_gray(250)
+        } else {
+            Color32::from_gray(160)
+        };
+        let stroke = Stroke {
+            color,
+            width: rect.width().min(rect.height()) * 0.002,
+        };
+        let radius = spot.radius * rect.height();
+        let destination = get_absolute_pos(rect, (spot.destination_x, spot.destination_y));
+        let source = get_absolute_pos(rect, (spot.source_x, spot.source_y));
+        let destination = Pos2::new(destination.0, destination.1);
+        let source = Pos2::new(source.0, source.1);
+        ui.painter().circle_stroke(destination, radius, stroke);
+        ui.painter().circle_stroke(source, radius, stroke);
+        let distance = (source - destination).length();
+        if distance > 2.0 * radius {
+            let direction = (source - destination) / distance;
+            ui.painter().line_segment(
+                [destination + direction * radius, source - direction * radius],
+                stroke,
+            );
+        }
+        draw_control_point_circle(ui, rect, (destination.x, destination.y));
+        draw_control_point_circle(ui, rect, (source.x, source.y));
+    }
+
+    if response.drag_stopped() && mask_edit_state.dragged_control_point_index.is_some() {
+        mask_edit_state.dragged_control_point_index = None;
+        return true;
+    }
+
+    if !hovering_control_point && mask_edit_state.dragged_control_point_index.is_none() {
+        if let Some(hover_pos) = response.hover_pos() {
+            if rect.contains(hover_pos) {
+                ui.output_mut(|out| out.cursor_icon = CursorIcon::Crosshair);
+                if response.clicked() {
+                    let (x, y) = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+                    spots.push(RetouchSpot::new(ui_state.retouch_mode, x, y, aspect_ratio));
+                    ui_state.selected_retouch_spot_index = Some(spots.len() - 1);
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+// returns whether pending changes to red-eye corrections should be committed
+fn red_eye_control_points(
+    ui: &mut Ui,
+    rect: egui::Rect,
+    response: &egui::Response,
+    corrections: &mut Vec<RedEyeCorrection>,
+    ui_state: &mut AppUiState,
+) -> bool {
+    let mask_edit_state = &mut ui_state.mask_edit_state;
+    let mut hovering_control_point = false;
+    for i in 0..corrections.len() {
+        let correction = &mut corrections[i];
+        let center_abs = vec2(get_absolute_pos(
+            rect,
+            (correction.center_x, correction.center_y),
+        ));
+        if let Some(dragged_index) = mask_edit_state.dragged_control_point_index {
+            if dragged_index == i {
+                ui.output_mut(|out| out.cursor_icon = CursorIcon::Grabbing);
+                if response.dragged() {
+                    if let Some(hover_pos) = response.hover_pos() {
+                        let new_p = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+                        correction.center_x = new_p.0;
+                        correction.center_y = new_p.1;
+                    }
+                }
+            }
+        } else if let Some(hover_pos) = response.hover_pos() {
+            let dist = (center_abs - vec2((hover_pos.x, hover_pos.y))).length();
+            if dist < rect.width().min(rect.height()) * 0.012 {
+                hovering_control_point = true;
+                ui.output_mut(|out| out.cursor_icon = CursorIcon::Grab);
+                if response.drag_started() {
+                    mask_edit_state.dragged_control_point_index = Some(i);
+                    ui_state.selected_red_eye_index = Some(i);
+                }
+            }
+        }
+
+        let is_selected = ui_state.selected_red_eye_index == Some(i);
+        let color = if is_selected {
+            Color32::from_gray(250)
+        } else {
+            Color32::from_gray(160)
+        };
+        let center = get_absolute_pos(rect, (correction.center_x, correction.center_y));
+        ui.painter().circle_stroke(
+            Pos2::new(center.0, center.1),
+            correction.pupil_radius * rect.height(),
+            Stroke {
+                color,
+                width: rect.width().min(rect.height()) * 0.002,
+            },
+        );
+        draw_control_point_circle(ui, rect, center);
+    }
+
+    if response.drag_stopped() && mask_edit_state.dragged_control_point_index.is_some() {
+        mask_edit_state.dragged_control_point_index = None;
+        return true;
+    }
+
+    if !hovering_control_point && mask_edit_state.dragged_control_point_index.is_none() {
+        if let Some(hover_pos) = response.hover_pos() {
+            if rect.contains(hover_pos) {
+                ui.output_mut(|out| out.cursor_icon = CursorIcon::Crosshair);
+                if response.clicked() {
+                    let (x, y) = get_relative_pos(rect, (hover_pos.x, hover_pos.y));
+                    corrections.push(RedEyeCorrection::new(x, y));
+                    ui_state.selected_red_eye_index = Some(corrections.len() - 1);
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
 fn find_edge_or_corner(pos: egui::Pos2, rect: egui::Rect) -> Option<CropDragEdgeOrCorner> {
     let mut x_selected: Option<f32> = None;
     let mut y_selected: Option<f32> = None;
@@ -882,6 +1492,37 @@ fn handle_crop_and_rotate_response(
     None
 }
 
+// returns the change of rotation (in degrees) that levels the line drawn by the user
+fn handle_straighten_response(
+    ui: &mut Ui,
+    response: &egui::Response,
+    ui_state: &mut AppUiState,
+) -> Option<f32> {
+    ui.output_mut(|out| out.cursor_icon = CursorIcon::Crosshair);
+    if response.drag_started() {
+        ui_state.crop_drag_state.straighten_line_start = response.interact_pointer_pos();
+    }
+    let start = ui_state.crop_drag_state.straighten_line_start?;
+    let end = response.interact_pointer_pos().or(response.hover_pos());
+    if response.drag_stopped() {
+        ui_state.crop_drag_state.straighten_line_start = None;
+        let end = end?;
+        // ignore accidental clicks
+        if (end - start).length() < 10.0 {
+            return None;
+        }
+        ui_state.straighten_tool_enabled = false;
+        return get_rotation_degrees_to_level_line(pos2_to_vec2(start), pos2_to_vec2(end));
+    }
+    let end = end?;
+    let width = ui.max_rect().width().min(ui.max_rect().height()) * 0.002;
+    let stroke = Stroke::new(width.max(2.0), Color32::from_rgb(50, 150, 200));
+    ui.painter().line_segment([start, end], stroke);
+    ui.painter().circle_filled(start, width.max(2.0) * 2.0, Color32::from_gray(230));
+    ui.painter().circle_filled(end, width.max(2.0) * 2.0, Color32::from_gray(230));
+    None
+}
+
 fn draw_drag_handles(ui: &mut Ui, ui_crop_rect: egui::Rect, ui_state: &mut AppUiState) {
     let thickness = ui_crop_rect.width().min(ui_crop_rect.height()) * 0.005;
     let thickness = thickness.max(3.0);
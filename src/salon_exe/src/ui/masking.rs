@@ -1,5 +1,7 @@
 
 
+use std::path::PathBuf;
+
 use eframe::{
     egui::{self, CollapsingHeader, Ui},
     egui_wgpu,
@@ -7,24 +9,38 @@ use eframe::{
 use egui_extras::{Column, TableBuilder};
 use salon_core::{
     editor::{Edit, GlobalEdit, MaskedEdit},
-    ir::{GlobalMask, LinearGradientMask, Mask, MaskPrimitive, MaskTerm, RadialGradientMask},
+    ir::{
+        BlendMode, BrushMask, ColorRangeMask, GlobalMask, ImageMask, LinearGradientMask, LuminanceRangeMask, Mask, MaskPrimitive,
+        PolygonMask,
+        MaskTerm, MaskTermMode, RadialGradientMask,
+    },
     session::Session,
+    utils::math::{remap_mask_to_new_image, remove_image_mask_terms},
 };
 
 use super::{
+    file_dialogues::file_dialogue_export_mask,
     utils::get_max_image_size,
     widgets::{EditorSlider, MaskIndicatorCallback},
-    AppUiState,
+    AppUiState, CopiedMaskedEdit, MaskImageImportTarget,
 };
 
 pub fn masking(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState, edit: &mut Edit) {
+    while let Some((path, target)) = ui_state.import_mask_image_dialog.get_picked_mask_image() {
+        add_image_mask(edit, session, ui_state, path, target);
+    }
     CollapsingHeader::new("Masking")
         .default_open(true)
         .show(ui, |ui| {
             ui.group(|ui| {
                 masks_table(ui, session, ui_state, edit);
             });
-            new_mask_menu_button(ui, edit, session, ui_state);
+            ui.horizontal(|ui| {
+                new_mask_menu_button(ui, edit, session, ui_state);
+                if ui_state.copied_masked_edit.is_some() {
+                    paste_new_mask_menu_button(ui, edit, session, ui_state);
+                }
+            });
         });
 }
 
@@ -65,7 +81,15 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
     let mut mask_to_delete: Option<usize> = None;
     let mut mask_term_to_delete: Option<(usize, usize)> = None;
     let mut mask_to_duplicate: Option<usize> = None;
+    let mut mask_to_move: Option<(usize, usize)> = None;
     let mut mask_term_to_duplicate: Option<(usize, usize)> = None;
+    let mut mask_to_export: Option<(usize, Option<usize>)> = None;
+    let mut mask_to_copy: Option<usize> = None;
+    let mut mask_to_paste_into: Option<usize> = None;
+    let mut adjustments_to_paste_into: Option<usize> = None;
+    let has_copied_masked_edit = ui_state.copied_masked_edit.is_some();
+
+    let num_masks = edit.masked_edits.len();
 
     table.body(|mut body| {
         for mask_index in 0..edit.masked_edits.len() {
@@ -81,6 +105,8 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
                 row.col(|ui| {
                     ui.with_layout(egui::Layout::left_to_right(egui::Align::LEFT), |ui| {
                         ui.horizontal_centered(|ui| {
+                            ui.checkbox(&mut edit.masked_edits[mask_index].enabled, "")
+                                .on_hover_text("Enable or disable this mask");
                             if let Some(ref result) = session
                                 .editor
                                 .current_edit_context_ref()
@@ -119,6 +145,31 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
                             mask_to_duplicate = Some(mask_index);
                             ui.close_menu();
                         }
+                        if mask_index > 0 && ui.button("Move Up").clicked() {
+                            mask_to_move = Some((mask_index, mask_index - 1));
+                            ui.close_menu();
+                        }
+                        if mask_index + 1 < num_masks && ui.button("Move Down").clicked() {
+                            mask_to_move = Some((mask_index, mask_index + 1));
+                            ui.close_menu();
+                        }
+                        if ui.button("Export Mask...").clicked() {
+                            mask_to_export = Some((mask_index, None));
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button("Copy").clicked() {
+                            mask_to_copy = Some(mask_index);
+                            ui.close_menu();
+                        }
+                        if has_copied_masked_edit && ui.button("Paste Mask").clicked() {
+                            mask_to_paste_into = Some(mask_index);
+                            ui.close_menu();
+                        }
+                        if has_copied_masked_edit && ui.button("Paste Adjustments").clicked() {
+                            adjustments_to_paste_into = Some(mask_index);
+                            ui.close_menu();
+                        }
                     });
                 });
                 if row.response().clicked() {
@@ -164,8 +215,10 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
                                     }
                                     let mut term_str =
                                         mask_primtive_type_str(&term.primitive).to_string();
-                                    if term.subtracted {
-                                        term_str += " (Subtracted)"
+                                    match term.mode {
+                                        MaskTermMode::Add => {}
+                                        MaskTermMode::Subtract => term_str += " (Subtracted)",
+                                        MaskTermMode::Intersect => term_str += " (Intersected)",
                                     }
                                     if term.inverted {
                                         term_str += " (Inverted)"
@@ -190,6 +243,10 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
                                     mask_term_to_duplicate = Some((mask_index, term_index));
                                     ui.close_menu();
                                 }
+                                if ui.button("Export Term...").clicked() {
+                                    mask_to_export = Some((mask_index, Some(term_index)));
+                                    ui.close_menu();
+                                }
                             });
                         });
                         if row.response().clicked() {
@@ -213,11 +270,26 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
                                 row.col(|_ui| {});
                             });
                         }
+                        if let MaskPrimitive::Brush(ref mut brush) = &mut term.primitive {
+                            brush_settings_rows(&mut body, row_height, ui_state, brush);
+                        }
+                        if let MaskPrimitive::Polygon(ref mut m) = &mut term.primitive {
+                            polygon_settings_rows(&mut body, row_height, m);
+                        }
+                        if let MaskPrimitive::Image(ref m) = &term.primitive {
+                            image_mask_rows(&mut body, row_height, m);
+                        }
+                        if let MaskPrimitive::LuminanceRange(ref mut m) = &mut term.primitive {
+                            luminance_range_rows(&mut body, row_height, m);
+                        }
+                        if let MaskPrimitive::ColorRange(ref mut m) = &mut term.primitive {
+                            color_range_rows(&mut body, row_height, m);
+                        }
                     }
                 }
             }
 
-            // row for adding/subtracting terms
+            // row for adding/subtracting/intersecting terms
             if is_selected {
                 body.row(row_height, |mut row| {
                     row.col(|_ui| {});
@@ -229,7 +301,7 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
                                     ui_state,
                                     &mut edit.masked_edits[mask_index].mask,
                                     mask_aspect_ratio,
-                                    false,
+                                    MaskTermMode::Add,
                                 );
                             }
                             new_mask_term_menu_button(
@@ -237,16 +309,52 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
                                 ui_state,
                                 &mut edit.masked_edits[mask_index].mask,
                                 mask_aspect_ratio,
-                                true,
+                                MaskTermMode::Subtract,
                             );
+                            if !edit.masked_edits[mask_index].mask.is_singe_global() {
+                                new_mask_term_menu_button(
+                                    ui,
+                                    ui_state,
+                                    &mut edit.masked_edits[mask_index].mask,
+                                    mask_aspect_ratio,
+                                    MaskTermMode::Intersect,
+                                );
+                            }
                         });
                     });
                     row.col(|_ui| {});
                 });
             }
+
+            // rows for how the masked edit is applied
+            if is_selected {
+                masked_edit_settings_rows(&mut body, row_height, &mut edit.masked_edits[mask_index]);
+            }
         }
     });
 
+    if let Some((m, t)) = mask_to_export {
+        file_dialogue_export_mask(session, m, t);
+    }
+
+    if let Some(m) = mask_to_copy {
+        ui_state.copied_masked_edit = Some(CopiedMaskedEdit {
+            masked_edit: edit.masked_edits[m].clone(),
+            aspect_ratio: mask_aspect_ratio,
+            source: session.editor.current_image_identifier(),
+        });
+    } else if let Some(m) = mask_to_paste_into {
+        if let Some(mask) = copied_mask_for_current_image(session, ui_state, edit) {
+            edit.masked_edits[m].mask = mask;
+            ui_state.selected_mask_term_index = None
+        }
+    } else if let Some(m) = adjustments_to_paste_into {
+        let copied = &ui_state.copied_masked_edit.as_ref().unwrap().masked_edit;
+        edit.masked_edits[m].edit = copied.edit.clone();
+        edit.masked_edits[m].opacity = copied.opacity;
+        edit.masked_edits[m].blend_mode = copied.blend_mode;
+    }
+
     if let Some((m, t)) = mask_term_to_delete {
         edit.masked_edits[m].mask.terms.remove(t);
         if edit.masked_edits[m].mask.terms.is_empty() {
@@ -262,20 +370,304 @@ pub fn masks_table(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState
         let term = edit.masked_edits[m].mask.terms[t].clone();
         edit.masked_edits[m].mask.terms.insert(t + 1, term);
         ui_state.selected_mask_term_index = Some(t + 1);
+    } else if let Some((from, to)) = mask_to_move {
+        edit.masked_edits.swap(from, to);
+        ui_state.selected_mask_index = to;
+        ui_state.selected_mask_term_index = None
     } else if let Some(m) = mask_to_duplicate {
         let mask = edit.masked_edits[m].mask.clone();
         edit.masked_edits.insert(
             m + 1,
-            MaskedEdit {
+            MaskedEdit::new(
                 mask,
-                edit: GlobalEdit::new(),
-                name: edit.masked_edits[m].name.clone() + " Copy",
-            },
+                GlobalEdit::new(),
+                edit.masked_edits[m].name.clone() + " Copy",
+            ),
         );
         ui_state.selected_mask_index = m + 1;
     }
 }
 
+fn brush_settings_rows(
+    body: &mut egui_extras::TableBody,
+    row_height: f32,
+    ui_state: &mut AppUiState,
+    brush: &mut BrushMask,
+) {
+    let brush_state = &mut ui_state.mask_edit_state;
+    body.row(row_height, |mut row| {
+        row.set_selected(true);
+        row.col(|_ui| {});
+        row.col(|ui| {
+            ui.horizontal_centered(|ui| {
+                ui.separator();
+                ui.selectable_value(&mut brush_state.brush_erase, false, "Paint");
+                ui.selectable_value(&mut brush_state.brush_erase, true, "Erase");
+                ui.separator();
+                if ui
+                    .add_enabled(!brush.strokes.is_empty(), egui::Button::new("Clear"))
+                    .clicked()
+                {
+                    brush.strokes.clear();
+                }
+            });
+        });
+        row.col(|_ui| {});
+    });
+    let size = &mut brush_state.brush_size;
+    mask_term_slider_row(body, row_height, size, 0.002..=0.3, 0.05, "Size");
+    let feather = &mut brush_state.brush_feather;
+    mask_term_slider_row(body, row_height, feather, 0.0..=100.0, 50.0, "Feather");
+    let flow = &mut brush_state.brush_flow;
+    mask_term_slider_row(body, row_height, flow, 1.0..=100.0, 100.0, "Flow");
+}
+
+fn polygon_settings_rows(
+    body: &mut egui_extras::TableBody,
+    row_height: f32,
+    polygon: &mut PolygonMask,
+) {
+    body.row(row_height, |mut row| {
+        row.set_selected(true);
+        row.col(|_ui| {});
+        row.col(|ui| {
+            ui.horizontal_centered(|ui| {
+                ui.separator();
+                if polygon.vertices.len() < 3 {
+                    ui.label("Click on the image to add vertices");
+                } else {
+                    ui.label(format!("{} vertices", polygon.vertices.len()));
+                }
+                ui.separator();
+                if ui
+                    .add_enabled(!polygon.vertices.is_empty(), egui::Button::new("Clear"))
+                    .clicked()
+                {
+                    polygon.vertices.clear();
+                }
+            });
+        });
+        row.col(|_ui| {});
+    });
+    let feather = &mut polygon.feather;
+    mask_term_slider_row(body, row_height, feather, 0.0..=0.1, 0.005, "Feather");
+}
+
+fn image_mask_rows(body: &mut egui_extras::TableBody, row_height: f32, image_mask: &ImageMask) {
+    body.row(row_height, |mut row| {
+        row.set_selected(true);
+        row.col(|_ui| {});
+        row.col(|ui| {
+            ui.horizontal_centered(|ui| {
+                ui.separator();
+                let file_name = image_mask
+                    .path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("");
+                ui.label(file_name)
+                    .on_hover_text(image_mask.path.to_str().unwrap_or(""));
+            });
+        });
+        row.col(|_ui| {});
+    });
+}
+
+fn color_range_rows(
+    body: &mut egui_extras::TableBody,
+    row_height: f32,
+    color_range: &mut ColorRangeMask,
+) {
+    body.row(row_height, |mut row| {
+        row.set_selected(true);
+        row.col(|_ui| {});
+        row.col(|ui| {
+            ui.horizontal_centered(|ui| {
+                ui.separator();
+                if color_range.reference_colors.is_empty() {
+                    ui.label("Click on the image to sample colors");
+                }
+                let mut color_to_remove = None;
+                for (i, c) in color_range.reference_colors.iter().enumerate() {
+                    let color = egui::Color32::from(egui::Rgba::from_rgb(c[0], c[1], c[2]));
+                    let size = egui::Vec2::splat(row_height * 0.6);
+                    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+                    ui.painter().rect_filled(rect, 2.0, color);
+                    if response.on_hover_text("Click to remove").clicked() {
+                        color_to_remove = Some(i);
+                    }
+                }
+                if let Some(i) = color_to_remove {
+                    color_range.reference_colors.remove(i);
+                }
+            });
+        });
+        row.col(|_ui| {});
+    });
+    let tolerance = &mut color_range.tolerance;
+    mask_term_slider_row(body, row_height, tolerance, 1.0..=100.0, 20.0, "Tolerance");
+}
+
+fn luminance_range_rows(
+    body: &mut egui_extras::TableBody,
+    row_height: f32,
+    luminance_range: &mut LuminanceRangeMask,
+) {
+    let m = luminance_range;
+    mask_term_slider_row(body, row_height, &mut m.low, 0.0..=100.0, 60.0, "Low");
+    mask_term_slider_row(body, row_height, &mut m.high, 0.0..=100.0, 100.0, "High");
+    let low_feather = &mut m.low_feather;
+    mask_term_slider_row(body, row_height, low_feather, 0.0..=50.0, 10.0, "Low Feather");
+    let high_feather = &mut m.high_feather;
+    mask_term_slider_row(body, row_height, high_feather, 0.0..=50.0, 10.0, "High Feather");
+    if m.high < m.low {
+        m.high = m.low;
+    }
+}
+
+fn masked_edit_settings_rows(
+    body: &mut egui_extras::TableBody,
+    row_height: f32,
+    masked_edit: &mut MaskedEdit,
+) {
+    body.row(row_height, |mut row| {
+        row.col(|_ui| {});
+        row.col(|ui| {
+            ui.horizontal_centered(|ui| {
+                ui.add(
+                    EditorSlider::new(&mut masked_edit.opacity, 0.0..=100.0)
+                        .double_click_reset_value(100.0)
+                        .text("Opacity"),
+                );
+            });
+        });
+        row.col(|_ui| {});
+    });
+    body.row(row_height, |mut row| {
+        row.col(|_ui| {});
+        row.col(|ui| {
+            ui.horizontal_centered(|ui| {
+                ui.label("Blend ");
+                let blend_mode = &mut masked_edit.blend_mode;
+                ui.selectable_value(blend_mode, BlendMode::Normal, "Normal");
+                ui.selectable_value(blend_mode, BlendMode::Luminosity, "Luminosity");
+                ui.selectable_value(blend_mode, BlendMode::Color, "Color");
+            });
+        });
+        row.col(|_ui| {});
+    });
+    // edge refinement of the combined mask
+    if !masked_edit.mask.is_singe_global() {
+        body.row(row_height, |mut row| {
+            row.col(|_ui| {});
+            row.col(|ui| {
+                ui.horizontal_centered(|ui| {
+                    ui.add(
+                        EditorSlider::new(&mut masked_edit.mask.refine_edges, 0.0..=100.0)
+                            .double_click_reset_value(0.0)
+                            .text("Refine Edges"),
+                    );
+                });
+            });
+            row.col(|_ui| {});
+        });
+    }
+}
+
+fn mask_term_slider_row(
+    body: &mut egui_extras::TableBody,
+    row_height: f32,
+    value: &mut f32,
+    range: std::ops::RangeInclusive<f32>,
+    reset_value: f32,
+    text: &str,
+) {
+    body.row(row_height, |mut row| {
+        row.set_selected(true);
+        row.col(|_ui| {});
+        row.col(|ui| {
+            ui.horizontal_centered(|ui| {
+                ui.separator();
+                ui.add(
+                    EditorSlider::new(value, range)
+                        .double_click_reset_value(reset_value as f64)
+                        .text(text),
+                );
+            });
+        });
+        row.col(|_ui| {});
+    });
+}
+
+fn paste_new_mask_menu_button(
+    ui: &mut Ui,
+    edit: &mut Edit,
+    session: &mut Session,
+    ui_state: &mut AppUiState,
+) {
+    ui.menu_button("Paste as New Mask", |ui| {
+        let mut paste_adjustments = None;
+        if ui.button("Mask and Adjustments").clicked() {
+            paste_adjustments = Some(true);
+        }
+        if ui.button("Mask Only").clicked() {
+            paste_adjustments = Some(false);
+        }
+        if let Some(paste_adjustments) = paste_adjustments {
+            let Some(mask) = copied_mask_for_current_image(session, ui_state, edit) else {
+                ui.close_menu();
+                return;
+            };
+            let copied = &ui_state.copied_masked_edit.as_ref().unwrap().masked_edit;
+            let mut masked_edit = MaskedEdit::new(mask, GlobalEdit::new(), copied.name.clone());
+            if paste_adjustments {
+                masked_edit.edit = copied.edit.clone();
+                masked_edit.opacity = copied.opacity;
+                masked_edit.blend_mode = copied.blend_mode;
+            }
+            edit.masked_edits.push(masked_edit);
+            ui_state.selected_mask_index = edit.masked_edits.len() - 1;
+            ui_state.selected_mask_term_index = None;
+            ui.close_menu();
+        }
+    });
+}
+
+// the copied mask, with its geometry adapted to the current image.
+// none if it only consists of image masks, which can't be pasted into another image
+fn copied_mask_for_current_image(
+    session: &Session,
+    ui_state: &AppUiState,
+    edit: &Edit,
+) -> Option<Mask> {
+    let copied = ui_state
+        .copied_masked_edit
+        .as_ref()
+        .expect("expecting a copied masked edit");
+    let image_aspect_ratio = session
+        .editor
+        .current_edit_context_ref()
+        .expect("expecting an input image")
+        .input_image()
+        .aspect_ratio();
+    let mut mask = copied.masked_edit.mask.clone();
+    // virtual copies share the image of their original
+    let current_original = session
+        .editor
+        .current_image_identifier()
+        .map(|identifier| identifier.get_original());
+    let source_original = copied
+        .source
+        .as_ref()
+        .map(|identifier| identifier.get_original());
+    if source_original != current_original && !remove_image_mask_terms(&mut mask) {
+        log::warn!("image masks can't be pasted into another image");
+        return None;
+    }
+    remap_mask_to_new_image(&mut mask, copied.aspect_ratio, image_aspect_ratio, edit);
+    Some(mask)
+}
+
 fn new_mask_menu_button(
     ui: &mut Ui,
     edit: &mut Edit,
@@ -308,6 +700,45 @@ fn new_mask_menu_button(
             );
             ui.close_menu();
         }
+        if ui.button("Brush").clicked() {
+            add_single_primitive_masked_edit(
+                edit,
+                ui_state,
+                MaskPrimitive::Brush(BrushMask::default()),
+            );
+            ui.close_menu();
+        }
+        if ui.button("Polygon").clicked() {
+            add_single_primitive_masked_edit(
+                edit,
+                ui_state,
+                MaskPrimitive::Polygon(PolygonMask::default()),
+            );
+            ui.close_menu();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Image...").clicked() {
+            ui_state
+                .import_mask_image_dialog
+                .open_pick_mask_image(MaskImageImportTarget::NewMask);
+            ui.close_menu();
+        }
+        if ui.button("Luminance Range").clicked() {
+            add_single_primitive_masked_edit(
+                edit,
+                ui_state,
+                MaskPrimitive::LuminanceRange(LuminanceRangeMask::default()),
+            );
+            ui.close_menu();
+        }
+        if ui.button("Color Range").clicked() {
+            add_single_primitive_masked_edit(
+                edit,
+                ui_state,
+                MaskPrimitive::ColorRange(ColorRangeMask::default()),
+            );
+            ui.close_menu();
+        }
         if ui.button("Global").clicked() {
             add_single_primitive_masked_edit(
                 edit,
@@ -324,15 +755,19 @@ fn new_mask_term_menu_button(
     ui_state: &mut AppUiState,
     mask: &mut Mask,
     aspect_ratio: f32,
-    subtracted: bool,
+    mode: MaskTermMode,
 ) {
-    let button_name = if subtracted { "Subtract" } else { "Add" };
+    let button_name = match mode {
+        MaskTermMode::Add => "Add",
+        MaskTermMode::Subtract => "Subtract",
+        MaskTermMode::Intersect => "Intersect",
+    };
     ui.menu_button(button_name, |ui| {
         if ui.button("Radial Gradient").clicked() {
             mask.terms.push(MaskTerm {
                 primitive: MaskPrimitive::RadialGradient(RadialGradientMask::default(aspect_ratio)),
                 inverted: false,
-                subtracted,
+                mode,
             });
             ui_state.selected_mask_term_index = Some(mask.terms.len() - 1);
             ui.close_menu();
@@ -341,7 +776,54 @@ fn new_mask_term_menu_button(
             mask.terms.push(MaskTerm {
                 primitive: MaskPrimitive::LinearGradient(LinearGradientMask::default()),
                 inverted: false,
-                subtracted,
+                mode,
+            });
+            ui_state.selected_mask_term_index = Some(mask.terms.len() - 1);
+            ui.close_menu();
+        }
+        if ui.button("Brush").clicked() {
+            mask.terms.push(MaskTerm {
+                primitive: MaskPrimitive::Brush(BrushMask::default()),
+                inverted: false,
+                mode,
+            });
+            ui_state.selected_mask_term_index = Some(mask.terms.len() - 1);
+            ui.close_menu();
+        }
+        if ui.button("Polygon").clicked() {
+            mask.terms.push(MaskTerm {
+                primitive: MaskPrimitive::Polygon(PolygonMask::default()),
+                inverted: false,
+                mode,
+            });
+            ui_state.selected_mask_term_index = Some(mask.terms.len() - 1);
+            ui.close_menu();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Image...").clicked() {
+            let target = MaskImageImportTarget::NewTerm {
+                mask_index: ui_state.selected_mask_index,
+                mode,
+            };
+            ui_state
+                .import_mask_image_dialog
+                .open_pick_mask_image(target);
+            ui.close_menu();
+        }
+        if ui.button("Luminance Range").clicked() {
+            mask.terms.push(MaskTerm {
+                primitive: MaskPrimitive::LuminanceRange(LuminanceRangeMask::default()),
+                inverted: false,
+                mode,
+            });
+            ui_state.selected_mask_term_index = Some(mask.terms.len() - 1);
+            ui.close_menu();
+        }
+        if ui.button("Color Range").clicked() {
+            mask.terms.push(MaskTerm {
+                primitive: MaskPrimitive::ColorRange(ColorRangeMask::default()),
+                inverted: false,
+                mode,
             });
             ui_state.selected_mask_term_index = Some(mask.terms.len() - 1);
             ui.close_menu();
@@ -356,26 +838,67 @@ fn add_single_primitive_masked_edit(
 ) {
     let added_index = edit.masked_edits.len();
     let name = "Mask ".to_string() + added_index.to_string().as_str();
-    edit.masked_edits.push(MaskedEdit {
-        mask: Mask {
+    edit.masked_edits.push(MaskedEdit::new(
+        Mask {
             terms: vec![MaskTerm {
                 primitive,
                 inverted: false,
-                subtracted: false,
+                mode: MaskTermMode::Add,
             }],
+            refine_edges: 0.0,
         },
-        edit: GlobalEdit::new(),
+        GlobalEdit::new(),
         name,
-    });
+    ));
     ui_state.selected_mask_index = added_index;
     ui_state.selected_mask_term_index = Some(0);
 }
 
+fn add_image_mask(
+    edit: &mut Edit,
+    session: &mut Session,
+    ui_state: &mut AppUiState,
+    path: PathBuf,
+    target: MaskImageImportTarget,
+) {
+    let image_mask = match session.editor.import_image_mask(path) {
+        Ok(image_mask) => image_mask,
+        Err(e) => {
+            log::error!("{}", e);
+            return;
+        }
+    };
+    let primitive = MaskPrimitive::Image(image_mask);
+    match target {
+        MaskImageImportTarget::NewMask => {
+            add_single_primitive_masked_edit(edit, ui_state, primitive);
+        }
+        MaskImageImportTarget::NewTerm { mask_index, mode } => {
+            if mask_index >= edit.masked_edits.len() {
+                return;
+            }
+            let mask = &mut edit.masked_edits[mask_index].mask;
+            mask.terms.push(MaskTerm {
+                primitive,
+                inverted: false,
+                mode,
+            });
+            ui_state.selected_mask_index = mask_index;
+            ui_state.selected_mask_term_index = Some(mask.terms.len() - 1);
+        }
+    }
+}
+
 fn mask_primtive_type_str(primitive: &MaskPrimitive) -> &str {
     match primitive {
         MaskPrimitive::Global(_) => "Global",
         MaskPrimitive::RadialGradient(_) => "Radial",
         MaskPrimitive::LinearGradient(_) => "Linear",
+        MaskPrimitive::Brush(_) => "Brush",
+        MaskPrimitive::Polygon(_) => "Polygon",
+        MaskPrimitive::LuminanceRange(_) => "Luminance",
+        MaskPrimitive::ColorRange(_) => "Color",
+        MaskPrimitive::Image(_) => "Image",
     }
 }
 
@@ -24,6 +24,8 @@ pub fn menu_bar(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
             ui.with_layout(egui::Layout::right_to_left(egui::Align::RIGHT), |ui| {
                 ui.checkbox(&mut ui_state.show_grid, "Show Grid");
                 ui.separator();
+                ui.checkbox(&mut ui_state.show_loupe, "Show Loupe (L)");
+                ui.separator();
                 ui.checkbox(&mut ui_state.show_comparison, "Show Comparison");
             });
         }
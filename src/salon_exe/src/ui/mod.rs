@@ -14,6 +14,7 @@ mod export_panel;
 mod file_menu;
 mod framing;
 mod histogram;
+mod history;
 mod keyboard_response;
 mod library_albums_browser;
 mod library_images_browser;
@@ -22,7 +23,11 @@ mod light_adjust;
 mod main_image;
 mod masking;
 mod menu_bar;
+mod paste_settings_dialog;
+mod presets;
+mod retouch;
 mod rotate_and_crop;
+mod scopes;
 
 mod utils;
 
@@ -40,6 +45,7 @@ pub use effects::*;
 pub use file_menu::*;
 pub use framing::*;
 pub use histogram::*;
+pub use history::*;
 pub use keyboard_response::*;
 pub use library_albums_browser::*;
 pub use library_images_browser::*;
@@ -48,4 +54,8 @@ pub use light_adjust::*;
 pub use main_image::*;
 pub use masking::*;
 pub use menu_bar::*;
+pub use paste_settings_dialog::*;
+pub use presets::*;
+pub use retouch::*;
 pub use rotate_and_crop::*;
+pub use scopes::*;
@@ -0,0 +1,120 @@
+use eframe::egui;
+use salon_core::{editor::EditSections, library::LibraryImageIdentifier, session::Session};
+
+use super::{utils::legalize_ui_state, AppUiState, CopiedEdit, PasteSettingsDialogState};
+
+pub fn paste_settings_dialog(ctx: &egui::Context, session: &mut Session, ui_state: &mut AppUiState) {
+    let Some(ref mut dialog) = ui_state.paste_settings_dialog else {
+        return;
+    };
+
+    let mut open = true;
+    let mut confirmed = false;
+    egui::Window::new(dialog.title.clone())
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let sections = &mut dialog.sections;
+            ui.checkbox(&mut sections.light, "Light");
+            ui.checkbox(&mut sections.color, "Color");
+            ui.checkbox(&mut sections.curves, "Curves");
+            ui.checkbox(&mut sections.effects, "Effects");
+            ui.checkbox(&mut sections.geometry, "Geometry");
+            ui.checkbox(&mut sections.masks, "Masks");
+            ui.horizontal(|ui| {
+                if ui.button("Select All").clicked() {
+                    *sections = EditSections::all();
+                }
+                if ui.button("Select None").clicked() {
+                    *sections = EditSections::none();
+                }
+            });
+            ui.separator();
+            ui.add(
+                egui::Slider::new(&mut dialog.amount, 0.0..=100.0)
+                    .suffix("%")
+                    .text("Amount"),
+            );
+            ui.separator();
+            let num_targets = dialog.targets.len();
+            let confirm_text = if num_targets == 1 {
+                "Apply to 1 Image".to_owned()
+            } else {
+                format!("Apply to {} Images", num_targets)
+            };
+            if ui.button(confirm_text).clicked() {
+                confirmed = true;
+            }
+        });
+
+    if confirmed {
+        let dialog = ui_state.paste_settings_dialog.take().unwrap();
+        session.sync_edit_sections(
+            &dialog.source.edit,
+            dialog.source.image_aspect_ratio,
+            &dialog.sections,
+            dialog.amount,
+            &dialog.targets,
+        );
+        if let Some(context) = session.editor.current_edit_context_ref() {
+            legalize_ui_state(ui_state, context.current_edit_ref());
+        }
+    } else if !open {
+        ui_state.paste_settings_dialog = None;
+    }
+}
+
+pub fn copy_settings_action(session: &mut Session, ui_state: &mut AppUiState) {
+    if let Some(context) = session.editor.current_edit_context_ref() {
+        ui_state.copied_edit = Some(CopiedEdit {
+            edit: context.current_edit_ref().clone(),
+            image_aspect_ratio: context.input_image().aspect_ratio(),
+        });
+    }
+}
+
+// pastes the copied settings into the targets
+pub fn open_paste_settings_dialog(ui_state: &mut AppUiState, targets: Vec<LibraryImageIdentifier>) {
+    let Some(ref copied) = ui_state.copied_edit else {
+        return;
+    };
+    ui_state.paste_settings_dialog = Some(PasteSettingsDialogState {
+        title: "Paste Settings".to_owned(),
+        source: CopiedEdit {
+            edit: copied.edit.clone(),
+            image_aspect_ratio: copied.image_aspect_ratio,
+        },
+        targets,
+        sections: EditSections::all(),
+        amount: 100.0,
+    });
+}
+
+// copies the settings of the current image into the other targets
+pub fn open_sync_settings_dialog(
+    session: &mut Session,
+    ui_state: &mut AppUiState,
+    targets: Vec<LibraryImageIdentifier>,
+) {
+    let Some(current_identifier) = session.editor.current_image_identifier() else {
+        return;
+    };
+    let Some(context) = session.editor.current_edit_context_ref() else {
+        return;
+    };
+    let targets = targets
+        .into_iter()
+        .filter(|identifier| *identifier != current_identifier)
+        .collect();
+    ui_state.paste_settings_dialog = Some(PasteSettingsDialogState {
+        title: "Sync Settings".to_owned(),
+        source: CopiedEdit {
+            edit: context.current_edit_ref().clone(),
+            image_aspect_ratio: context.input_image().aspect_ratio(),
+        },
+        targets,
+        sections: EditSections::all(),
+        amount: 100.0,
+    });
+}
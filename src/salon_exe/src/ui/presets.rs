@@ -0,0 +1,189 @@
+use eframe::egui::{self, CollapsingHeader, Ui};
+
+use salon_core::{
+    editor::AppliedLook,
+    presets::{Preset, PresetLibrary},
+    session::Session,
+};
+
+use super::{
+    file_dialogues::file_dialogue_export_preset, utils::legalize_ui_state, widgets::EditorSlider,
+    AppUiState,
+};
+
+pub fn presets(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    while let Some(json_str) = ui_state.import_preset_dialog.get_picked_preset() {
+        ui_state.preset_import_error_msg = session
+            .presets
+            .import_preset(json_str.as_str(), PresetLibrary::default_group_name())
+            .err();
+    }
+
+    let mut hovered_preset = None;
+    let mut clicked_preset = None;
+    let mut preset_to_delete = None;
+    let mut preset_to_export = None;
+
+    applied_look_amount(ui, session, ui_state);
+
+    CollapsingHeader::new("Presets")
+        .default_open(true)
+        .show(ui, |ui| {
+            if session.presets.groups().is_empty() {
+                ui.label("No presets yet");
+            }
+            for (group_index, group) in session.presets.groups().iter().enumerate() {
+                CollapsingHeader::new(group.name.as_str())
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for (preset_index, preset) in group.presets.iter().enumerate() {
+                            let indices = (group_index, preset_index);
+                            let response = ui.selectable_label(false, preset.name.as_str());
+                            // previewing the preset as it is hovered
+                            if response.hovered() {
+                                hovered_preset = Some(indices);
+                            }
+                            if response.clicked() {
+                                clicked_preset = Some(indices);
+                            }
+                            response.context_menu(|ui| {
+                                if ui.button("Export...").clicked() {
+                                    preset_to_export = Some(indices);
+                                    ui.close_menu();
+                                }
+                                if ui.button("Delete").clicked() {
+                                    preset_to_delete = Some(indices);
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                    });
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Import Presets...").clicked() {
+                ui_state.import_preset_dialog.open_pick_presets();
+            }
+            if let Some(ref msg) = ui_state.preset_import_error_msg {
+                ui.colored_label(egui::Color32::RED, msg);
+            }
+        });
+
+    save_preset(ui, session, ui_state);
+
+    if let Some((g, p)) = clicked_preset {
+        let preset = session.presets.groups()[g].presets[p].clone();
+        if session.editor.apply_preset(&preset, 100.0) {
+            session.update_thumbnail_for_current_image();
+        }
+        if let Some(context) = session.editor.current_edit_context_ref() {
+            legalize_ui_state(ui_state, context.current_edit_ref());
+        }
+        ui_state.previewing_preset = false;
+    } else if let Some((g, p)) = hovered_preset {
+        let preview_edit = session
+            .editor
+            .get_edit_with_preset(&session.presets.groups()[g].presets[p], 100.0);
+        session.editor.update_transient_edit(preview_edit, true);
+        ui_state.previewing_preset = true;
+    } else if ui_state.previewing_preset {
+        // nothing is hovered, so stop previewing
+        let current_edit = session
+            .editor
+            .current_edit_context_ref()
+            .unwrap()
+            .current_edit_ref()
+            .clone();
+        session.editor.update_transient_edit(current_edit, true);
+        ui_state.previewing_preset = false;
+    }
+
+    if let Some((g, p)) = preset_to_export {
+        file_dialogue_export_preset(&session.presets.groups()[g].presets[p]);
+    }
+    if let Some((g, p)) = preset_to_delete {
+        session.presets.remove_preset(g, p);
+    }
+}
+
+// the strength of the last applied preset or pasted settings, until the adjustments are changed by other means
+fn applied_look_amount(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    if ui_state.previewing_preset {
+        return;
+    }
+    let transient_edit = session
+        .editor
+        .current_edit_context_ref()
+        .unwrap()
+        .transient_edit_ref()
+        .clone();
+    if !AppliedLook::is_adjustable(&transient_edit) {
+        return;
+    }
+    let look = transient_edit.applied_look.as_ref().unwrap();
+    let mut amount = look.amount;
+    let includes_masked_edits = look.includes_masked_edits;
+    CollapsingHeader::new("Applied Look")
+        .default_open(true)
+        .show(ui, |ui| {
+            ui.spacing_mut().slider_width = ui.available_width() * 0.6;
+            let response = ui.add(
+                EditorSlider::new(&mut amount, 0.0..=100.0)
+                    .double_click_reset_value(100.0)
+                    .suffix("%")
+                    .text("Amount"),
+            );
+            if includes_masked_edits {
+                ui.weak("Masks of the look are applied in full");
+            }
+            if response.changed() {
+                let edit = AppliedLook::with_amount(&transient_edit, amount);
+                session.editor.update_transient_edit(edit, true);
+            }
+            // the edit remains transient while the slider is being dragged
+            if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                if session.editor.commit_transient_edit(false) {
+                    session.update_thumbnail_for_current_image();
+                }
+            }
+        });
+}
+
+fn save_preset(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    CollapsingHeader::new("Save Preset")
+        .default_open(true)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name ");
+                ui.text_edit_singleline(&mut ui_state.new_preset_name);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Group ");
+                ui.text_edit_singleline(&mut ui_state.new_preset_group);
+            });
+            let sections = &mut ui_state.new_preset_sections;
+            ui.checkbox(&mut sections.light, "Light");
+            ui.checkbox(&mut sections.color, "Color");
+            ui.checkbox(&mut sections.curves, "Curves");
+            ui.checkbox(&mut sections.effects, "Effects");
+            ui.checkbox(&mut sections.masks, "Masks");
+
+            let can_save = !ui_state.new_preset_name.trim().is_empty()
+                && !ui_state.new_preset_group.trim().is_empty();
+            if ui
+                .add_enabled(can_save, egui::Button::new("Save"))
+                .clicked()
+            {
+                let context = session.editor.current_edit_context_ref().unwrap();
+                let preset = Preset::new(
+                    ui_state.new_preset_name.trim().to_owned(),
+                    context.current_edit_ref(),
+                    context.input_image().aspect_ratio(),
+                    ui_state.new_preset_sections,
+                );
+                session
+                    .presets
+                    .add_preset(ui_state.new_preset_group.trim(), preset);
+                ui_state.new_preset_name.clear();
+            }
+        });
+}
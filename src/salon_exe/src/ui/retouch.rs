@@ -0,0 +1,145 @@
+use eframe::egui::{self, Ui};
+
+use salon_core::{editor::Edit, ir::RetouchMode, session::Session};
+
+use super::{widgets::EditorSlider, AppUiState, RetouchTool};
+
+pub fn retouch(ui: &mut Ui, _session: &mut Session, ui_state: &mut AppUiState, edit: &mut Edit) {
+    ui.spacing_mut().slider_width = ui.available_width() * 0.6;
+
+    ui.horizontal(|ui| {
+        ui.label("Tool ");
+        ui.selectable_value(&mut ui_state.retouch_tool, RetouchTool::Spot, "Spot Removal");
+        ui.selectable_value(&mut ui_state.retouch_tool, RetouchTool::RedEye, "Red Eye");
+    });
+
+    ui.separator();
+
+    match ui_state.retouch_tool {
+        RetouchTool::Spot => retouch_spots(ui, ui_state, edit),
+        RetouchTool::RedEye => red_eye_corrections(ui, ui_state, edit),
+    }
+}
+
+fn retouch_spots(ui: &mut Ui, ui_state: &mut AppUiState, edit: &mut Edit) {
+    if let Some(index) = ui_state.selected_retouch_spot_index {
+        if index >= edit.retouch_spots.len() {
+            ui_state.selected_retouch_spot_index = None;
+        }
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Mode ");
+        ui.selectable_value(&mut ui_state.retouch_mode, RetouchMode::Heal, "Heal");
+        ui.selectable_value(&mut ui_state.retouch_mode, RetouchMode::Clone, "Clone");
+    });
+    ui.label("Click on the image to add a spot. Drag the circles to move the spot or its source.");
+
+    ui.separator();
+
+    let mut spot_to_delete = None;
+    for i in 0..edit.retouch_spots.len() {
+        let is_selected = ui_state.selected_retouch_spot_index == Some(i);
+        ui.horizontal(|ui| {
+            let mode_str = match edit.retouch_spots[i].mode {
+                RetouchMode::Heal => "Heal",
+                RetouchMode::Clone => "Clone",
+            };
+            let text = format!("Spot {} ({})", i + 1, mode_str);
+            if ui.selectable_label(is_selected, text).clicked() {
+                ui_state.selected_retouch_spot_index = Some(i);
+                ui_state.retouch_mode = edit.retouch_spots[i].mode;
+            }
+            if ui.button("Delete").clicked() {
+                spot_to_delete = Some(i);
+            }
+        });
+    }
+
+    if let Some(i) = spot_to_delete {
+        edit.retouch_spots.remove(i);
+        ui_state.selected_retouch_spot_index = None;
+    }
+
+    if let Some(index) = ui_state.selected_retouch_spot_index {
+        ui.separator();
+        let spot = &mut edit.retouch_spots[index];
+        spot.mode = ui_state.retouch_mode;
+        ui.add(
+            EditorSlider::new(&mut spot.radius, 0.005..=0.25)
+                .double_click_reset_value(0.03)
+                .text("Size"),
+        );
+        ui.add(
+            EditorSlider::new(&mut spot.feather, 0.0..=100.0)
+                .double_click_reset_value(50.0)
+                .text("Feather"),
+        );
+        ui.add(
+            EditorSlider::new(&mut spot.opacity, 0.0..=100.0)
+                .double_click_reset_value(100.0)
+                .text("Opacity"),
+        );
+    }
+
+    if !edit.retouch_spots.is_empty() {
+        ui.separator();
+        if ui.add(egui::Button::new("Remove All Spots")).clicked() {
+            edit.retouch_spots.clear();
+            ui_state.selected_retouch_spot_index = None;
+        }
+    }
+}
+
+fn red_eye_corrections(ui: &mut Ui, ui_state: &mut AppUiState, edit: &mut Edit) {
+    if let Some(index) = ui_state.selected_red_eye_index {
+        if index >= edit.red_eye_corrections.len() {
+            ui_state.selected_red_eye_index = None;
+        }
+    }
+
+    ui.label("Click on an eye to remove red-eye. Drag the circle to move it.");
+
+    ui.separator();
+
+    let mut correction_to_delete = None;
+    for i in 0..edit.red_eye_corrections.len() {
+        let is_selected = ui_state.selected_red_eye_index == Some(i);
+        ui.horizontal(|ui| {
+            if ui.selectable_label(is_selected, format!("Eye {}", i + 1)).clicked() {
+                ui_state.selected_red_eye_index = Some(i);
+            }
+            if ui.button("Delete").clicked() {
+                correction_to_delete = Some(i);
+            }
+        });
+    }
+
+    if let Some(i) = correction_to_delete {
+        edit.red_eye_corrections.remove(i);
+        ui_state.selected_red_eye_index = None;
+    }
+
+    if let Some(index) = ui_state.selected_red_eye_index {
+        ui.separator();
+        let correction = &mut edit.red_eye_corrections[index];
+        ui.add(
+            EditorSlider::new(&mut correction.pupil_radius, 0.002..=0.1)
+                .double_click_reset_value(0.02)
+                .text("Pupil Size"),
+        );
+        ui.add(
+            EditorSlider::new(&mut correction.darken, 0.0..=100.0)
+                .double_click_reset_value(50.0)
+                .text("Darken"),
+        );
+    }
+
+    if !edit.red_eye_corrections.is_empty() {
+        ui.separator();
+        if ui.add(egui::Button::new("Remove All")).clicked() {
+            edit.red_eye_corrections.clear();
+            ui_state.selected_red_eye_index = None;
+        }
+    }
+}
@@ -9,7 +9,9 @@ use salon_core::{
     utils::{
         math::{
             approximate_aspect_ratio, get_cropped_image_dimensions,
-            get_max_crop_rect_with_aspect_ratio, handle_new_crop_rect, handle_new_rotation, reduced_aspect_ratio,
+            get_deviation_from_right_angle, get_max_crop_rect_with_aspect_ratio,
+            handle_new_crop_rect, handle_new_rotation, reduced_aspect_ratio,
+            wrap_rotation_degrees,
         },
         rectangle::Rectangle,
     },
@@ -20,7 +22,7 @@ use super::{widgets::EditorSlider, AppUiState};
 pub fn rotate_and_crop(
     ui: &mut Ui,
     session: &mut Session,
-    _ui_state: &mut AppUiState,
+    ui_state: &mut AppUiState,
     edit: &mut Edit,
 ) {
     ui.spacing_mut().slider_width = ui.available_width() * 0.6;
@@ -73,5 +75,25 @@ pub fn rotate_and_crop(
         );
     });
 
+    ui.horizontal(|ui| {
+        ui.toggle_value(&mut ui_state.straighten_tool_enabled, "Straighten")
+            .on_hover_text("Draw a line along the horizon to level the image");
+        if ui.button("Auto").clicked() {
+            ui_state.pending_leveling_rotation =
+                Some(session.toolbox.detect_leveling_rotation(input_image.clone()));
+        }
+    });
+
+    if let Some(ref mut pending) = ui_state.pending_leveling_rotation {
+        if let Some(detected) = pending.poll_value() {
+            if let Some(detected_degrees) = *detected {
+                // keep any intentional 90 degree rotations
+                let delta = get_deviation_from_right_angle(detected_degrees - rotation_degrees);
+                rotation_degrees = wrap_rotation_degrees(rotation_degrees + delta);
+            }
+            ui_state.pending_leveling_rotation = None;
+        }
+    }
+
     handle_new_rotation(input_image.aspect_ratio(), edit, rotation_degrees);
 }
@@ -0,0 +1,143 @@
+use eframe::{
+    egui::{self, CollapsingHeader, ColorImage, TextureOptions, Ui},
+    epaint::{Color32, Stroke},
+};
+use salon_core::engine::common::{ImageScope, ScopeKind};
+use salon_core::session::Session;
+
+use super::AppUiState;
+
+pub fn scopes(ui: &mut Ui, session: &mut Session, ui_state: &mut AppUiState) {
+    CollapsingHeader::new("Scopes")
+        .default_open(false)
+        .show(ui, |ui| {
+            let mut scope = session.editor.scope();
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut scope, None, "Off");
+                ui.selectable_value(&mut scope, Some(ScopeKind::LumaWaveform), "Waveform");
+                ui.selectable_value(&mut scope, Some(ScopeKind::RgbParade), "Parade");
+                ui.selectable_value(&mut scope, Some(ScopeKind::Vectorscope), "Vectorscope");
+            });
+            // computed along with the histogram, so that it's updated as the edit changes
+            session.editor.set_scope(scope);
+
+            let Some(context) = session.editor.current_edit_context_mut() else {
+                return;
+            };
+            let Some(ref mut result) = context.current_result else {
+                return;
+            };
+            let Some(ref mut scope_final) = result.scope_final else {
+                return;
+            };
+            if scope_final.poll_value().is_none() {
+                return;
+            }
+            // until the reader has read its own value, it holds the previous one, which is already in the texture
+            let read_uuid = (!scope_final.pending_read()).then(|| scope_final.uuid());
+            let image_scope = scope_final.poll_value().unwrap();
+
+            let has_new_value =
+                read_uuid.is_some() && read_uuid != ui_state.scope_texture_reader_uuid;
+            if has_new_value || ui_state.scope_texture.is_none() {
+                let color_image = scope_to_color_image(image_scope);
+                match ui_state.scope_texture {
+                    Some(ref mut texture) => texture.set(color_image, TextureOptions::LINEAR),
+                    None => {
+                        ui_state.scope_texture = Some(ui.ctx().load_texture(
+                            "scope",
+                            color_image,
+                            TextureOptions::LINEAR,
+                        ))
+                    }
+                }
+                ui_state.scope_texture_reader_uuid = read_uuid;
+            }
+            let texture = ui_state.scope_texture.as_ref().unwrap();
+
+            let height = ui_state.last_frame_size.unwrap().1 * 0.15;
+            let size = if image_scope.kind == ScopeKind::Vectorscope {
+                egui::Vec2::splat(height)
+            } else {
+                egui::vec2(ui.available_width(), height)
+            };
+            ui.vertical_centered(|ui| {
+                let response = ui.image((texture.id(), size));
+                draw_graticule(ui, response.rect, image_scope.kind);
+            });
+        });
+}
+
+fn scope_to_color_image(scope: &ImageScope) -> ColorImage {
+    let (w, h) = scope.dimensions;
+    // log scale, so that sparse parts of the image are still visible
+    let max = (scope.max_count().max(1) as f32).ln_1p();
+    let mut pixels = Vec::with_capacity((w * h) as usize);
+    for row in 0..h {
+        // the first row of the scope is the bottom one
+        let y = h - 1 - row;
+        for x in 0..w {
+            let intensity = (scope.count(x, y) as f32).ln_1p() / max;
+            let color = match scope.kind {
+                ScopeKind::LumaWaveform => [220, 220, 220],
+                ScopeKind::RgbParade => match 3 * x / w {
+                    0 => [255, 60, 40],
+                    1 => [60, 230, 40],
+                    _ => [60, 90, 255],
+                },
+                ScopeKind::Vectorscope => vectorscope_cell_color(x, y, w),
+            };
+            pixels.push(Color32::from_rgb(
+                (color[0] as f32 * intensity) as u8,
+                (color[1] as f32 * intensity) as u8,
+                (color[2] as f32 * intensity) as u8,
+            ));
+        }
+    }
+    ColorImage {
+        size: [w as usize, h as usize],
+        pixels,
+    }
+}
+
+// the (sRGB) color of mid-gray luminance with the Cb and Cr of a cell, so that hues are recognizable
+fn vectorscope_cell_color(x: u32, y: u32, size: u32) -> [u8; 3] {
+    let cb = x as f32 / (size - 1) as f32 - 0.5;
+    let cr = y as f32 / (size - 1) as f32 - 0.5;
+    let luma = 0.5;
+    let r = luma + 1.5748 * cr;
+    let b = luma + 1.8556 * cb;
+    let g = (luma - 0.2126 * r - 0.0722 * b) / 0.7152;
+    // brightened, as the scope is mostly dark
+    [r, g, b].map(|c| ((c + 0.25).clamp(0.0, 1.0) * 255.0) as u8)
+}
+
+fn draw_graticule(ui: &mut Ui, rect: egui::Rect, kind: ScopeKind) {
+    let painter = ui.painter_at(rect);
+    let stroke = Stroke::new(1.0, Color32::from_gray(70));
+    match kind {
+        ScopeKind::LumaWaveform | ScopeKind::RgbParade => {
+            // every 25%
+            for i in 1..4 {
+                let y = rect.bottom() - rect.height() * i as f32 / 4.0;
+                painter.hline(rect.x_range(), y, stroke);
+            }
+            if kind == ScopeKind::RgbParade {
+                for i in 1..3 {
+                    let x = rect.left() + rect.width() * i as f32 / 3.0;
+                    painter.vline(x, rect.y_range(), stroke);
+                }
+            }
+        }
+        ScopeKind::Vectorscope => {
+            let center = rect.center();
+            painter.hline(rect.x_range(), center.y, stroke);
+            painter.vline(center.x, rect.y_range(), stroke);
+            painter.circle_stroke(center, rect.width() * 0.375, stroke);
+            // the skin tone line, at about 123 degrees from the Cb axis
+            let angle = 123f32.to_radians();
+            let end = center + egui::vec2(angle.cos(), -angle.sin()) * rect.width() * 0.5;
+            painter.line_segment([center, end], Stroke::new(1.0, Color32::from_gray(110)));
+        }
+    }
+}
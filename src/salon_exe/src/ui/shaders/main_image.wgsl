@@ -11,6 +11,11 @@ struct Params {
     min_v: f32,
     max_u: f32,
     max_v: f32,
+
+    show_shadow_clipping: u32,
+    show_highlight_clipping: u32,
+    shadow_clipping_color: vec4<f32>,
+    highlight_clipping_color: vec4<f32>,
 };
 
 @group(0) @binding(0)
@@ -60,6 +65,13 @@ fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
         color = linear_to_srgb(color);
     }
 
+    if (params.show_shadow_clipping != 0u || params.show_highlight_clipping != 0u) {
+        // the unfiltered pixel, so that clipped pixels aren't hidden by interpolation with their neighbors
+        let xy = min(vec2<u32>(uv * vec2<f32>(image_size)), image_size - 1u);
+        let pixel = textureLoad(tex, xy, 0).rgb;
+        color = clipping_warning(color, pixel);
+    }
+
     if (params.indicate_mask != 0u) {
         let mask_value = textureSample(mask, tex_sampler, uv).r;
         let mask_color = vec3(1.0, 0.1, 0.1);
@@ -68,3 +80,24 @@ fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
 
     return vec4(color, 1.0);
 }
+
+// pixels with all channels clipped are painted in the warning color,
+// and those with only some channels clipped are tinted with it
+fn clipping_warning(color: vec3<f32>, pixel: vec3<f32>) -> vec3<f32> {
+    var result = color;
+    if (params.show_shadow_clipping != 0u) {
+        let num_clipped = dot(step(pixel, vec3(0.0)), vec3(1.0));
+        if (num_clipped > 0.0) {
+            let strength = select(0.5, 1.0, num_clipped == 3.0);
+            result = mix(result, params.shadow_clipping_color.rgb, strength);
+        }
+    }
+    if (params.show_highlight_clipping != 0u) {
+        let num_clipped = dot(step(vec3(1.0), pixel), vec3(1.0));
+        if (num_clipped > 0.0) {
+            let strength = select(0.5, 1.0, num_clipped == 3.0);
+            result = mix(result, params.highlight_clipping_color.rgb, strength);
+        }
+    }
+    return result;
+}
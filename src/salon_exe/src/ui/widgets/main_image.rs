@@ -14,17 +14,20 @@ use salon_core::runtime::{BufferProperties, RingBuffer};
 use salon_core::shader::{Shader, ShaderLibraryModule};
 
 use crate::ui::utils::get_max_image_size;
-use crate::ui::MainImageZoom;
+use crate::ui::{ClippingWarning, MainImageZoom};
 
 pub struct MainImageCallback {
     pub image: Arc<Image>,
     pub mask: Option<Arc<Image>>,
     pub zoom: Option<MainImageZoom>,
     pub ui_max_rect: egui::Rect,
+    // computed against the (linear) image itself, so that values out of the displayable range are caught
+    pub clipping_warning: Option<ClippingWarning>,
 }
 
 impl MainImageCallback {
-    fn full_image_ui_rect(&self) -> egui::Rect {
+    // the rect of the whole image, which extends beyond the ui when zoomed-in
+    pub fn full_image_ui_rect(&self) -> egui::Rect {
         let mut zoom_ratio = 1.0;
         let mut translation = egui::Vec2 { x: 0.0, y: 0.0 };
 
@@ -100,7 +103,10 @@ impl MainImageRenderResources {
         let ring_buffer = RingBuffer::new(
             runtime.clone(),
             BufferProperties {
-                size: size_of::<u32>() * 2 + size_of::<f32>() * 4,
+                size: size_of::<u32>() * 2
+                    + size_of::<f32>() * 4
+                    + size_of::<u32>() * 2
+                    + size_of::<f32>() * 8,
                 host_readable: false,
             },
         );
@@ -159,6 +165,34 @@ impl MainImageRenderResources {
             bytemuck::cast_slice(&[min_uv.x as f32, min_uv.y, max_uv.x, max_uv.y]),
         );
 
+        let clipping_warning = render_call
+            .clipping_warning
+            .unwrap_or(ClippingWarning::new());
+        let to_rgba = |c: egui::Color32| {
+            [
+                c.r() as f32 / 255.0,
+                c.g() as f32 / 255.0,
+                c.b() as f32 / 255.0,
+                1.0,
+            ]
+        };
+        queue.write_buffer(
+            &buffer.buffer,
+            (size_of::<u32>() * 2 + size_of::<f32>() * 4) as u64,
+            bytemuck::cast_slice(&[
+                clipping_warning.show_shadows as u32,
+                clipping_warning.show_highlights as u32,
+            ]),
+        );
+        queue.write_buffer(
+            &buffer.buffer,
+            (size_of::<u32>() * 4 + size_of::<f32>() * 4) as u64,
+            bytemuck::cast_slice(&[
+                to_rgba(clipping_warning.shadow_color),
+                to_rgba(clipping_warning.highlight_color),
+            ]),
+        );
+
         let bind_group_desc = BindGroupDescriptor {
             entries: vec![
                 BindGroupEntry {
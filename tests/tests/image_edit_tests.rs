@@ -35,7 +35,7 @@ fn run_test(test_context: &mut TestContext, test: ImageEditTest) {
 
     let edit_json_str =
         std::fs::read_to_string(&test.edit_json_path).expect("failed to read edit json");
-    let edit = serde_json::from_str::<Edit>(edit_json_str.as_str())
+    let edit = Edit::from_persisted_json_str(edit_json_str.as_str())
         .expect("failed to parse edit json str");
 
     session.editor.update_transient_edit(edit, false);